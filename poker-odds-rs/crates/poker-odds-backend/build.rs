@@ -0,0 +1,15 @@
+// works the evaluator's lookup tables out once per build, so a run only
+// has to check them. see src/tables.rs.
+#[allow(dead_code)]
+#[path = "src/tables.rs"]
+mod tables;
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/tables.rs");
+    let out: PathBuf = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("tables.bin");
+    fs::write(out, tables::to_bytes(&tables::generate())).unwrap();
+}
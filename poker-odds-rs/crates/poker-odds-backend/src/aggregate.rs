@@ -0,0 +1,305 @@
+use crate::deck::Combinations;
+use crate::error::Error;
+use crate::flops::{canonical, live_weights, solve_flops, symmetries};
+use crate::range::Range;
+use crate::solver::parse_board;
+use crate::texture::Texture;
+use poker_odds_core::{card_names, VALUES};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::fmt;
+
+// the flops a report goes over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlopSubset {
+    // all 22100 of them.
+    All,
+    // `flops` different ones at random, for a quicker and rougher report.
+    Sample { flops: usize, seed: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Suits {
+    Rainbow,
+    TwoTone,
+    Monotone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pairing {
+    Unpaired,
+    Paired,
+    Trips,
+}
+
+// the kind of flop it is, by what aggregate reports group flops on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlopClass {
+    // the highest card's value, from 0 for a deuce to 12 for an ace.
+    pub high: usize,
+    pub suits: Suits,
+    pub pairing: Pairing,
+    // three values within a straight's five.
+    pub connected: bool,
+}
+
+impl FlopClass {
+    pub fn of(flop: &str) -> Result<Self, Error> {
+        let board: u64 = parse_board(flop)?;
+        if board.count_ones() != 3 {
+            return Err(Error::InvalidBoard(flop.to_string()));
+        }
+        Ok(FlopClass::of_mask(board))
+    }
+
+    fn of_mask(flop: u64) -> Self {
+        let values: Vec<usize> = (0..13).filter(|v| flop >> (4 * v) & 0xf != 0).collect();
+        let suits: usize = (0..4)
+            .filter(|s| (0..13).any(|v| flop >> (4 * v + s) & 1 == 1))
+            .count();
+        FlopClass {
+            high: *values.last().unwrap(),
+            suits: match suits {
+                1 => Suits::Monotone,
+                2 => Suits::TwoTone,
+                _ => Suits::Rainbow,
+            },
+            pairing: match values.len() {
+                1 => Pairing::Trips,
+                2 => Pairing::Paired,
+                _ => Pairing::Unpaired,
+            },
+            connected: Texture::of_mask(flop).straight_possible,
+        }
+    }
+
+    // the groups the flop is counted in, e.g. "A high", "two-tone",
+    // "unpaired" and "disconnected".
+    pub fn groups(&self) -> [&'static str; 4] {
+        [
+            // nine high and lower are one group.
+            GROUPS[12 - self.high.max(7)],
+            match self.suits {
+                Suits::Rainbow => "rainbow",
+                Suits::TwoTone => "two-tone",
+                Suits::Monotone => "monotone",
+            },
+            match self.pairing {
+                Pairing::Unpaired => "unpaired",
+                Pairing::Paired => "paired",
+                Pairing::Trips => "trips",
+            },
+            match self.connected {
+                true => "connected",
+                false => "disconnected",
+            },
+        ]
+    }
+}
+
+// every group of a report, in the order it lists them.
+const GROUPS: [&str; 14] = [
+    "A high",
+    "K high",
+    "Q high",
+    "J high",
+    "T high",
+    "9 high or lower",
+    "rainbow",
+    "two-tone",
+    "monotone",
+    "unpaired",
+    "paired",
+    "trips",
+    "connected",
+    "disconnected",
+];
+
+// one flop of a report, standing for the flops that only differ from it in
+// suits the two ranges treat alike.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlopEquity {
+    // e.g. "Th7h2c".
+    pub flop: String,
+    pub class: FlopClass,
+    // how many of the report's flops it stands for.
+    pub count: usize,
+    // None when no two combos of the ranges can be dealt on it.
+    pub equity: Option<f32>,
+}
+
+// the hero range's equity in one group of flops.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlopGroup {
+    // e.g. "monotone".
+    pub name: String,
+    pub flops: usize,
+    // of the report's flops.
+    pub share: f32,
+    // the average over its flops, each as often as it comes. None when no
+    // two combos can be dealt on any of them.
+    pub equity: Option<f32>,
+}
+
+// one range's equity against another over every flop or a sample of them,
+// each solved over all turns and rivers, and averaged by the kind of flop:
+// its high card, suits, pairing and whether it's connected.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlopReport {
+    pub hero: Range,
+    pub villain: Range,
+    pub subset: FlopSubset,
+    // highest flops first.
+    pub flops: Vec<FlopEquity>,
+}
+
+impl FlopReport {
+    // every flop for QQ+,AK against TT+,AQ+ takes ~5s on one core, a
+    // sample of a hundred a fraction of a second.
+    pub fn new(hero: &Range, villain: &Range, subset: FlopSubset) -> Result<Self, Error> {
+        let (heroes, villains) = (live_weights(hero)?, live_weights(villain)?);
+        let symmetries: Vec<[usize; 4]> = symmetries(&heroes, &villains);
+        let mut flops: Vec<u64> = Combinations::new((0..52).collect(), 3).collect();
+        if let FlopSubset::Sample { flops: n, seed } = subset {
+            flops.shuffle(&mut StdRng::seed_from_u64(seed));
+            flops.truncate(n.max(1));
+        }
+        let mut counts: HashMap<u64, usize> = HashMap::new();
+        for flop in flops {
+            *counts.entry(canonical(flop, &symmetries)).or_default() += 1;
+        }
+        let mut solved: Vec<u64> = counts.keys().copied().collect();
+        solved.sort_unstable_by(|a, b| b.cmp(a));
+        let equity: HashMap<u64, Option<f32>> = solve_flops(heroes, villains, solved.clone());
+
+        let flops: Vec<FlopEquity> = solved
+            .into_iter()
+            .map(|flop| FlopEquity {
+                flop: flop_name(flop),
+                class: FlopClass::of_mask(flop),
+                count: counts[&flop],
+                equity: equity[&flop],
+            })
+            .collect();
+        Ok(FlopReport {
+            hero: hero.clone(),
+            villain: villain.clone(),
+            subset,
+            flops,
+        })
+    }
+
+    // the equity over all the report's flops.
+    pub fn equity(&self) -> Option<f32> {
+        average(self.flops.iter())
+    }
+
+    // the groups in the order "A high" to "9 high or lower", rainbow to
+    // monotone, unpaired to trips, connected and disconnected, leaving out
+    // the ones no flop fell in.
+    pub fn groups(&self) -> Vec<FlopGroup> {
+        let total: usize = self.flops.iter().map(|f| f.count).sum();
+        GROUPS
+            .iter()
+            .filter_map(|&name| {
+                let flops: Vec<&FlopEquity> = self
+                    .flops
+                    .iter()
+                    .filter(|f| f.class.groups().contains(&name))
+                    .collect();
+                let count: usize = flops.iter().map(|f| f.count).sum();
+                (count > 0).then(|| FlopGroup {
+                    name: name.to_string(),
+                    flops: count,
+                    share: count as f32 / total as f32,
+                    equity: average(flops.into_iter()),
+                })
+            })
+            .collect()
+    }
+
+    // a line per flop: flop, high card, suits, pairing, whether connected,
+    // how many flops it stands for and equity, empty when nothing can be
+    // dealt on it.
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = String::from("flop,high,suits,pairing,connected,count,equity\n");
+        for f in &self.flops {
+            let [_, suits, pairing, _] = f.class.groups();
+            let equity: String = f.equity.map(|e| format!("{:.4}", e)).unwrap_or_default();
+            csv += &format!(
+                "{},{},{},{},{},{},{}\n",
+                f.flop,
+                VALUES[f.class.high] as char,
+                suits,
+                pairing,
+                f.class.connected,
+                f.count,
+                equity
+            );
+        }
+        csv
+    }
+
+    // a line per group: name, flops, share and equity.
+    pub fn groups_csv(&self) -> String {
+        let mut csv: String = String::from("group,flops,share,equity\n");
+        for g in self.groups() {
+            let equity: String = g.equity.map(|e| format!("{:.4}", e)).unwrap_or_default();
+            csv += &format!("{},{},{:.4},{}\n", g.name, g.flops, g.share, equity);
+        }
+        csv
+    }
+}
+
+impl fmt::Display for FlopReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: usize = self.flops.iter().map(|f| f.count).sum();
+        writeln!(
+            f,
+            "Equity (%) of {} vs {} over {} flops",
+            self.hero, self.villain, total
+        )?;
+        let percent = |e: Option<f32>| e.map_or("-".to_string(), |e| format!("{:.1}", e * 100.));
+        for g in self.groups() {
+            writeln!(
+                f,
+                "{:<16}{:>6.1}% of flops{:>8}",
+                g.name,
+                g.share * 100.,
+                percent(g.equity)
+            )?;
+        }
+        writeln!(f, "{:<16}{:>22}", "all flops", percent(self.equity()))
+    }
+}
+
+// the equity of `flops`, each counted as often as it comes.
+fn average<'a>(flops: impl Iterator<Item = &'a FlopEquity>) -> Option<f32> {
+    let (mut sum, mut count): (f64, f64) = (0., 0.);
+    for f in flops {
+        if let Some(e) = f.equity {
+            sum += e as f64 * f.count as f64;
+            count += f.count as f64;
+        }
+    }
+    (count > 0.).then(|| (sum / count) as f32)
+}
+
+// the flop's cards, highest first.
+fn flop_name(flop: u64) -> String {
+    let mut cards: Vec<String> = (0..52)
+        .filter(|i| flop >> i & 1 == 1)
+        .map(|i| card_names(1 << i))
+        .collect();
+    cards.reverse();
+    cards.concat()
+}
@@ -0,0 +1,121 @@
+use std::simd::cmp::{SimdOrd, SimdPartialEq, SimdPartialOrd};
+use std::simd::num::SimdUint;
+use std::simd::{u64x16, Select};
+
+// boards scored per pass.
+pub const LANES: usize = 16;
+
+// scores sixteen seven card hands at once, one per lane, so that a higher
+// score is a better hand. each lane goes through every check and the
+// answer is picked with selects instead of an if-else ladder.
+#[inline(always)]
+pub fn scores(cards: u64x16) -> u64x16 {
+    let zero: u64x16 = u64x16::splat(0);
+    let s0: u64x16 = suit(cards, 0);
+    let s1: u64x16 = suit(cards, 1);
+    let s2: u64x16 = suit(cards, 2);
+    let s3: u64x16 = suit(cards, 3);
+    let ranks: u64x16 = s0 | s1 | s2 | s3;
+
+    // count the cards of each rank with a bit-sliced adder.
+    let (t1, c1) = (s0 ^ s1, s0 & s1);
+    let (t2, c2) = (s2 ^ s3, s2 & s3);
+    let ones: u64x16 = t1 ^ t2;
+    let c3: u64x16 = t1 & t2;
+    let twos: u64x16 = c1 ^ c2 ^ c3;
+    let quads: u64x16 = c1 & c2;
+    let trips: u64x16 = twos & ones;
+    let pairs: u64x16 = twos & !ones;
+
+    // seven cards hold at most one suit with five or more.
+    let mut flush: u64x16 = zero;
+    for s in [s0, s1, s2, s3] {
+        flush = s.count_ones().simd_ge(u64x16::splat(5)).select(s, flush);
+    }
+
+    let mut score: u64x16 = make(0, top(ranks, 5), zero);
+
+    let pair: u64x16 = top(pairs, 1);
+    score = pairs
+        .simd_ne(zero)
+        .select(make(1, pair, top(ranks & !pair, 3)), score);
+
+    let two: u64x16 = top(pairs, 2);
+    score = two
+        .count_ones()
+        .simd_eq(u64x16::splat(2))
+        .select(make(2, two, top(ranks & !two, 1)), score);
+
+    let trip: u64x16 = top(trips, 1);
+    score = trips
+        .simd_ne(zero)
+        .select(make(3, trip, top(ranks & !trip, 2)), score);
+
+    let straight: u64x16 = straight_high(ranks);
+    score = straight
+        .simd_ne(zero)
+        .select(make(4, straight, zero), score);
+
+    score = flush
+        .simd_ne(zero)
+        .select(make(5, top(flush, 5), zero), score);
+
+    let full: u64x16 = top((trips & !trip) | pairs, 1);
+    score = (trips.simd_ne(zero) & full.simd_ne(zero)).select(make(6, trip, full), score);
+
+    score = quads
+        .simd_ne(zero)
+        .select(make(7, quads, top(ranks & !quads, 1)), score);
+
+    let straight_flush: u64x16 = straight_high(flush);
+    straight_flush
+        .simd_ne(zero)
+        .select(make(8, straight_flush, zero), score)
+}
+
+// the 13 bit rank mask of one suit. card i is rank i / 4 of suit i % 4,
+// so the suit's cards are every fourth bit which get packed together.
+#[inline(always)]
+fn suit(cards: u64x16, s: u64) -> u64x16 {
+    let mut x: u64x16 = (cards >> u64x16::splat(s)) & u64x16::splat(0x1111_1111_1111_1111);
+    x = (x | (x >> u64x16::splat(3))) & u64x16::splat(0x0303_0303_0303_0303);
+    x = (x | (x >> u64x16::splat(6))) & u64x16::splat(0x000F_000F_000F_000F);
+    x = (x | (x >> u64x16::splat(12))) & u64x16::splat(0x0000_00FF_0000_00FF);
+    (x | (x >> u64x16::splat(24))) & u64x16::splat(0xFFFF)
+}
+
+// one above the top rank of the best straight in a rank mask, 0 if none.
+#[inline(always)]
+fn straight_high(ranks: u64x16) -> u64x16 {
+    // the ace also plays below the deuce.
+    let m: u64x16 = (ranks << u64x16::splat(1)) | ((ranks >> u64x16::splat(12)) & u64x16::splat(1));
+    let runs: u64x16 = m
+        & (m >> u64x16::splat(1))
+        & (m >> u64x16::splat(2))
+        & (m >> u64x16::splat(3))
+        & (m >> u64x16::splat(4));
+    let zero: u64x16 = u64x16::splat(0);
+    runs.simd_ne(zero)
+        .select(u64x16::splat(68) - runs.leading_zeros(), zero)
+}
+
+// keeps the n highest ranks of each lane's mask.
+#[inline(always)]
+fn top(ranks: u64x16, n: usize) -> u64x16 {
+    let zero: u64x16 = u64x16::splat(0);
+    let mut m: u64x16 = ranks;
+    let mut out: u64x16 = zero;
+    for _ in 0..n {
+        let shift: u64x16 = u64x16::splat(63) - m.leading_zeros().simd_min(u64x16::splat(63));
+        let high: u64x16 = m.simd_ne(zero).select(u64x16::splat(1) << shift, zero);
+        out |= high;
+        m &= !high;
+    }
+    out
+}
+
+// category in the top bits, then two 13 bit tie breakers.
+#[inline(always)]
+fn make(category: u64, a: u64x16, b: u64x16) -> u64x16 {
+    u64x16::splat(category << 26) | (a << u64x16::splat(13)) | b
+}
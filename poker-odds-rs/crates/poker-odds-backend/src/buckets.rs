@@ -0,0 +1,270 @@
+use crate::deck::{sample_runouts, Combinations};
+use crate::error::Error;
+use crate::matrix::{RunoutScorer, Score};
+use crate::solver::{all_runouts, run_workers, SolveMode};
+use poker_odds_core::card_names;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+// what hands are bucketed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Metric {
+    // expected hand strength, see HandStrength.
+    #[default]
+    Ehs,
+    // its square averaged over the runouts, which sets draws apart from
+    // made hands of the same strength.
+    Ehs2,
+}
+
+// how the metric is cut into buckets. either way a bucket means the same on
+// every board, higher buckets for stronger hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bucketing {
+    // equal slices of 0 to 1.
+    #[default]
+    Width,
+    // equal numbers of hands over all the boards.
+    Percentile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BucketOptions {
+    // the cards on the boards, 3 for flops to 5 for rivers.
+    pub cards: usize,
+    // how many different boards to sample.
+    pub boards: usize,
+    pub buckets: usize,
+    pub metric: Metric,
+    pub bucketing: Bucketing,
+    // picks the boards, and the runouts in monte carlo mode.
+    pub seed: u64,
+}
+
+impl Default for BucketOptions {
+    fn default() -> Self {
+        BucketOptions {
+            cards: 3,
+            boards: 100,
+            buckets: 50,
+            metric: Metric::Ehs,
+            bucketing: Bucketing::Width,
+            seed: 0,
+        }
+    }
+}
+
+// one hand on one board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BucketedHand {
+    pub cards: u64,
+    pub ehs: f32,
+    pub ehs2: f32,
+    pub bucket: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardBuckets {
+    pub board: u64,
+    // every hand that shares no card with the board, in hand index order.
+    pub hands: Vec<BucketedHand>,
+}
+
+// hands bucketed by strength on sampled boards, for card abstractions like
+// the ones CFR bots are built on.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EquityBuckets {
+    pub options: BucketOptions,
+    pub boards: Vec<BoardBuckets>,
+}
+
+// the start of a bucket file, and its version.
+const BUCKETS_MAGIC: &[u8; 4] = b"PEB1";
+
+// a hand that shares a card with the board, in the binary format.
+pub const NO_BUCKET: u16 = u16::MAX;
+
+impl EquityBuckets {
+    // scores every hand on each board over its runouts in `mode`, the boards
+    // split over `nthreads` workers.
+    pub(crate) fn new(
+        options: BucketOptions,
+        mode: SolveMode,
+        nthreads: usize,
+    ) -> Result<Self, Error> {
+        if !(3..=5).contains(&options.cards) {
+            return Err(Error::InvalidBoard(format!("{} cards", options.cards)));
+        }
+        let boards: Arc<Vec<u64>> = Arc::new(sample_boards(&options));
+        let workers: Vec<_> = (0..nthreads)
+            .map(|t| {
+                let boards = boards.clone();
+                move |report: &mut dyn FnMut((usize, Strengths))| {
+                    for (i, &board) in boards.iter().enumerate().skip(t).step_by(nthreads) {
+                        let seed: u64 = options.seed.wrapping_add(i as u64);
+                        report((i, strengths(board, options.cards, mode, seed)));
+                    }
+                }
+            })
+            .collect();
+        let mut strengths: Vec<Strengths> = vec![Vec::new(); boards.len()];
+        run_workers(workers, |(i, s): (usize, Strengths)| strengths[i] = s);
+
+        let metric = |&(ehs, ehs2): &(f32, f32)| match options.metric {
+            Metric::Ehs => ehs,
+            Metric::Ehs2 => ehs2,
+        };
+        let buckets: usize = options.buckets.clamp(1, NO_BUCKET as usize);
+        // the metric each bucket from the second on starts at.
+        let edges: Vec<f32> = match options.bucketing {
+            Bucketing::Width => (1..buckets).map(|b| b as f32 / buckets as f32).collect(),
+            Bucketing::Percentile => {
+                let mut all: Vec<f32> = strengths.iter().flatten().map(metric).collect();
+                all.sort_unstable_by(f32::total_cmp);
+                (1..buckets).map(|b| all[b * all.len() / buckets]).collect()
+            }
+        };
+
+        let boards: Vec<BoardBuckets> = boards
+            .iter()
+            .zip(strengths)
+            .map(|(&board, strengths)| BoardBuckets {
+                board,
+                hands: live_hands(board)
+                    .into_iter()
+                    .zip(strengths)
+                    .map(|(cards, s)| BucketedHand {
+                        cards,
+                        ehs: s.0,
+                        ehs2: s.1,
+                        bucket: edges.partition_point(|&e| e <= metric(&s)) as u16,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(EquityBuckets { options, boards })
+    }
+
+    // a line per hand and board: board, hand, ehs, ehs2 and bucket.
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = String::from("board,hand,ehs,ehs2,bucket\n");
+        for b in &self.boards {
+            let board: String = card_names(b.board);
+            for h in &b.hands {
+                csv += &format!(
+                    "{},{},{:.4},{:.4},{}\n",
+                    board,
+                    card_names(h.cards),
+                    h.ehs,
+                    h.ehs2,
+                    h.bucket
+                );
+            }
+        }
+        csv
+    }
+
+    // "PEB1", the cards per board as a byte, the buckets as a u16 and the
+    // boards as a u32, then for each board its cards, a byte each from 0
+    // for 2c, 1 for 2h, 2 for 2s and 3 for 2d to 51 for Ad, and the bucket of every one of the 1326 hands as
+    // a u16, NO_BUCKET for hands sharing a card with the board. a hand's
+    // place is that of its two cards (a, b), a < b, counting up from (0, 1),
+    // (0, 2) to (50, 51). numbers are little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = BUCKETS_MAGIC.to_vec();
+        bytes.push(self.options.cards as u8);
+        let buckets: usize = self.options.buckets.clamp(1, NO_BUCKET as usize);
+        bytes.extend((buckets as u16).to_le_bytes());
+        bytes.extend((self.boards.len() as u32).to_le_bytes());
+        for b in &self.boards {
+            bytes.extend((0..52u8).filter(|&i| b.board >> i & 1 == 1));
+            let mut hands = b.hands.iter().peekable();
+            for hand in Combinations::new((0..52).collect(), 2) {
+                let bucket: u16 = match hands.next_if(|h| h.cards == hand) {
+                    Some(h) => h.bucket,
+                    None => NO_BUCKET,
+                };
+                bytes.extend(bucket.to_le_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+// `options.boards` different boards at random, as many as there are at most.
+fn sample_boards(options: &BucketOptions) -> Vec<u64> {
+    let mut rng: StdRng = StdRng::seed_from_u64(options.seed);
+    let mut deck: Vec<usize> = (0..52).collect();
+    let mut seen: HashSet<u64> = HashSet::new();
+    let mut boards: Vec<u64> = Vec::new();
+    // there are 22100 flops, and far more turns and rivers.
+    let target: usize = match options.cards {
+        3 => options.boards.min(22100),
+        _ => options.boards,
+    };
+    while boards.len() < target {
+        let (dealt, _) = deck.partial_shuffle(&mut rng, options.cards);
+        let board: u64 = dealt.iter().fold(0, |acc, c| acc | 1 << c);
+        if seen.insert(board) {
+            boards.push(board);
+        }
+    }
+    boards
+}
+
+// the two card hands that share no card with `board`, in hand index order.
+fn live_hands(board: u64) -> Vec<u64> {
+    Combinations::new((0..52).collect(), 2)
+        .filter(|&hand| hand & board == 0)
+        .collect()
+}
+
+// the ehs and ehs2 of every live hand on a board, in hand index order.
+type Strengths = Vec<(f32, f32)>;
+
+// the strengths on `board`, over all its runouts or the mode's sample of
+// them.
+fn strengths(board: u64, cards: usize, mode: SolveMode, seed: u64) -> Strengths {
+    let hands: Vec<(u64, f32)> = live_hands(board).into_iter().map(|h| (h, 1.)).collect();
+    let deck: Vec<usize> = (0..52).filter(|&i| board & 1 << i == 0).collect();
+    let mut runouts: Vec<u64> = Vec::new();
+    match mode {
+        SolveMode::MonteCarlo { samples } => {
+            runouts = sample_runouts(&deck, board, 5 - cards, samples, seed)
+        }
+        SolveMode::Exact | SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => {
+            all_runouts(&deck, 5 - cards, board, &mut runouts)
+        }
+    }
+
+    // the hands are played against each other, every one standing for the
+    // random opponent's holding too.
+    let mut scorer: RunoutScorer = RunoutScorer::new(&hands, &hands);
+    let mut sums: Vec<(f64, f64, u32)> = vec![(0., 0., 0); hands.len()];
+    let mut scores: Vec<Score> = vec![Score::default(); hands.len()];
+    for runout in runouts {
+        scores.fill(Score::default());
+        scorer.add(runout, &mut scores);
+        for (sum, s) in sums.iter_mut().zip(scores.iter()) {
+            if s.against > 0. {
+                let strength: f64 = s.won / s.against;
+                *sum = (sum.0 + strength, sum.1 + strength * strength, sum.2 + 1);
+            }
+        }
+    }
+    sums.iter()
+        .map(|&(s, sq, n)| {
+            let n: f64 = n.max(1) as f64;
+            ((s / n) as f32, (sq / n) as f32)
+        })
+        .collect()
+}
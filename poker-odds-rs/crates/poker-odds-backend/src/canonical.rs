@@ -0,0 +1,80 @@
+use crate::error::Error;
+use crate::solver::hole_values;
+use poker_odds_core::{card_names, VALUES};
+
+// the 169 starting hands, numbered like the cells of the usual 13x13
+// chart read row by row: aces in the first row and column, pairs on the
+// diagonal, suited hands above it and offsuit hands below.
+pub(crate) const CLASSES: usize = 169;
+
+// the values (deuce 0) of the hand in cell `i`, higher first, and whether
+// it's suited.
+fn cell(i: usize) -> (usize, usize, bool) {
+    let (row, col) = (12 - i / 13, 12 - i % 13);
+    (row.max(col), row.min(col), row > col)
+}
+
+// the cell of the hand with these values, higher first.
+pub(crate) fn cell_at(hi: usize, lo: usize, suited: bool) -> usize {
+    let (row, col) = if suited { (hi, lo) } else { (lo, hi) };
+    (12 - row) * 13 + 12 - col
+}
+
+// e.g. "AKs", "AKo" or "AA".
+pub(crate) fn cell_name(i: usize) -> String {
+    let (hi, lo, suited) = cell(i);
+    let mut name: String = [VALUES[hi] as char, VALUES[lo] as char].iter().collect();
+    if hi != lo {
+        name.push(if suited { 's' } else { 'o' });
+    }
+    name
+}
+
+pub(crate) fn cell_of(name: &str) -> Option<usize> {
+    (0..CLASSES).find(|&i| cell_name(i).eq_ignore_ascii_case(name.trim()))
+}
+
+// the card masks of every combo of the hand in cell `i`.
+pub(crate) fn cell_combos(i: usize) -> Vec<u64> {
+    let (hi, lo, suited) = cell(i);
+    let mut combos: Vec<u64> = Vec::new();
+    for a in 0..4 {
+        for b in 0..4 {
+            let keep: bool = if hi == lo { a < b } else { (a == b) == suited };
+            if keep {
+                combos.push(1 << (hi * 4 + a) | 1 << (lo * 4 + b));
+            }
+        }
+    }
+    combos
+}
+
+// a combo's two cards, the higher value first, pairs in suit order, e.g.
+// "AhKd" or "7c7s".
+pub(crate) fn combo_name(mask: u64) -> String {
+    let low: u64 = mask & mask.wrapping_neg();
+    let high: u64 = mask & !low;
+    match low.trailing_zeros() / 4 == high.trailing_zeros() / 4 {
+        true => card_names(mask),
+        false => card_names(high) + &card_names(low),
+    }
+}
+
+// the 169 starting hands in chart order, "AA", "AKs", "AQs" ... "22".
+pub fn starting_hands() -> Vec<String> {
+    (0..CLASSES).map(cell_name).collect()
+}
+
+// the starting hand two hole cards are one of, e.g. "AKs" for "KhAh",
+// "Q9o" for "9dQc" and "77" for "7s7c".
+pub fn canonical(hand: &str) -> Result<String, Error> {
+    let (hi, lo, suited) = hole_values(hand)?;
+    Ok(cell_name(cell_at(hi as usize - 2, lo as usize - 2, suited)))
+}
+
+// every combo of a starting hand, e.g. the four of "AKs", the twelve of
+// "AKo" and the six of "77", written as in `combo_name`.
+pub fn combos_of(class: &str) -> Result<Vec<String>, Error> {
+    let i: usize = cell_of(class).ok_or_else(|| Error::InvalidRange(class.to_string()))?;
+    Ok(cell_combos(i).into_iter().map(combo_name).collect())
+}
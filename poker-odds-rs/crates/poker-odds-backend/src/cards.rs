@@ -0,0 +1,135 @@
+use crate::error::Error;
+use crate::solver::{hand_mask, parse_board};
+use poker_odds_core::card_names;
+use std::fmt;
+use std::str::FromStr;
+
+// two hole cards, checked once when they're read so solves taking them
+// can't fail on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HoleCards {
+    // one bit a card, by card index.
+    pub(crate) mask: u64,
+}
+
+// zero to five board cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board {
+    pub(crate) mask: u64,
+}
+
+impl HoleCards {
+    // e.g. "AhKh".
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Ok(HoleCards {
+            mask: hand_mask(s)?,
+        })
+    }
+
+    // one bit a card, by card index, as poker_odds_core::card_names takes.
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+}
+
+impl Board {
+    // e.g. "7c8c9c", or "" preflop.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Ok(Board {
+            mask: parse_board(s)?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+}
+
+impl FromStr for HoleCards {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        HoleCards::parse(s)
+    }
+}
+
+impl FromStr for Board {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Board::parse(s)
+    }
+}
+
+// highest card first.
+fn write_cards(f: &mut fmt::Formatter<'_>, mask: u64) -> fmt::Result {
+    for i in (0..52).rev().filter(|i| mask >> i & 1 == 1) {
+        write!(f, "{}", card_names(1 << i))?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for HoleCards {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_cards(f, self.mask)
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_cards(f, self.mask)
+    }
+}
+
+// `n` different cards as a mask, each picked from the ones still left.
+#[cfg(feature = "arbitrary")]
+pub(crate) fn arbitrary_cards(
+    u: &mut arbitrary::Unstructured<'_>,
+    n: u32,
+) -> arbitrary::Result<u64> {
+    let mut mask: u64 = 0;
+    for k in 0..n {
+        let mut pick: u32 = u.int_in_range(0..=51 - k)?;
+        let card: usize = (0..52)
+            .filter(|c| mask >> c & 1 == 0)
+            .find(|_| {
+                pick = pick.wrapping_sub(1);
+                pick == u32::MAX
+            })
+            .unwrap();
+        mask |= 1 << card;
+    }
+    Ok(mask)
+}
+
+// any two different cards. two of them, or one and a Board, can still
+// share a card.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for HoleCards {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(HoleCards {
+            mask: arbitrary_cards(u, 2)?,
+        })
+    }
+}
+
+// a board of some street: none, a flop, a turn or a river.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Board {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let n: u32 = *u.choose(&[0, 3, 4, 5])?;
+        Ok(Board {
+            mask: arbitrary_cards(u, n)?,
+        })
+    }
+}
@@ -0,0 +1,104 @@
+use crate::solver::ladder_score;
+use poker_odds_core::{card_names, describe};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::SeedableRng;
+use std::cmp::Ordering;
+
+// disagreements kept with their cards, the rest are only counted.
+const EXAMPLES: usize = 20;
+
+// another evaluator, e.g. another crate's, to hold ours up against. it gets
+// seven cards as one string, the lowest first, e.g. "2s7c8c9cTdKhAh",
+// which most evaluators read as they are, and only has to order them: a
+// better hand greater, two hands that split equal. any closure from &str
+// to something ordered is one, e.g. with rs_poker
+//
+//     cross_check(|cards: &str| Hand::new_from_str(cards).unwrap().rank(), 100_000, 1)
+pub trait ExternalEvaluator {
+    type Strength: Ord;
+
+    fn strength(&self, cards: &str) -> Self::Strength;
+}
+
+impl<F, S> ExternalEvaluator for F
+where
+    F: Fn(&str) -> S,
+    S: Ord,
+{
+    type Strength = S;
+
+    fn strength(&self, cards: &str) -> S {
+        self(cards)
+    }
+}
+
+// two hands on a board the evaluators don't agree the winner of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Disagreement {
+    pub board: String,
+    pub first: String,
+    pub second: String,
+    // how the first hand does against the second by each: 1 if it wins,
+    // 0 on a split and -1 if it loses.
+    pub ours: i8,
+    pub theirs: i8,
+    // what we make of each, e.g. "flush A K 9 8 7".
+    pub first_hand: String,
+    pub second_hand: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CrossCheck {
+    pub showdowns: u64,
+    pub disagreements: u64,
+    // the first few disagreements found.
+    pub examples: Vec<Disagreement>,
+}
+
+impl CrossCheck {
+    pub fn passed(&self) -> bool {
+        self.disagreements == 0
+    }
+}
+
+// deals `showdowns` random boards with two random hands on each and checks
+// that `external` picks the same winner as we do, or a split where we do.
+// only the order is compared, so the evaluators can score hands however
+// they like.
+pub fn cross_check(external: &impl ExternalEvaluator, showdowns: u64, seed: u64) -> CrossCheck {
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+    let mut result: CrossCheck = CrossCheck {
+        showdowns,
+        ..CrossCheck::default()
+    };
+    for _ in 0..showdowns {
+        let cards: Vec<usize> = index::sample(&mut rng, 52, 9).into_vec();
+        let mask = |cards: &[usize]| cards.iter().fold(0u64, |acc, c| acc | 1 << c);
+        let (board, first, second) = (mask(&cards[..5]), mask(&cards[5..7]), mask(&cards[7..]));
+        let (a, b) = (board | first, board | second);
+        let (ours_a, ours_b) = (ladder_score(a), ladder_score(b));
+        let ours: Ordering = ours_a.cmp(&ours_b);
+        let theirs: Ordering = external
+            .strength(&card_names(a))
+            .cmp(&external.strength(&card_names(b)));
+        if ours == theirs {
+            continue;
+        }
+        result.disagreements += 1;
+        if result.examples.len() < EXAMPLES {
+            result.examples.push(Disagreement {
+                board: card_names(board),
+                first: card_names(first),
+                second: card_names(second),
+                ours: ours as i8,
+                theirs: theirs as i8,
+                first_hand: describe(ours_a),
+                second_hand: describe(ours_b),
+            });
+        }
+    }
+    result
+}
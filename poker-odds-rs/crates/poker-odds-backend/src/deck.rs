@@ -0,0 +1,213 @@
+use crate::cards::{Board, HoleCards};
+use crate::error::Error;
+use crate::solver::{batch_rng, deal, parse_board, SAMPLE_BATCH};
+use poker_odds_core::card_names;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+// the cards left once some are known, in the order they'll be dealt, to
+// fill in hands and boards with at random. monte carlo solves deal their
+// runouts from one of these.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    // card indexes, the top of the deck first.
+    cards: Vec<usize>,
+    rng: StdRng,
+}
+
+impl Deck {
+    // all 52 cards, shuffled in the order `seed` gives, or at random when None.
+    pub fn new(seed: Option<u64>) -> Self {
+        let mut deck: Deck = Deck {
+            cards: (0..52).collect(),
+            rng: StdRng::seed_from_u64(seed.unwrap_or_else(rand::random)),
+        };
+        deck.shuffle();
+        deck
+    }
+
+    // `cards` in that order, unshuffled, for a monte carlo batch.
+    pub(crate) fn batch(cards: &[usize], seed: u64, batch: usize) -> Self {
+        Deck {
+            cards: cards.to_vec(),
+            rng: batch_rng(seed, batch),
+        }
+    }
+
+    pub fn shuffle(&mut self) {
+        self.cards.shuffle(&mut self.rng);
+    }
+
+    // takes `cards`, e.g. a hand or a board, out of the deck, the rest
+    // staying in order. fails on a card that's already out.
+    pub fn remove(&mut self, cards: &str) -> Result<(), Error> {
+        let mask: u64 = parse_board(cards)?;
+        let live: u64 = self.cards.iter().fold(0, |acc, c| acc | 1 << c);
+        if mask & !live != 0 {
+            let taken: u64 = mask & !live;
+            return Err(Error::DuplicateCard(card_names(
+                taken & taken.wrapping_neg(),
+            )));
+        }
+        self.cards.retain(|c| mask >> c & 1 == 0);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    // the `n` cards off the top of the deck, the highest first, e.g.
+    // "AhKd". None when fewer are left.
+    pub fn deal(&mut self, n: usize) -> Option<String> {
+        let cards: u64 = self.take(n)?;
+        Some(
+            (0..52)
+                .rev()
+                .filter(|c| cards >> c & 1 == 1)
+                .map(|c| card_names(1 << c))
+                .collect(),
+        )
+    }
+
+    pub fn deal_hand(&mut self) -> Option<HoleCards> {
+        Some(HoleCards {
+            mask: self.take(2)?,
+        })
+    }
+
+    // `n` board cards, e.g. three for a flop.
+    pub fn deal_board(&mut self, n: usize) -> Option<Board> {
+        Some(Board {
+            mask: self.take(n.min(5))?,
+        })
+    }
+
+    fn take(&mut self, n: usize) -> Option<u64> {
+        if n > self.len() {
+            return None;
+        }
+        Some(self.cards.drain(..n).fold(0, |acc, c| acc | 1 << c))
+    }
+
+    // `n` random cards moved to the top and left in the deck, so the next
+    // draw is from all of them again. a partial shuffle, for sampling.
+    pub(crate) fn draw(&mut self, n: usize) -> &[usize] {
+        deal(0, &mut self.cards, n, &mut self.rng);
+        &self.cards[..n]
+    }
+
+    // `board` finished with random cards, drawn as above.
+    pub(crate) fn runout(&mut self, board: u64, need: usize) -> u64 {
+        deal(board, &mut self.cards, need, &mut self.rng)
+    }
+
+    // `board` finished with `first` and then random cards, for sampling by
+    // the next card.
+    pub(crate) fn runout_from(&mut self, board: u64, first: usize, need: usize) -> u64 {
+        let at: usize = self.cards.iter().position(|&c| c == first).unwrap();
+        self.cards.swap(0, at);
+        deal(
+            board | 1 << first,
+            &mut self.cards[1..],
+            need - 1,
+            &mut self.rng,
+        )
+    }
+
+    // the rng the deck deals with, for picks that go with its runouts.
+    pub(crate) fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    // every way to take `k` of the cards left, each once, the highest card
+    // first, e.g. all 1326 starting hands of a full deck with k = 2.
+    pub fn combinations(&self, k: usize) -> impl Iterator<Item = String> {
+        let mut deck: Vec<usize> = self.cards.clone();
+        deck.sort_unstable();
+        Combinations::new(deck, k).map(|cards| {
+            (0..52)
+                .rev()
+                .filter(|c| cards >> c & 1 == 1)
+                .map(|c| card_names(1 << c))
+                .collect()
+        })
+    }
+}
+
+// the runouts of `board` a monte carlo solve with `seed` deals from `cards`,
+// in fixed batches each drawn from its own deck, so they don't depend on
+// how the batches are shared out.
+pub(crate) fn sample_runouts(
+    cards: &[usize],
+    board: u64,
+    need: usize,
+    samples: usize,
+    seed: u64,
+) -> Vec<u64> {
+    let mut runouts: Vec<u64> = Vec::with_capacity(samples);
+    for b in 0..samples.div_ceil(SAMPLE_BATCH) {
+        let n: usize = (samples - b * SAMPLE_BATCH).min(SAMPLE_BATCH);
+        let mut deck: Deck = Deck::batch(cards, seed, b);
+        for _ in 0..n {
+            runouts.push(deck.runout(board, need));
+        }
+    }
+    runouts
+}
+
+// every way to finish `board` with the cards not in it or in `dead`, e.g.
+// the 990 turns and rivers of a heads up flop. a complete board is its own
+// only runout.
+pub fn runouts(board: &Board, dead: &[HoleCards]) -> impl Iterator<Item = Board> {
+    let dead: u64 = dead.iter().fold(board.mask, |acc, h| acc | h.mask);
+    let deck: Vec<usize> = (0..52).filter(|c| dead >> c & 1 == 0).collect();
+    let board: u64 = board.mask;
+    Combinations::new(deck, 5usize.saturating_sub(board.count_ones() as usize)).map(move |cards| {
+        Board {
+            mask: board | cards,
+        }
+    })
+}
+
+// the `k` card subsets of `deck` as masks, in the order of the positions
+// they're taken from, the first ones first.
+pub(crate) struct Combinations {
+    deck: Vec<usize>,
+    // the positions in `deck` of the next subset, None once they're all out.
+    at: Option<Vec<usize>>,
+}
+
+impl Combinations {
+    pub(crate) fn new(deck: Vec<usize>, k: usize) -> Self {
+        let at: Option<Vec<usize>> = (k <= deck.len()).then(|| (0..k).collect());
+        Combinations { deck, at }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let at: &mut Vec<usize> = self.at.as_mut()?;
+        let cards: u64 = at.iter().fold(0, |acc, &i| acc | 1 << self.deck[i]);
+        // move the last position that can still move up one, and the ones
+        // after it right behind it.
+        let (n, k) = (self.deck.len(), at.len());
+        match (0..k).rev().find(|&j| at[j] < n - k + j) {
+            Some(j) => {
+                at[j] += 1;
+                for l in j + 1..k {
+                    at[l] = at[l - 1] + 1;
+                }
+            }
+            None => self.at = None,
+        }
+        Some(cards)
+    }
+}
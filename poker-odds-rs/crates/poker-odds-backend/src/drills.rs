@@ -0,0 +1,108 @@
+use crate::error::Error;
+use crate::query::Scenario;
+use crate::solver::{Solution, SolveMode, Solver};
+use poker_odds_core::card_names;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+// a question and its answer, for a spaced repetition deck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flashcard {
+    // e.g. "AhKh vs QsQd on Th7h2c".
+    pub front: String,
+    // the first hand's equity, e.g. "46.2%".
+    pub back: String,
+    // the street, "preflop", "flop", "turn" or "river".
+    pub tag: String,
+}
+
+// a flashcard for each matchup, the hands with the board they're on, with
+// the first hand's equity solved in `mode` on the back. the matchups are
+// solved as a batch, see Solver::solve_batch.
+pub fn equity_drills(
+    solver: &Solver,
+    matchups: &[(Vec<String>, String)],
+    mode: SolveMode,
+) -> Result<Vec<Flashcard>, Error> {
+    let scenarios: Vec<Scenario> = matchups
+        .iter()
+        .map(|(hands, board)| Scenario::new(hands, board))
+        .collect();
+    matchups
+        .iter()
+        .zip(solver.solve_batch_in(&scenarios, mode))
+        .map(|((hands, board), solution)| {
+            let solution: Solution = solution?;
+            let (front, tag): (String, &str) = match board.len() / 2 {
+                0 => (hands.join(" vs "), "preflop"),
+                3 => (format!("{} on {}", hands.join(" vs "), board), "flop"),
+                4 => (format!("{} on {}", hands.join(" vs "), board), "turn"),
+                _ => (format!("{} on {}", hands.join(" vs "), board), "river"),
+            };
+            Ok(Flashcard {
+                front,
+                back: format!("{:.1}%", solution.equity * 100.),
+                tag: tag.to_string(),
+            })
+        })
+        .collect()
+}
+
+// one matchup a line, "AhKh QsQd" or "AhKh QsQd on Th7h2c", the way the
+// cards show them. blank lines and lines starting with '#' are skipped.
+pub fn parse_matchups(text: &str) -> Vec<(Vec<String>, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (hands, board) = line.split_once(" on ").unwrap_or((line, ""));
+            let hands: Vec<String> = hands.split_whitespace().map(str::to_string).collect();
+            (hands, board.trim().to_string())
+        })
+        .collect()
+}
+
+// `n` preflop matchups of `players` random hands each, the same ones for
+// the same seed.
+pub fn random_matchups(n: usize, players: usize, seed: u64) -> Vec<(Vec<String>, String)> {
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+    let mut deck: Vec<usize> = (0..52).collect();
+    (0..n)
+        .map(|_| {
+            deck.shuffle(&mut rng);
+            let hands: Vec<String> = deck
+                .chunks(2)
+                .take(players.min(26))
+                // higher card first.
+                .map(|c| card_names(1 << c[0].max(c[1])) + &card_names(1 << c[0].min(c[1])))
+                .collect();
+            (hands, String::new())
+        })
+        .collect()
+}
+
+// the cards as csv that anki imports as they are: front, back and a tag,
+// with the header lines that tell it so.
+pub fn to_csv(cards: &[Flashcard]) -> String {
+    let mut csv: String = String::from("#separator:Comma\n#html:false\n#tags column:3\n");
+    for card in cards {
+        let fields: Vec<String> = [&card.front, &card.back, &card.tag]
+            .iter()
+            .map(|f| csv_field(f))
+            .collect();
+        csv += &fields.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+// quoted when it has to be, with any quotes doubled.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Error {
+    InvalidCard(String),
+    InvalidHand(String),
+    InvalidBoard(String),
+    DuplicateCard(String),
+    PlayerCount(usize),
+    InvalidRange(String),
+    // every hand of the range shares a card with the board or the hero.
+    EmptyRange,
+    // a line of a hand history that couldn't be read.
+    InvalidHistory(String),
+    // a question for `answer_query` that isn't "hand vs hands on board".
+    InvalidQuery(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidCard(s) => write!(f, "'{}' is not a valid card (e.g. Ah, Td, 7c)", s),
+            Error::InvalidHand(s) => write!(f, "'{}' is not a valid hand (e.g. AhKh)", s),
+            Error::InvalidBoard(s) => write!(f, "'{}' is not a valid board (0 to 5 cards)", s),
+            Error::DuplicateCard(s) => write!(f, "{} is dealt more than once", s),
+            Error::PlayerCount(n) => write!(f, "need between 2 and 10 players, got {}", n),
+            Error::InvalidRange(s) => {
+                write!(f, "'{}' is not a valid range (e.g. QQ+, AKs, A5s-A2s)", s)
+            }
+            Error::EmptyRange => write!(f, "no hand of the range can be dealt"),
+            Error::InvalidHistory(s) => write!(f, "couldn't read the hand history at '{}'", s),
+            Error::InvalidQuery(s) => {
+                write!(f, "couldn't read '{}' (e.g. AhKh vs QsQd on 7c8c9c)", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
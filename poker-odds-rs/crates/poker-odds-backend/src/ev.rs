@@ -0,0 +1,36 @@
+// what betting `bet` into `pot` is worth when the villain folds `fold` of
+// the time and otherwise calls, after which the hero wins the pot plus both
+// bets `equity` of the time. in the same units as the pot, counting from
+// before the bet.
+pub fn semi_bluff_ev(pot: f32, bet: f32, fold: f32, equity: f32) -> f32 {
+    fold * pot + (1. - fold) * (equity * (pot + 2. * bet) - bet)
+}
+
+// how often a pure bluff of `bet` into `pot` has to take the pot down
+// right away to break even.
+pub fn break_even_fold(pot: f32, bet: f32) -> f32 {
+    bet / (pot + bet)
+}
+
+// whether a bluff gets through often enough against a range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BluffCheck {
+    // see `break_even_fold`.
+    pub break_even: f32,
+    // the equity against the hero a villain combo needs to continue,
+    // by default the villain's pot odds.
+    pub threshold: f32,
+    // how often the villain's range continues, weighting its combos.
+    pub continues: f32,
+}
+
+impl BluffCheck {
+    pub fn folds(&self) -> f32 {
+        1. - self.continues
+    }
+
+    pub fn profitable(&self) -> bool {
+        self.folds() >= self.break_even
+    }
+}
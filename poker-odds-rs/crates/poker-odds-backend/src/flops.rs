@@ -0,0 +1,144 @@
+use crate::deck::Combinations;
+use crate::error::Error;
+use crate::isomorphism::{permutations, permute};
+use crate::matrix::{RunoutScorer, Score};
+use crate::range::Range;
+use crate::solver::{default_threads, parse_board, run_workers};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// one range's equity against another on every flop, worked out up front so
+// looking a flop up afterwards is instant. flops that only differ in suits
+// the two ranges treat alike are solved once: with ranges written in the
+// usual shorthand that's 1755 of them, with single combos a few thousand.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlopTable {
+    pub hero: Range,
+    pub villain: Range,
+    // the suit relabelings that leave both ranges as they are.
+    symmetries: Vec<[usize; 4]>,
+    // by canonical flop, None when no two combos can be dealt on it.
+    equity: HashMap<u64, Option<f32>>,
+}
+
+impl FlopTable {
+    // solves every distinct flop exactly, over all turns and rivers. a
+    // couple hundred combos against sixty take ~15s on one core.
+    pub fn new(hero: &Range, villain: &Range) -> Result<Self, Error> {
+        let (heroes, villains) = (live_weights(hero)?, live_weights(villain)?);
+        let symmetries: Vec<[usize; 4]> = symmetries(&heroes, &villains);
+        let mut flops: Vec<u64> = Combinations::new((0..52).collect(), 3)
+            .map(|flop| canonical(flop, &symmetries))
+            .collect();
+        flops.sort_unstable();
+        flops.dedup();
+        let equity: HashMap<u64, Option<f32>> = solve_flops(heroes, villains, flops);
+
+        Ok(FlopTable {
+            hero: hero.clone(),
+            villain: villain.clone(),
+            symmetries,
+            equity,
+        })
+    }
+
+    // the flops that were solved.
+    pub fn len(&self) -> usize {
+        self.equity.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.equity.is_empty()
+    }
+
+    // the hero range's equity on `flop`, e.g. "Th7h2c". None when no two
+    // combos of the ranges can be dealt on it.
+    pub fn equity(&self, flop: &str) -> Result<Option<f32>, Error> {
+        let board: u64 = parse_board(flop)?;
+        if board.count_ones() != 3 {
+            return Err(Error::InvalidBoard(flop.to_string()));
+        }
+        Ok(self.equity[&canonical(board, &self.symmetries)])
+    }
+}
+
+// the combos of `range` it plays, with their weights.
+pub(crate) fn live_weights(range: &Range) -> Result<Vec<(u64, f32)>, Error> {
+    let live = range.combos().iter().filter(|c| c.weight > 0.);
+    let weights: Vec<(u64, f32)> = live.map(|c| (c.cards(), c.weight)).collect();
+    match weights.is_empty() {
+        true => Err(Error::EmptyRange),
+        false => Ok(weights),
+    }
+}
+
+// the suit relabelings that leave both sides' combos as they are.
+pub(crate) fn symmetries(heroes: &[(u64, f32)], villains: &[(u64, f32)]) -> Vec<[usize; 4]> {
+    permutations()
+        .into_iter()
+        .filter(|perm| keeps(heroes, perm) && keeps(villains, perm))
+        .collect()
+}
+
+// the equity of `heroes` against `villains` on each of `flops`, over all
+// turns and rivers.
+pub(crate) fn solve_flops(
+    heroes: Vec<(u64, f32)>,
+    villains: Vec<(u64, f32)>,
+    flops: Vec<u64>,
+) -> HashMap<u64, Option<f32>> {
+    let nthreads: usize = default_threads();
+    let (flops, heroes, villains) = (Arc::new(flops), Arc::new(heroes), Arc::new(villains));
+    let workers: Vec<_> = (0..nthreads)
+        .map(|t| {
+            let (flops, heroes, villains) = (flops.clone(), heroes.clone(), villains.clone());
+            move |report: &mut dyn FnMut((u64, Option<f32>))| {
+                for &flop in flops.iter().skip(t).step_by(nthreads) {
+                    report((flop, flop_equity(&heroes, &villains, flop)));
+                }
+            }
+        })
+        .collect();
+    let mut equity: HashMap<u64, Option<f32>> = HashMap::with_capacity(flops.len());
+    run_workers(workers, |(flop, e): (u64, Option<f32>)| {
+        equity.insert(flop, e);
+    });
+    equity
+}
+
+// whether relabeling the suits with `perm` maps the combos onto themselves.
+fn keeps(combos: &[(u64, f32)], perm: &[usize; 4]) -> bool {
+    let weights: HashMap<u64, f32> = combos.iter().copied().collect();
+    combos
+        .iter()
+        .all(|&(cards, w)| weights.get(&permute(cards, perm)) == Some(&w))
+}
+
+pub(crate) fn canonical(flop: u64, symmetries: &[[usize; 4]]) -> u64 {
+    symmetries.iter().map(|p| permute(flop, p)).min().unwrap()
+}
+
+// the weighted equity of every hero combo against every villain combo it
+// doesn't share a card with, over each turn and river.
+fn flop_equity(heroes: &[(u64, f32)], villains: &[(u64, f32)], flop: u64) -> Option<f32> {
+    let heroes: Vec<(u64, f32)> = heroes.iter().copied().filter(|h| h.0 & flop == 0).collect();
+    let villains: Vec<(u64, f32)> = villains
+        .iter()
+        .copied()
+        .filter(|v| v.0 & flop == 0)
+        .collect();
+    let deck: Vec<usize> = (0..52).filter(|&i| flop & 1 << i == 0).collect();
+
+    let mut scorer: RunoutScorer = RunoutScorer::new(&heroes, &villains);
+    let mut scores: Vec<Score> = vec![Score::default(); heroes.len()];
+    for runout in Combinations::new(deck, 2) {
+        scorer.add(flop | runout, &mut scores);
+    }
+    let (mut won, mut total): (f64, f64) = (0., 0.);
+    for (&(_, w), s) in heroes.iter().zip(scores) {
+        won += w as f64 * s.won;
+        total += w as f64 * s.against;
+    }
+    (total > 0.).then(|| (won / total) as f32)
+}
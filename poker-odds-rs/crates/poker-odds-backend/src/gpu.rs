@@ -0,0 +1,187 @@
+use futures_lite::future::block_on;
+use log::{debug, warn};
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+// runouts per dispatch. keeps each upload well under the storage buffer
+// limits of even the smallest adapters, and the split pot shares of a
+// dispatch within a u32.
+const CHUNK: usize = 1 << 20;
+const WORKGROUP_SIZE: usize = 256;
+
+// evaluates showdowns for batches of runouts on the gpu.
+#[derive(Debug)]
+pub struct GpuEvaluator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuEvaluator {
+    // None if there's no adapter that can run compute shaders.
+    pub fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))?;
+        if !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            warn!("{} can't run compute shaders.", adapter.get_info().name);
+            return None;
+        }
+
+        let (device, queue) = block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("poker-odds"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("showdown"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("showdown.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("showdown"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        debug!("Using {:?} for showdowns.", adapter.get_info());
+        Some(GpuEvaluator {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    // how many of the complete `runouts` the hero wins outright, and the
+    // hero's share of the split pots in 1/2520 pots. hands and runouts are
+    // card masks. None if the device gave up on us.
+    pub fn wins(&self, hands: &[u64], hero: usize, runouts: &[u64]) -> Option<(u64, u64)> {
+        let hands_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("hands"),
+                contents: &to_words(hands),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+
+        let mut wins: u64 = 0;
+        let mut ties: u64 = 0;
+        for chunk in runouts.chunks(CHUNK) {
+            let (w, t) = self.dispatch(&hands_buf, hands.len(), hero, chunk)?;
+            wins += w as u64;
+            ties += t as u64;
+        }
+        Some((wins, ties))
+    }
+
+    fn dispatch(
+        &self,
+        hands: &wgpu::Buffer,
+        nplayers: usize,
+        hero: usize,
+        runouts: &[u64],
+    ) -> Option<(u32, u32)> {
+        let params: Vec<u8> = [nplayers as u32, hero as u32, runouts.len() as u32, 0]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        let params_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: &params,
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let runouts_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("runouts"),
+                contents: &to_words(runouts),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let tally_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("tally"),
+                contents: &[0; 8],
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: 8,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buf.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: hands.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: runouts_buf.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: tally_buf.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(runouts.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&tally_buf, 0, &readback, 0, 8);
+        self.queue.submit(Some(encoder.finish()));
+
+        let (tx, rx) = mpsc::channel();
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |r| {
+            let _ = tx.send(r);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv().ok()?.ok()?;
+
+        let bytes = slice.get_mapped_range();
+        let word =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Some((word(0), word(4)))
+    }
+}
+
+// card masks as (low, high) pairs of u32, which is all wgsl has.
+fn to_words(masks: &[u64]) -> Vec<u8> {
+    masks
+        .iter()
+        .flat_map(|m| [*m as u32, (*m >> 32) as u32])
+        .flat_map(|w| w.to_le_bytes())
+        .collect()
+}
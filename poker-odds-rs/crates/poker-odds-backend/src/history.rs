@@ -0,0 +1,289 @@
+use crate::error::Error;
+use std::collections::HashMap;
+
+// what a player did, in the order it happened.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Action {
+    pub player: String,
+    // e.g. "raises", "calls" or "posts".
+    pub kind: String,
+    // the chips this action put in the pot.
+    pub amount: f32,
+    pub all_in: bool,
+    // how many board cards were out at the time.
+    pub board_cards: usize,
+}
+
+// one hand of a PokerStars hand history, as much of it as the equity tools
+// need. amounts are in the history's units, dollars or tournament chips.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandHistory {
+    pub id: String,
+    // the player the history was written for, from "Dealt to".
+    pub hero: Option<String>,
+    // every hand that was dealt to the hero or shown down, e.g. "AhKh".
+    pub hole_cards: HashMap<String, String>,
+    // the whole board, e.g. "Th7h2c8d3s".
+    pub board: String,
+    pub actions: Vec<Action>,
+    // what each player put in the pot, less uncalled bets.
+    pub invested: HashMap<String, f32>,
+    // what each player took from the pot.
+    pub collected: HashMap<String, f32>,
+    pub total_pot: f32,
+    pub rake: f32,
+}
+
+impl HandHistory {
+    pub fn folded(&self, player: &str) -> bool {
+        self.actions
+            .iter()
+            .any(|a| a.player == player && a.kind == "folds")
+    }
+
+    // what `player` won or lost in the hand.
+    pub fn net(&self, player: &str) -> f32 {
+        let collected: f32 = self.collected.get(player).copied().unwrap_or(0.);
+        collected - self.invested.get(player).copied().unwrap_or(0.)
+    }
+}
+
+// every hand in a PokerStars hand history file. lines outside a hand and
+// lines it doesn't know are skipped.
+pub fn parse_hand_histories(text: &str) -> Result<Vec<HandHistory>, Error> {
+    let mut hands: Vec<HandHistory> = Vec::new();
+    // chips each player has put in on the current street.
+    let mut street: HashMap<String, f32> = HashMap::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("PokerStars Hand #") {
+            let id: &str = rest.split(':').next().unwrap_or_default();
+            hands.push(HandHistory {
+                id: id.trim().to_string(),
+                ..HandHistory::default()
+            });
+            street.clear();
+            continue;
+        }
+        let Some(hand) = hands.last_mut() else {
+            continue;
+        };
+        let invalid = || Error::InvalidHistory(line.to_string());
+
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            // some sites list the other players without their cards.
+            if let Some((name, _)) = rest.rsplit_once(" [") {
+                hand.hero = Some(name.to_string());
+                hand.hole_cards.insert(name.to_string(), cards_of(rest));
+            }
+        } else if ["*** FLOP", "*** TURN", "*** RIVER"]
+            .iter()
+            .any(|s| line.starts_with(s))
+        {
+            hand.board = cards_of(line);
+            street.clear();
+        } else if line.starts_with("Seat ") {
+            // the seats and the summary repeat what the actions said.
+            continue;
+        } else if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = rest.split_once(") returned to ").ok_or_else(invalid)?;
+            *hand.invested.entry(name.to_string()).or_default() -= amount_of(amount)?;
+        } else if let Some(rest) = line.strip_prefix("Total pot ") {
+            let pot: &str = rest.split_whitespace().next().unwrap_or_default();
+            hand.total_pot = amount_of(pot)?;
+            if let Some((_, rake)) = rest.split_once("| Rake ") {
+                hand.rake = amount_of(rake.split_whitespace().next().unwrap_or_default())?;
+            }
+        } else if let Some((name, rest)) = line.split_once(" collected ") {
+            // "Bob collected $19.90 from pot", or from a side pot.
+            let amount: &str = rest.split_whitespace().next().unwrap_or_default();
+            *hand.collected.entry(name.to_string()).or_default() += amount_of(amount)?;
+        } else if let Some((name, rest)) = line.split_once(": ") {
+            if rest.starts_with("shows [") {
+                hand.hole_cards.insert(name.to_string(), cards_of(rest));
+                continue;
+            }
+            let Some(action) = parse_action(name, rest, &mut street, hand.board.len() / 2)? else {
+                continue;
+            };
+            *hand.invested.entry(name.to_string()).or_default() += action.amount;
+            hand.actions.push(action);
+        }
+    }
+
+    if hands.is_empty() {
+        return Err(Error::InvalidHistory(
+            "no PokerStars hands found".to_string(),
+        ));
+    }
+    Ok(hands)
+}
+
+// "raises $2 to $3 and is all-in" by `name`. None for lines that aren't
+// bets, like chat or seat lines.
+fn parse_action(
+    name: &str,
+    rest: &str,
+    street: &mut HashMap<String, f32>,
+    board_cards: usize,
+) -> Result<Option<Action>, Error> {
+    let (rest, all_in) = match rest.strip_suffix(" and is all-in") {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    let committed: &mut f32 = street.entry(name.to_string()).or_default();
+    let amount: f32 = match words.as_slice() {
+        ["folds", ..] | ["checks", ..] => 0.,
+        ["calls", amount, ..] | ["bets", amount, ..] => {
+            let amount: f32 = amount_of(amount)?;
+            *committed += amount;
+            amount
+        }
+        // "raises $2 to $3": the $3 is the whole bet on this street.
+        ["raises", _, "to", to, ..] => {
+            let to: f32 = amount_of(to)?;
+            let amount: f32 = to - *committed;
+            *committed = to;
+            amount
+        }
+        // antes are dead money, the blinds count towards calling.
+        ["posts", "the", "ante", amount, ..] => amount_of(amount)?,
+        ["posts", .., amount] => {
+            let amount: f32 = amount_of(amount)?;
+            *committed += amount;
+            amount
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(Action {
+        player: name.to_string(),
+        kind: words[0].to_string(),
+        amount,
+        all_in,
+        board_cards,
+    }))
+}
+
+// the cards in every [..] of a line, e.g. "*** TURN *** [Th 7h 2c] [8d]".
+fn cards_of(line: &str) -> String {
+    line.split('[')
+        .skip(1)
+        .filter_map(|part| part.split(']').next())
+        .flat_map(str::split_whitespace)
+        .collect()
+}
+
+// "$1,000.50" or "1000".
+fn amount_of(s: &str) -> Result<f32, Error> {
+    let digits: String = s
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    digits
+        .parse::<f32>()
+        .map_err(|_| Error::InvalidHistory(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDS: &str = "\
+PokerStars Hand #1001: Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:00:00 ET
+Table 'Alpha' 6-max Seat #1 is the button
+Seat 1: Alice ($100 in chips)
+Seat 2: Bob ($50 in chips)
+Alice: posts small blind $0.50
+Bob: posts big blind $1
+*** HOLE CARDS ***
+Dealt to Alice [Ah Ad]
+Alice: raises $2 to $3
+Bob: calls $2
+*** FLOP *** [2c 7d 9s]
+Bob: bets $5
+Alice: raises $15 to $20
+Bob: raises $27 to $47 and is all-in
+Alice: calls $27
+*** TURN *** [2c 7d 9s] [Kc]
+*** RIVER *** [2c 7d 9s Kc] [3h]
+*** SHOW DOWN ***
+Bob: shows [Kh Kd] (three of a kind, Kings)
+Alice: shows [Ah Ad] (a pair of Aces)
+Bob collected $98.50 from pot
+*** SUMMARY ***
+Total pot $100 | Rake $1.50
+Board [2c 7d 9s Kc 3h]
+Seat 1: Alice (button) (small blind) showed [Ah Ad] and lost with a pair of Aces
+Seat 2: Bob (big blind) showed [Kh Kd] and won ($98.50) with three of a kind, Kings
+
+PokerStars Hand #1002: Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:01:00 ET
+Alice: posts big blind $1
+Bob: posts small blind $0.50
+Dealt to Alice [7h 2d]
+Bob: raises $2 to $3
+Alice: folds
+Uncalled bet ($2) returned to Bob
+Bob collected $2 from pot
+Total pot $2 | Rake $0
+";
+
+    #[test]
+    fn reads_a_hand_to_showdown() {
+        let hands: Vec<HandHistory> = parse_hand_histories(HANDS).unwrap();
+        assert_eq!(hands.len(), 2);
+        let h: &HandHistory = &hands[0];
+        assert_eq!(h.id, "1001");
+        assert_eq!(h.hero.as_deref(), Some("Alice"));
+        assert_eq!(h.hole_cards["Alice"], "AhAd");
+        assert_eq!(h.hole_cards["Bob"], "KhKd");
+        assert_eq!(h.board, "2c7d9sKc3h");
+        assert_eq!((h.total_pot, h.rake), (100., 1.5));
+        // the raises only count what went in on top of the street's bets.
+        assert_eq!(h.invested["Alice"], 50.);
+        assert_eq!(h.invested["Bob"], 50.);
+        assert_eq!(h.net("Alice"), -50.);
+        assert_eq!(h.net("Bob"), 48.5);
+    }
+
+    #[test]
+    fn keeps_the_street_and_all_ins_of_actions() {
+        let hands: Vec<HandHistory> = parse_hand_histories(HANDS).unwrap();
+        let shove: &Action = hands[0].actions.iter().find(|a| a.all_in).unwrap();
+        assert_eq!(shove.player, "Bob");
+        assert_eq!(shove.kind, "raises");
+        assert_eq!(shove.amount, 42.);
+        assert_eq!(shove.board_cards, 3);
+        assert_eq!(hands[0].actions.len(), 8);
+    }
+
+    #[test]
+    fn takes_back_an_uncalled_bet() {
+        let hands: Vec<HandHistory> = parse_hand_histories(HANDS).unwrap();
+        let h: &HandHistory = &hands[1];
+        assert!(h.folded("Alice"));
+        assert!(!h.folded("Bob"));
+        assert_eq!(h.invested["Bob"], 1.);
+        assert_eq!(h.net("Alice"), -1.);
+        assert_eq!(h.net("Bob"), 1.);
+    }
+
+    #[test]
+    fn a_file_without_hands_is_rejected() {
+        assert!(parse_hand_histories("").is_err());
+        assert!(parse_hand_histories("Alice: raises $2 to $3").is_err());
+        assert!(matches!(
+            parse_hand_histories("PokerStars Hand #1: Hold'em\nAlice: bets $x"),
+            Err(Error::InvalidHistory(_))
+        ));
+    }
+
+    #[test]
+    fn reads_amounts_with_separators() {
+        assert_eq!(amount_of("$1,000.50").unwrap(), 1000.5);
+        assert_eq!(amount_of("2500").unwrap(), 2500.);
+        assert!(amount_of("all").is_err());
+    }
+}
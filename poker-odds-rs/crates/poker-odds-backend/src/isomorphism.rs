@@ -0,0 +1,96 @@
+use crate::cards::{Board, HoleCards};
+use crate::error::Error;
+use crate::solver::{check_free, hand_mask, parse_board};
+
+// one bit per value in the clubs column of a card mask.
+const CLUBS: u64 = 0x1_1111_1111_1111;
+
+// `mask` with every card of suit `s` moved to suit `perm[s]`.
+pub(crate) fn permute(mask: u64, perm: &[usize; 4]) -> u64 {
+    (0..4).fold(0, |acc, s| acc | (mask >> s & CLUBS) << perm[s])
+}
+
+// the 24 ways to relabel the suits, the identity first.
+pub(crate) fn permutations() -> Vec<[usize; 4]> {
+    let mut perms: Vec<[usize; 4]> = Vec::with_capacity(24);
+    for a in 0..4 {
+        for b in (0..4).filter(|&b| b != a) {
+            for c in (0..4).filter(|&c| c != a && c != b) {
+                perms.push([a, b, c, 6 - a - b - c]);
+            }
+        }
+    }
+    perms
+}
+
+// the relabeling that gives `groups` their smallest form, the groups from
+// `unordered` on sorted as a set, and that form. the third value is how
+// many relabelings give it, the ones that leave the cards as they are.
+pub(crate) fn canonical(groups: &[u64], unordered: usize) -> ([usize; 4], Vec<u64>, usize) {
+    let relabel = |perm: &[usize; 4]| -> Vec<u64> {
+        let mut key: Vec<u64> = groups.iter().map(|&g| permute(g, perm)).collect();
+        key[unordered.min(groups.len())..].sort_unstable();
+        key
+    };
+    let mut best: ([usize; 4], Vec<u64>, usize) = ([0, 1, 2, 3], relabel(&[0, 1, 2, 3]), 0);
+    for perm in permutations() {
+        let key: Vec<u64> = relabel(&perm);
+        if key < best.1 {
+            best = (perm, key, 1);
+        } else if key == best.1 {
+            best.2 += 1;
+        }
+    }
+    best
+}
+
+// the board written the same for every relabeling of its suits, e.g.
+// "AcKc2h" for both "AhKh2c" and "AsKs2d". flops that come out the same
+// play the same, there are 1755 different ones.
+pub fn canonical_board(board: &str) -> Result<String, Error> {
+    let (_, key, _) = canonical(&[parse_board(board)?], 1);
+    Ok(Board { mask: key[0] }.to_string())
+}
+
+// how many boards relabeling the suits of `board` gives, itself included,
+// e.g. 4 for a monotone flop, 12 for a two tone one and 24 for a rainbow one.
+pub fn board_orbit(board: &str) -> Result<usize, Error> {
+    let (_, _, fixed) = canonical(&[parse_board(board)?], 1);
+    Ok(24 / fixed)
+}
+
+fn scenario(hands: &[impl AsRef<str>], board: &str) -> Result<Vec<u64>, Error> {
+    let mut groups: Vec<u64> = vec![parse_board(board)?];
+    let mut dead: u64 = groups[0];
+    for hand in hands {
+        let hand: u64 = hand_mask(hand.as_ref())?;
+        check_free(dead, hand)?;
+        dead |= hand;
+        groups.push(hand);
+    }
+    Ok(groups)
+}
+
+// the hands and board written the same for every relabeling of the suits,
+// with the opponents sorted, so equivalent spots can be told apart from
+// different ones, e.g. "AhKh vs QsQd" and "AcKc vs QdQh". the memo keys
+// matchups the same way, up to the board.
+pub fn canonical_scenario(
+    hands: &[impl AsRef<str>],
+    board: &str,
+) -> Result<(Vec<String>, String), Error> {
+    let (_, key, _) = canonical(&scenario(hands, board)?, 2);
+    let hands: Vec<String> = key[1..]
+        .iter()
+        .map(|&mask| HoleCards { mask }.to_string())
+        .collect();
+    Ok((hands, Board { mask: key[0] }.to_string()))
+}
+
+// how many different spots relabeling the suits of the hands and board
+// gives, itself included. weighs a canonical spot when counting how often
+// it comes up.
+pub fn scenario_orbit(hands: &[impl AsRef<str>], board: &str) -> Result<usize, Error> {
+    let (_, _, fixed) = canonical(&scenario(hands, board)?, 2);
+    Ok(24 / fixed)
+}
@@ -1,8 +1,113 @@
-#![feature(portable_simd)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 
+mod aggregate;
+#[cfg(feature = "simd")]
+mod batch;
+mod buckets;
+mod canonical;
+mod cards;
+#[cfg(feature = "crosscheck")]
+mod crosscheck;
+mod deck;
+mod drills;
+mod error;
+mod ev;
+mod flops;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod history;
+mod isomorphism;
+mod lookup;
+mod matrix;
+mod memo;
+#[cfg(feature = "parallel")]
+mod pool;
+mod preflop;
+mod presets;
+mod pushfold;
+mod query;
+mod range;
+mod rare;
+mod realization;
+mod sampling;
+mod selftest;
+mod session;
 mod solver;
+mod store;
+mod strength;
+mod tables;
+mod texture;
+#[cfg(feature = "verify")]
+mod verify;
 
-pub fn solve(hands: &Vec<String>, board: &String) -> f32 {
+pub use aggregate::{FlopClass, FlopEquity, FlopGroup, FlopReport, FlopSubset, Pairing, Suits};
+pub use buckets::{
+    BoardBuckets, BucketOptions, BucketedHand, Bucketing, EquityBuckets, Metric, NO_BUCKET,
+};
+pub use canonical::{canonical, combos_of, starting_hands};
+pub use cards::{Board, HoleCards};
+#[cfg(feature = "crosscheck")]
+pub use crosscheck::{cross_check, CrossCheck, Disagreement, ExternalEvaluator};
+pub use deck::{runouts, Deck};
+pub use drills::{equity_drills, parse_matchups, random_matchups, to_csv, Flashcard};
+pub use error::Error;
+pub use ev::{break_even_fold, semi_bluff_ev, BluffCheck};
+pub use flops::FlopTable;
+pub use history::{parse_hand_histories, Action, HandHistory};
+pub use isomorphism::{board_orbit, canonical_board, canonical_scenario, scenario_orbit};
+#[cfg(feature = "mmap")]
+pub use lookup::share_tables;
+pub use matrix::{ComboEquity, RangeMatrix};
+pub use preflop::{PreflopChart, PreflopTable};
+pub use presets::RangePresets;
+pub use pushfold::{icm, PushFold, PushFoldChart, PushFoldSpot, PushFoldStrategy};
+pub use query::{answer_query, parse_scenario, Scenario};
+pub use range::{Combo, Range};
+pub use rare::{Category, CategoryEstimate};
+pub use realization::{HandClass, Position, Realization};
+pub use sampling::Sampling;
+pub use selftest::{selftest, Mismatch, SelfTest};
+pub use session::{all_in_report, AllIn, SessionReport, Variance};
+pub use solver::{
+    exact_runouts, CacheStats, DetailedSolution, EquityDistribution, EquityEstimate,
+    IncrementalSolve, MemoMode, PlayerResult, RangeEquity, Solution, SolveMode, SolveSize,
+    SolveStats, Solver, SolverOptions, StopReason, Street, Tally, Variant,
+};
+pub use store::{ResultStore, StoredResult};
+pub use strength::{nuts, percentile, HandStrength, Nuts, Percentile};
+pub use texture::{runout_shifts, Shift, Texture, TextureGroup, TextureReport};
+
+// takes Vec<String>s, slices of &strs or anything else that derefs to
+// strings. panics on hands or a board that can't be read.
+pub fn solve(hands: &[impl AsRef<str>], board: &str) -> f32 {
+    let solution = solver::Solver::new();
+    solution.solve(hands, board)
+}
+
+pub fn try_solve(hands: &[impl AsRef<str>], board: &str) -> Result<Solution, Error> {
+    let solution = solver::Solver::new();
+    solution.try_solve(hands, board)
+}
+
+// the hero's equity against `opponents` random hands, from 100k samples.
+pub fn equity_vs_random(hero: &str, opponents: usize, board: &str) -> Result<f32, Error> {
+    let solution = solver::Solver::new();
+    solution.equity_vs_random(
+        hero,
+        opponents,
+        board,
+        SolveMode::MonteCarlo {
+            samples: strength::RANDOM_SAMPLES,
+        },
+    )
+}
+// the exact equity with every player's breakdown.
+pub fn solve_detailed(hands: &[impl AsRef<str>], board: &str) -> Result<DetailedSolution, Error> {
+    let solution = solver::Solver::new();
+    solution.solve_detailed(hands, board, SolveMode::Exact)
+}
+// the exact equity of hands that were already read.
+pub fn solve_cards(hands: &[HoleCards], board: &Board) -> Result<Solution, Error> {
     let solution = solver::Solver::new();
-    solution.solve(&hands, &board)
+    solution.solve_cards(hands, board, SolveMode::Exact)
 }
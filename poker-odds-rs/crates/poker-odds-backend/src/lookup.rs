@@ -0,0 +1,188 @@
+use crate::tables::{self, Offsets, CLUBS, FLUSHES};
+use log::warn;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use poker_odds_core::reference;
+#[cfg(feature = "mmap")]
+use std::fs::{self, File};
+#[cfg(feature = "mmap")]
+use std::io;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+use std::sync::OnceLock;
+
+// the tables build.rs worked out, see tables::to_bytes.
+static ASSET: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/tables.bin"));
+
+// a five to seven card hand's score is two lookups: the flush suit's value
+// mask in one table if it has a flush, else its value counts in the other.
+// nothing else in seven cards beats a flush, so the suits only matter then. the
+// scores are in the batch evaluator's format, by the reference evaluator.
+struct Tables<'a> {
+    // the flush scores then the value counts', four bytes each, read
+    // straight out of the embedded asset or a shared file. the ones in use
+    // are kept for as long as we run.
+    scores: &'a [u8],
+    offsets: Offsets,
+}
+
+static TABLES: OnceLock<Tables<'static>> = OnceLock::new();
+
+#[inline(always)]
+pub(crate) fn score(cards: u64) -> u32 {
+    Tables::get().score(cards)
+}
+
+// maps the tables from the file at `path`, writing it there first when
+// there's none, so every process that shares the file shares one copy of
+// them in memory instead of each holding its own. it has to come before the
+// first hand is scored, the tables in use stay after that. fails on a file
+// that isn't the tables this build works out. nothing may write to the file
+// while a process has it mapped.
+#[cfg(feature = "mmap")]
+pub fn share_tables(path: impl AsRef<Path>) -> io::Result<()> {
+    let path: &Path = path.as_ref();
+    if !path.exists() {
+        // written to the side and moved in, so no other process maps it
+        // half written.
+        let partial: std::path::PathBuf =
+            path.with_extension(format!("{}.part", std::process::id()));
+        fs::write(&partial, ASSET)?;
+        fs::rename(&partial, path)?;
+    }
+    // SAFETY: the file must never be modified while it's mapped, which is
+    // for as long as any process that shares it runs. writing to it then,
+    // even the same bytes over again, is undefined behavior, checked or not.
+    let map: Mmap = unsafe { Mmap::map(&File::open(path)?)? };
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not this build's evaluator tables",
+        )
+    };
+    let tables: Tables = Tables {
+        scores: tables::scores_of(&map).ok_or_else(invalid)?,
+        offsets: tables::offsets(),
+    };
+    if !tables.agrees() {
+        return Err(invalid());
+    }
+    // the map is only leaked once it's going in, a file that's turned away
+    // is unmapped again.
+    let mut mapped: bool = false;
+    TABLES.get_or_init(|| {
+        mapped = true;
+        let map: &'static Mmap = Box::leak(Box::new(map));
+        Tables {
+            scores: tables::scores_of(map).unwrap(),
+            offsets: tables::offsets(),
+        }
+    });
+    match mapped {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the evaluator tables are already in use",
+        )),
+    }
+}
+
+// every fourth bit of `cards` packed together, e.g. one suit's values.
+#[inline(always)]
+fn pack(cards: u64) -> u32 {
+    let mut x: u64 = cards & CLUBS;
+    x = (x | x >> 3) & 0x0303_0303_0303_0303;
+    x = (x | x >> 6) & 0x000F_000F_000F_000F;
+    x = (x | x >> 12) & 0x0000_00FF_0000_00FF;
+    ((x | x >> 24) & 0xFFFF) as u32
+}
+
+impl Tables<'_> {
+    #[inline(always)]
+    fn get() -> &'static Tables<'static> {
+        TABLES.get_or_init(Tables::new)
+    }
+
+    // the embedded tables once they've checked out, else worked out again,
+    // which takes a few tens of milliseconds.
+    fn new() -> Tables<'static> {
+        let embedded: Option<Tables<'static>> = tables::scores_of(ASSET).map(|scores| Tables {
+            scores,
+            offsets: tables::offsets(),
+        });
+        match embedded {
+            Some(tables) if tables.agrees() => tables,
+            _ => {
+                warn!("The embedded evaluator tables don't check out, working them out again.");
+                let bytes: &'static [u8] = tables::to_bytes(&tables::generate()).leak();
+                Tables {
+                    scores: tables::scores_of(bytes).unwrap(),
+                    offsets: tables::offsets(),
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn score(&self, cards: u64) -> u32 {
+        for suit in 0..4 {
+            if (cards & CLUBS << suit).count_ones() >= 5 {
+                return self.at(pack(cards >> suit & CLUBS) as usize);
+            }
+        }
+        self.at(FLUSHES + tables::index(&self.offsets, cards) as usize)
+    }
+
+    #[inline(always)]
+    fn at(&self, i: usize) -> u32 {
+        let b: &[u8; 4] = self.scores[i * 4..][..4].try_into().unwrap();
+        u32::from_le_bytes(*b)
+    }
+
+    // a few hundred random five, six and seven card hands scored as the
+    // reference does.
+    fn agrees(&self) -> bool {
+        let mut x: u64 = 0x9e37_79b9_7f4a_7c15;
+        (0..768).all(|i| {
+            let mut cards: u64 = 0;
+            while cards.count_ones() < 5 + i % 3 {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                cards |= 1 << ((x >> 32) % 52);
+            }
+            self.score(cards) == reference(cards) as u32
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::parse_board;
+
+    // flops and turns are scored straight off the tables too.
+    #[test]
+    fn scores_five_six_and_seven_cards() {
+        let mut x: u64 = 0x2545_f491_4f6c_dd1d;
+        for n in 5..=7 {
+            for _ in 0..20_000 {
+                let mut cards: u64 = 0;
+                while cards.count_ones() < n {
+                    x ^= x << 13;
+                    x ^= x >> 7;
+                    x ^= x << 17;
+                    cards |= 1 << ((x >> 32) % 52);
+                }
+                assert_eq!(score(cards), reference(cards) as u32, "{:#x}", cards);
+            }
+        }
+    }
+
+    #[test]
+    fn a_flop_isnt_filled_out_with_aces() {
+        // 9h9d on 2c7d9s is a set, not quad aces.
+        let cards: u64 = parse_board("9h9d2c7d9s").unwrap();
+        assert_eq!(score(cards) >> 26, 3);
+    }
+}
@@ -1,8 +1,1323 @@
-#![feature(portable_simd)]
+use poker_odds_backend::{
+    all_in_report, canonical_scenario, equity_drills, nuts, parse_hand_histories, parse_matchups,
+    percentile, random_matchups, selftest, semi_bluff_ev, to_csv, BluffCheck, Board, BucketOptions,
+    Bucketing, EquityBuckets, Error, Flashcard, FlopReport, FlopSubset, HoleCards,
+    IncrementalSolve, Metric, PreflopChart, PreflopTable, PushFold, PushFoldSpot, PushFoldStrategy,
+    Range, RangeEquity, RangeMatrix, RangePresets, ResultStore, Sampling, SelfTest, SessionReport,
+    Solution, SolveMode, SolveStats, Solver, SolverOptions, StopReason, StoredResult,
+    TextureReport, Variance,
+};
+use poker_odds_core::card_names;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
 
-mod solver;
-use solver::parse_input_and_solve;
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("selftest") => run_selftest(),
+        Some("fold-equity") => run_fold_equity(&args[1..]),
+        Some("bluff") => run_bluff(&args[1..]),
+        Some("pushfold") => run_pushfold(&args[1..]),
+        Some("preflop-table") => run_preflop_table(&args[1..]),
+        Some("strength") => run_strength(&args[1..]),
+        Some("nuts") => run_nuts(&args[1..]),
+        Some("cards") => run_cards(&args[1..]),
+        Some("replay") => run_replay(&args[1..]),
+        Some("session") => run_session(&args[1..]),
+        Some("drills") => run_drills(&args[1..]),
+        Some("ranges") => run_ranges(&args[1..]),
+        Some("chart") => run_chart(&args[1..]),
+        Some("matrix") => run_matrix(&args[1..]),
+        Some("flops") => run_flops(&args[1..]),
+        Some("textures") => run_textures(&args[1..]),
+        Some("buckets") => run_buckets(&args[1..]),
+        Some("history") => run_history(&args[1..]),
+        _ => run_prompt(&args),
+    }
+}
+
+const PROMPT_USAGE: &str = "usage: [--stats] [--history results.txt] [--samples 100000] \
+[--precision 0.001] [--budget 10] [--sampling plain|stratified|antithetic|control] \
+[--preflop-table preflop.bin]";
+
+// the interactive prompt. `--history` records every solve in a results
+// file for the history command. `--samples` solves by monte carlo instead
+// of exactly, dealt as `--sampling` says; the control variate takes its
+// means from `--preflop-table`, worked out first if the file isn't there.
+// `--precision` samples until the equity is known to within that either
+// side, or for `--budget` seconds at most.
+fn run_prompt(args: &[String]) -> ExitCode {
+    let mut options: SolverOptions = Solver::builder();
+    let mut precision: Option<f32> = None;
+    let mut budget: Duration = Duration::from_secs(10);
+    let mut store: Option<ResultStore> = None;
+    let mut table: Option<PreflopTable> = None;
+    let mut stats: bool = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.clone().next()) {
+            ("--stats", _) => stats = true,
+            ("--sampling", Some(s)) if parse_sampling(s).is_some() => {
+                args.next();
+                options = options.sampling(parse_sampling(s).unwrap());
+            }
+            ("--preflop-table", Some(path)) => {
+                args.next();
+                match load_table(path, 20_000, 0) {
+                    Ok(t) => table = Some(t),
+                    Err(e) => {
+                        eprintln!("{}: {}", path, e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            ("--samples", Some(n)) if n.parse::<usize>().is_ok() => {
+                args.next();
+                let samples: usize = n.parse().unwrap();
+                options = options.mode(SolveMode::MonteCarlo { samples });
+            }
+            ("--precision", Some(p)) if p.parse::<f32>().is_ok_and(|p| p > 0.) => {
+                args.next();
+                precision = p.parse().ok();
+            }
+            ("--budget", Some(s)) if s.parse::<f64>().is_ok_and(|s| s >= 0.) => {
+                args.next();
+                budget = Duration::from_secs_f64(s.parse().unwrap());
+            }
+            ("--history", Some(path)) => {
+                args.next();
+                match ResultStore::open(path) {
+                    Ok(s) => store = Some(s),
+                    Err(e) => {
+                        eprintln!("{}: {}", path, e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            _ => {
+                eprintln!("{}", PROMPT_USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Some(half_width) = precision {
+        options = options.mode(SolveMode::UntilPrecision { half_width, budget });
+    }
+    let mut solver: Solver = options.build();
+    if let Some(store) = store {
+        solver = solver.with_store(Arc::new(store));
+    }
+    if let Some(table) = table {
+        solver = solver.with_preflop_table(Arc::new(table));
+    }
+    parse_input_and_solve(&solver, stats);
+    ExitCode::SUCCESS
+}
+
+// prints `question` and reads the answer, None once stdin is closed.
+fn prompt(question: &str) -> Option<String> {
+    println!("{}", question);
+    let mut answer: String = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(answer.trim_end_matches(['\n', '\r']).to_string()),
+    }
+}
+
+// the interactive prompt, solving with `solution`. with `stats` each solve
+// is followed by how it went, see print_stats.
+fn parse_input_and_solve(solution: &Solver, stats: bool) {
+    /*
+    By threading & sharing memo table across threads,
+    we get the following result on a board with 0 cards
+    running on 8 threads:
+
+        1 thread (Python): 60 seconds
+        1 thread (Rust): 60 seconds
+        8 threads - Without sharing memo: 60 seconds
+        8 threads - With sharing memo: 16 seconds.
+        8 threads with opt-level 3 + sharing memo: 5 seconds.
+        8 threads w/ opt l3 + sharing memo w/ rwlock: < 3 seconds
+        8 threads w/ opt l3 + memo as dashmap: < 1 seconds
+        The row above + all computations binary - remove heap allocation during Hand.rank call: < 400 ms
+    */
+
+    // a typo re-asks for what was mistyped, keeping what came before it.
+    // the prompt ends when stdin does.
+    loop {
+        let Some(nplayers) = prompt("# active players [0 to exit]:") else {
+            return;
+        };
+        let nplayers: usize = match nplayers.trim().parse::<usize>() {
+            Ok(0) => return,
+            Ok(n) if (2..=10).contains(&n) => n,
+            Ok(n) => {
+                println!("{}", Error::PlayerCount(n));
+                continue;
+            }
+            Err(_) => {
+                println!("'{}' is not a number of players.", nplayers.trim());
+                continue;
+            }
+        };
+
+        let mut hs: Vec<String> = Vec::new();
+        let mut seen: u64 = 0;
+        while hs.len() < nplayers {
+            let question: String = match hs.len() {
+                0 => "Your starting hand: ".to_string(),
+                i => format!("Opponent {} hand: ", i),
+            };
+            let Some(hand) = prompt(&question) else {
+                return;
+            };
+            match HoleCards::parse(&hand).and_then(|h| check_free(seen, h.mask()).map(|_| h.mask()))
+            {
+                Ok(mask) => {
+                    seen |= mask;
+                    hs.push(hand.trim().to_string());
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        // the board a street at a time, each solve starting from the
+        // subtrees the last one finished. a blank street ends the hand.
+        let mut spot: IncrementalSolve = solution
+            .incremental(&hs, "")
+            .expect("the hands were checked as they were typed");
+        for (street, n) in [("Flop", 3), ("Turn", 1), ("River", 1)] {
+            let question: String = match n {
+                3 => format!("{} [blank for preflop]: ", street),
+                _ => format!("{} [blank for the next hand]: ", street),
+            };
+            let cards: u64 = loop {
+                let Some(answer) = prompt(&question) else {
+                    return;
+                };
+                if answer.trim().is_empty() {
+                    break 0;
+                }
+                match street_cards(&answer, n, seen) {
+                    Ok(cards) => break cards,
+                    Err(e) => println!("{}", e),
+                }
+            };
+            if cards == 0 {
+                if street == "Flop" {
+                    let s: Solution = spot.solve();
+                    println!("Equity is {} ({:?}).", s.equity, s.stats.elapsed);
+                    if stats {
+                        print_stats(&s.stats);
+                    }
+                }
+                break;
+            }
+            for idx in (0..52).filter(|&i| cards >> i & 1 == 1) {
+                spot.extend_board(&card_names(1 << idx))
+                    .expect("the street was checked");
+            }
+            seen |= cards;
+            let s: Solution = spot.solve();
+            println!(
+                "Equity on {} is {} ({:?}).",
+                spot.board(),
+                s.equity,
+                s.stats.elapsed
+            );
+            if stats {
+                print_stats(&s.stats);
+            }
+        }
+    }
+}
+
+fn print_stats(stats: &SolveStats) {
+    println!(
+        "  {} leaves, {:.1}% of {} memo lookups hit{}.",
+        stats.leaves,
+        stats.hit_rate() * 100.,
+        stats.cache_hits + stats.cache_misses,
+        if stats.cached { ", answer cached" } else { "" }
+    );
+    if stats.threads > 0 {
+        println!(
+            "  {} thread{} {:.0}% busy, {:?} of work in {:?}.",
+            stats.threads,
+            if stats.threads == 1 { "" } else { "s" },
+            stats.utilization() * 100.,
+            stats.busy,
+            stats.elapsed
+        );
+    }
+    if let Some(std_err) = stats.std_err {
+        println!("  Standard error {:.2}%.", std_err * 100.);
+    }
+    match stats.stop {
+        Some(StopReason::Precision) => println!("  Stopped at the precision asked for."),
+        Some(StopReason::Budget) => println!("  Stopped when the time ran out."),
+        None => {}
+    }
+    if let Some(reduction) = stats.variance_reduction {
+        println!("  The sampling cut the variance {:.2}x.", reduction);
+    }
+}
+
+// fails with the first card of `cards` that is already in `dealt`.
+fn check_free(dealt: u64, cards: u64) -> Result<(), Error> {
+    match dealt & cards {
+        0 => Ok(()),
+        taken => Err(Error::DuplicateCard(card_names(
+            1 << taken.trailing_zeros(),
+        ))),
+    }
+}
+
+// the `n` cards of a street typed as `street`, none of them `dealt` yet.
+fn street_cards(street: &str, n: usize, dealt: u64) -> Result<u64, Error> {
+    let cards: u64 = Board::parse(street)?.mask();
+    if cards.count_ones() as usize != n {
+        return Err(Error::InvalidBoard(street.trim().to_string()));
+    }
+    check_free(dealt, cards)?;
+    Ok(cards)
+}
+
+fn parse_sampling(s: &str) -> Option<Sampling> {
+    match s {
+        "plain" => Some(Sampling::Plain),
+        "stratified" => Some(Sampling::Stratified),
+        "antithetic" => Some(Sampling::Antithetic),
+        "control" => Some(Sampling::ControlVariate),
+        _ => None,
+    }
+}
+
+// checks the hand evaluators against every seven card hand.
+fn run_selftest() -> ExitCode {
+    let result: SelfTest = selftest(|done| {
+        eprint!("\r{:.0}%", done * 100.);
+        let _ = std::io::stderr().flush();
+    });
+    eprintln!();
+    for m in result.examples.iter() {
+        println!(
+            "{}: {} scored {} instead of {}.",
+            m.cards, m.evaluator, m.got, m.expected
+        );
+    }
+    println!(
+        "{} of {} hands disagree with the reference.",
+        result.mismatches, result.hands
+    );
+    if result.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+const FOLD_EQUITY_USAGE: &str = "usage: fold-equity --hero AhKh --villain 'QQ+,AK' \
+[--board 7c8c9c] --pot 100 --bet 75 --fold 0.4 [--samples 100000]";
+
+// what a semi-bluff is worth against a range that folds some of the time.
+fn run_fold_equity(args: &[String]) -> ExitCode {
+    let flags: HashMap<&str, &str> = match flags(args) {
+        Some(flags) => flags,
+        None => {
+            eprintln!("{}", FOLD_EQUITY_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let number = |key: &str| flags.get(key).and_then(|v| v.parse::<f32>().ok());
+    let (Some(hero), Some(villain), Some(pot), Some(bet), Some(fold)) = (
+        flags.get("hero"),
+        flags.get("villain"),
+        number("pot"),
+        number("bet"),
+        number("fold"),
+    ) else {
+        eprintln!("{}", FOLD_EQUITY_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let board: &str = flags.get("board").copied().unwrap_or("");
+    // exact solves for every combo of a wide range are slow preflop.
+    let mode: SolveMode = match flags.get("samples").and_then(|v| v.parse::<usize>().ok()) {
+        Some(samples) => SolveMode::MonteCarlo { samples },
+        None => SolveMode::Exact,
+    };
+
+    let equity: Result<RangeEquity, _> = Range::parse(villain)
+        .and_then(|range| Solver::new().equity_vs_range_detailed(hero, &range, board, mode));
+    match equity {
+        Ok(RangeEquity {
+            equity,
+            combos,
+            weight,
+            range_weight,
+        }) => {
+            println!(
+                "Equity against the range is {:.4}, over {} combos weighing {:.2} of the range's {:.2}.",
+                equity, combos, weight, range_weight
+            );
+            println!(
+                "Betting {} into {} with {:.0}% folds is worth {:.2}.",
+                bet,
+                pot,
+                fold * 100.,
+                semi_bluff_ev(pot, bet, fold, equity)
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const BLUFF_USAGE: &str = "usage: bluff --hero 6h5h --villain 'QQ+,AK,JTs' \
+[--board Th7h2c] --pot 100 --bet 75 [--threshold 0.3] [--presets ranges.txt]";
+
+// whether a bluff folds out enough of a range to break even.
+fn run_bluff(args: &[String]) -> ExitCode {
+    let flags: HashMap<&str, &str> = match flags(args) {
+        Some(flags) => flags,
+        None => {
+            eprintln!("{}", BLUFF_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let number = |key: &str| flags.get(key).and_then(|v| v.parse::<f32>().ok());
+    let (Some(hero), Some(villain), Some(pot), Some(bet)) = (
+        flags.get("hero"),
+        flags.get("villain"),
+        number("pot"),
+        number("bet"),
+    ) else {
+        eprintln!("{}", BLUFF_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let board: &str = flags.get("board").copied().unwrap_or("");
+    // the villain can be the name of a saved range.
+    let presets: RangePresets = match flags.get("presets").map(|path| load_presets(path)) {
+        Some(Ok(presets)) => presets,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        None => RangePresets::default(),
+    };
+
+    let check: Result<BluffCheck, _> = presets.resolve(villain).and_then(|range| {
+        Solver::new().bluff_check(hero, &range, board, pot, bet, number("threshold"))
+    });
+    match check {
+        Ok(check) => {
+            println!(
+                "Betting {} into {} has to fold out {:.1}% of the range to break even.",
+                bet,
+                pot,
+                check.break_even * 100.
+            );
+            println!(
+                "The range continues {:.1}% of the time with {:.1}% equity or more, so it folds {:.1}%.",
+                check.continues * 100.,
+                check.threshold * 100.,
+                check.folds() * 100.
+            );
+            if check.profitable() {
+                println!("The bluff is profitable.");
+            } else {
+                println!("The bluff loses money.");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const PUSHFOLD_USAGE: &str = "usage: pushfold [--stack 10] [--small-blind 0.5] \
+[--others 20,30] [--payouts 0.5,0.3,0.2] [--samples 2000] [--seed 0] [--table preflop.bin]";
+
+// the shove and call ranges for one stack, or the charts for 1 to 20 big
+// blinds. with --table the equities come from a preflop table file, worked
+// out and saved there the first time.
+fn run_pushfold(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", PUSHFOLD_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let list = |key: &str| -> Option<Vec<f32>> {
+        match flags.get(key) {
+            Some(v) => v.split(',').map(|x| x.trim().parse::<f32>().ok()).collect(),
+            None => Some(Vec::new()),
+        }
+    };
+    let (Some(others), Some(payouts)) = (list("others"), list("payouts")) else {
+        eprintln!("{}", PUSHFOLD_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let mut spot: PushFoldSpot = PushFoldSpot {
+        others,
+        payouts,
+        ..PushFoldSpot::heads_up(0.)
+    };
+    if let Some(sb) = flags.get("small-blind").and_then(|v| v.parse::<f32>().ok()) {
+        spot.small_blind = sb;
+    }
+    let samples: usize = flags
+        .get("samples")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(2000);
+    let seed: u64 = flags
+        .get("seed")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    let pushfold: PushFold = match flags.get("table") {
+        Some(path) => match load_table(path, samples, seed) {
+            Ok(table) => PushFold::from_table(table),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => {
+            eprintln!("Estimating preflop equities...");
+            PushFold::new(samples, seed)
+        }
+    };
+    match flags.get("stack").and_then(|v| v.parse::<f32>().ok()) {
+        Some(stack) => {
+            spot.stacks = [stack, stack];
+            let strategy: PushFoldStrategy = pushfold.solve(&spot);
+            let text = |r: Option<Range>| r.map_or("nothing".to_string(), |r| r.to_string());
+            println!("Shove: {}", text(strategy.shove_range()));
+            println!("Call: {}", text(strategy.call_range()));
+        }
+        None => {
+            let depths: Vec<f32> = (1..=20).map(|d| d as f32).collect();
+            print!("{}", pushfold.chart(&spot, &depths));
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+const PREFLOP_TABLE_USAGE: &str = "usage: preflop-table --file preflop.bin \
+[--samples 20000] [--seed 0] [--hero AKs --villain 'QQ,JJ']";
+
+// works out every starting hand's equity against every other once and
+// saves it in --file, or loads it from there if it's already been. with
+// --hero and --villain it looks up a matchup, the villain a starting hand
+// or a range.
+fn run_preflop_table(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", PREFLOP_TABLE_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(path) = flags.get("file") else {
+        eprintln!("{}", PREFLOP_TABLE_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let samples: usize = flags
+        .get("samples")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(20_000);
+    let seed: u64 = flags
+        .get("seed")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    let table: PreflopTable = match load_table(path, samples, seed) {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (Some(hero), Some(villain)) = (flags.get("hero"), flags.get("villain")) else {
+        println!("{} runouts per matchup in {}", table.samples, path);
+        return ExitCode::SUCCESS;
+    };
+    let equity: Option<f32> = match table.equity(hero, villain) {
+        Some(equity) => Some(equity),
+        None => match villain.parse::<Range>() {
+            Ok(range) => table.equity_vs_range(hero, &range),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    match equity {
+        Some(equity) => {
+            println!("{} vs {}: {:.2}%", hero, villain, equity * 100.);
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!(
+                "{} isn't a starting hand, or can't be dealt against {}",
+                hero, villain
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// the preflop table in `path`, worked out and saved first if it isn't
+// there yet.
+fn load_table(path: &str, samples: usize, seed: u64) -> std::io::Result<PreflopTable> {
+    if !std::path::Path::new(path).exists() {
+        eprintln!("Working out every preflop matchup, saving to {}...", path);
+    }
+    PreflopTable::load_or_new(path, samples, seed)
+}
+
+const STRENGTH_USAGE: &str = "usage: strength --hero AhKh [--board Th7h2c] [--samples 100000]";
+
+// how the hero's hand does against a single random hand.
+fn run_strength(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", STRENGTH_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(hero) = flags.get("hero") else {
+        eprintln!("{}", STRENGTH_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let board: &str = flags.get("board").copied().unwrap_or("");
+    let mode: SolveMode = match flags.get("samples").and_then(|v| v.parse::<usize>().ok()) {
+        Some(samples) => SolveMode::MonteCarlo { samples },
+        None => SolveMode::Exact,
+    };
+    match Solver::new().hand_strength(hero, board, mode) {
+        Ok(hs) => {
+            println!(
+                "EHS {:.4}, EHS² {:.4} over {} runouts.",
+                hs.ehs, hs.ehs2, hs.runouts
+            );
+            // how the hand ranks right now, once there is a flop.
+            if let Ok(p) = percentile(hero, board) {
+                println!(
+                    "Your {} beats {:.1}% of hands ({} better, {} tied, {} worse).",
+                    p.hand,
+                    p.share() * 100.,
+                    p.loses,
+                    p.ties,
+                    p.beats
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const NUTS_USAGE: &str = "usage: nuts --hero AhKh --board Th7h2c";
+
+// the best possible hand on the board and whether the hero has it.
+fn run_nuts(args: &[String]) -> ExitCode {
+    let (Some(hero), Some(board)) = flags(args)
+        .map(|f| (f.get("hero").copied(), f.get("board").copied()))
+        .unwrap_or_default()
+    else {
+        eprintln!("{}", NUTS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    match nuts(hero, board) {
+        Ok(n) => {
+            // a board that plays can be the nuts for every holding.
+            const SHOWN: usize = 12;
+            let mut combos: String = n.combos[..n.combos.len().min(SHOWN)].join(", ");
+            if n.combos.len() > SHOWN {
+                combos += &format!(" and {} more", n.combos.len() - SHOWN);
+            }
+            println!("The nuts are {}: {}.", n.hand, combos);
+            if n.hero_has_nuts {
+                println!("You have the nuts.");
+            } else {
+                println!("{} holdings beat you.", n.stronger);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const CARDS_USAGE: &str = "usage: cards --hands 'AhKh QsQd' --board Th7h2c";
+
+// the hero's equity after each card that can come next, best first. the
+// first of `--hands` is the hero's.
+fn run_cards(args: &[String]) -> ExitCode {
+    let (Some(hands), Some(board)) = flags(args)
+        .map(|f| (f.get("hands").copied(), f.get("board").copied()))
+        .unwrap_or_default()
+    else {
+        eprintln!("{}", CARDS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let hands: Vec<&str> = hands.split([' ', ',']).filter(|h| !h.is_empty()).collect();
+    match Solver::new().distribution(&hands, board) {
+        Ok(d) => {
+            println!("Equity now {:.4}.", d.mean);
+            if d.by_card.is_empty() {
+                println!("There are no cards left to come.");
+            }
+            for (card, equity) in d.ranked() {
+                println!("{}  {:.4}  {:+.4}", card, equity, equity - d.mean);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const REPLAY_USAGE: &str = "usage: replay --hands 'AhKh QsQd' --board Th7h2c8d3s";
+
+// the hero's equity street by street as the board was dealt.
+fn run_replay(args: &[String]) -> ExitCode {
+    let (Some(hands), Some(board)) = flags(args)
+        .map(|f| (f.get("hands").copied(), f.get("board").copied()))
+        .unwrap_or_default()
+    else {
+        eprintln!("{}", REPLAY_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let hands: Vec<&str> = hands.split([' ', ',']).filter(|h| !h.is_empty()).collect();
+    match Solver::new().replay(&hands, board) {
+        Ok(streets) => {
+            let header: Vec<String> = hands.iter().map(|h| format!("{:<6}", h)).collect();
+            println!("{:<8} {:<10} {}", "", "", header.join(" ").trim_end());
+            for street in streets {
+                let equities: Vec<String> = street
+                    .equities
+                    .iter()
+                    .map(|e| format!("{:.4}", e))
+                    .collect();
+                println!(
+                    "{:<8} {:<10} {}",
+                    street.name,
+                    street.board,
+                    equities.join(" ")
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const SESSION_USAGE: &str =
+    "usage: session --file hands.txt [--samples 100000] [--trials 10000] [--seed 0]";
+
+// the hero's winnings over a PokerStars hand history next to their all-in
+// adjusted winnings.
+fn run_session(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", SESSION_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(path) = flags.get("file") else {
+        eprintln!("{}", SESSION_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let text: String = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mode: SolveMode = match flags.get("samples").and_then(|v| v.parse::<usize>().ok()) {
+        Some(samples) => SolveMode::MonteCarlo { samples },
+        None => SolveMode::Exact,
+    };
+    let report: Result<SessionReport, _> = parse_hand_histories(&text)
+        .and_then(|histories| all_in_report(&Solver::new(), &histories, mode));
+    match report {
+        Ok(report) => {
+            for a in report.all_ins.iter() {
+                println!(
+                    "#{} {} on '{}': {:.1}% of {:.2} is {:.2}, won {:.2}.",
+                    a.hand_id,
+                    a.hands.join(" vs "),
+                    a.board,
+                    a.equity * 100.,
+                    a.pot,
+                    a.expected,
+                    a.won
+                );
+            }
+            println!(
+                "{} hands, {} all ins. Won {:.2}, all-in adjusted {:.2}, {:+.2} from luck.",
+                report.hands,
+                report.all_ins.len(),
+                report.winnings,
+                report.adjusted,
+                report.luck()
+            );
+            if !report.all_ins.is_empty() {
+                let trials: usize = flags
+                    .get("trials")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(10_000);
+                let seed: u64 = flags
+                    .get("seed")
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(0);
+                print_variance(&report.variance(trials, seed));
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// the spread of the simulated sessions as a sideways histogram, with the
+// hero's result marked.
+fn print_variance(variance: &Variance) {
+    println!(
+        "The all ins were worth {:.2} ± {:.2} and paid {:.2}, luck percentile {:.0}%.",
+        variance.expected,
+        variance.std_dev,
+        variance.actual,
+        variance.percentile * 100.
+    );
+    let most: u32 = variance.counts.iter().copied().max().unwrap_or(0).max(1);
+    let width: f32 = (variance.high - variance.low) / variance.counts.len() as f32;
+    let hero: usize = variance.bucket(variance.actual);
+    for (i, &count) in variance.counts.iter().enumerate() {
+        let bar: String = "#".repeat((count as f32 / most as f32 * 40.).round() as usize);
+        let line: String = format!("{:>10.2} {}", variance.low + width * i as f32, bar)
+            .trim_end()
+            .to_string();
+        if i == hero {
+            println!("{:<51} <- you", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+const DRILLS_USAGE: &str = "usage: drills (--file matchups.txt | --random 50 [--players 2] \
+[--seed 0]) [--samples 100000] [--out deck.csv]";
+
+// a deck of equity flashcards for anki, from the matchups in a file (one
+// "AhKh QsQd on Th7h2c" a line) or from random preflop hands.
+fn run_drills(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", DRILLS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let parse = |key: &str| flags.get(key).and_then(|v| v.parse::<u64>().ok());
+    let matchups: Vec<(Vec<String>, String)> = match (flags.get("file"), parse("random")) {
+        (Some(path), _) => match std::fs::read_to_string(path) {
+            Ok(text) => parse_matchups(&text),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        },
+        (None, Some(n)) => random_matchups(
+            n as usize,
+            parse("players").unwrap_or(2) as usize,
+            parse("seed").unwrap_or(0),
+        ),
+        (None, None) => {
+            eprintln!("{}", DRILLS_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mode: SolveMode = match parse("samples") {
+        Some(samples) => SolveMode::MonteCarlo {
+            samples: samples as usize,
+        },
+        None => SolveMode::Exact,
+    };
+
+    eprintln!("Solving {} matchups...", matchups.len());
+    let cards: Vec<Flashcard> = match equity_drills(&Solver::new(), &matchups, mode) {
+        Ok(cards) => cards,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match flags.get("out") {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, to_csv(&cards)) {
+                eprintln!("{}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+            println!("Wrote {} cards to {}.", cards.len(), path);
+        }
+        None => print!("{}", to_csv(&cards)),
+    }
+    ExitCode::SUCCESS
+}
+
+const RANGES_USAGE: &str =
+    "usage: ranges --presets ranges.txt [--import export.txt --name 'BTN open']";
+
+// lists the saved ranges, or saves a range exported from a tracker under
+// a name.
+fn run_ranges(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", RANGES_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(path) = flags.get("presets").copied() else {
+        eprintln!("{}", RANGES_USAGE);
+        return ExitCode::FAILURE;
+    };
+    // a presets file is made by the first import.
+    let loaded: Result<RangePresets, String> = match std::path::Path::new(path).exists() {
+        true => load_presets(path),
+        false => Ok(RangePresets::default()),
+    };
+    let mut presets: RangePresets = match loaded {
+        Ok(presets) => presets,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (Some(export), Some(name)) = (flags.get("import"), flags.get("name")) else {
+        for name in presets.names() {
+            let range: &Range = presets.get(name).expect("listed presets exist");
+            println!("{}: {} combos, {}", name, range.combos().len(), range);
+        }
+        return ExitCode::SUCCESS;
+    };
+    let imported: Result<Range, String> = std::fs::read_to_string(export)
+        .map_err(|e| format!("{}: {}", export, e))
+        .and_then(|text| Range::import(&text).map_err(|e| e.to_string()));
+    let range: Range = match imported {
+        Ok(range) => range,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!(
+        "Saved '{}', {} combos: {}",
+        name,
+        range.combos().len(),
+        range
+    );
+    presets.insert(name, range);
+    if let Err(e) = std::fs::write(path, presets.to_string()) {
+        eprintln!("{}: {}", path, e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+const CHART_USAGE: &str = "usage: chart [--opponents 1] [--villain 'QQ+,AK'] \
+[--presets ranges.txt] [--samples 10000] [--seed 0]";
+
+// every starting hand's equity against random hands or a range, laid out
+// as the 13x13 chart.
+fn run_chart(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", CHART_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let number = |key: &str, default: u64| {
+        flags
+            .get(key)
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(default)
+    };
+    let presets: RangePresets = match flags.get("presets").map(|path| load_presets(path)) {
+        Some(Ok(presets)) => presets,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        None => RangePresets::default(),
+    };
+    let chart: Result<PreflopChart, _> = flags
+        .get("villain")
+        .map(|v| presets.resolve(v))
+        .transpose()
+        .and_then(|villain| {
+            PreflopChart::new(
+                number("opponents", 1) as usize,
+                villain.as_ref(),
+                number("samples", 10_000) as usize,
+                number("seed", 0),
+            )
+        });
+    match chart {
+        Ok(chart) => {
+            print!("{}", chart);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+const MATRIX_USAGE: &str = "usage: matrix --hero 'QQ+,AK' --villain 'TT+,AQ+' \
+[--board Th7h2c] [--samples 100000] [--format grid|csv|grid-csv|json] [--presets ranges.txt]";
+
+// every combo of the hero's range against the villain's range on a board,
+// as the 13x13 grid or exported for a heatmap.
+fn run_matrix(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", MATRIX_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let (Some(hero), Some(villain)) = (flags.get("hero"), flags.get("villain")) else {
+        eprintln!("{}", MATRIX_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let presets: RangePresets = match flags.get("presets").map(|path| load_presets(path)) {
+        Some(Ok(presets)) => presets,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        None => RangePresets::default(),
+    };
+    let board: &str = flags.get("board").copied().unwrap_or("");
+    // every runout preflop is 1.7 million, sampled unless asked otherwise.
+    let samples: Option<usize> = match flags.get("samples") {
+        Some(v) => v.parse::<usize>().ok(),
+        None if board.is_empty() => Some(100_000),
+        None => None,
+    };
+    let mode: SolveMode = match samples {
+        Some(samples) => SolveMode::MonteCarlo { samples },
+        None => SolveMode::Exact,
+    };
+    let solver: Solver = Solver::builder().mode(mode).seed(0).build();
+    let matrix: Result<RangeMatrix, _> = presets.resolve(hero).and_then(|hero| {
+        let villain: Range = presets.resolve(villain)?;
+        solver.range_matrix(&hero, &villain, board)
+    });
+    let matrix: RangeMatrix = match matrix {
+        Ok(matrix) => matrix,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match flags.get("format").copied().unwrap_or("grid") {
+        "grid" => print!("{}", matrix),
+        "csv" => print!("{}", matrix.to_csv()),
+        "grid-csv" => print!("{}", matrix.grid_csv()),
+        "json" => println!("{}", matrix.to_json()),
+        _ => {
+            eprintln!("{}", MATRIX_USAGE);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+const FLOPS_USAGE: &str = "usage: flops --hero 'QQ+,AK' --villain 'TT+,AQ+' \
+[--sample 200] [--seed 0] [--format table|csv|groups-csv] [--presets ranges.txt]";
+
+// the hero range's equity against the villain's over every flop, or a
+// sample of them, averaged by high card, suits, pairing and connectedness.
+fn run_flops(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", FLOPS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let (Some(hero), Some(villain)) = (flags.get("hero"), flags.get("villain")) else {
+        eprintln!("{}", FLOPS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let presets: RangePresets = match flags.get("presets").map(|path| load_presets(path)) {
+        Some(Ok(presets)) => presets,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        None => RangePresets::default(),
+    };
+    let subset: FlopSubset = match flags.get("sample").map(|v| v.parse::<usize>()) {
+        Some(Ok(flops)) => FlopSubset::Sample {
+            flops,
+            seed: flags
+                .get("seed")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0),
+        },
+        Some(Err(_)) => {
+            eprintln!("{}", FLOPS_USAGE);
+            return ExitCode::FAILURE;
+        }
+        None => FlopSubset::All,
+    };
+    let report: Result<FlopReport, _> = presets.resolve(hero).and_then(|hero| {
+        let villain: Range = presets.resolve(villain)?;
+        FlopReport::new(&hero, &villain, subset)
+    });
+    let report: FlopReport = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match flags.get("format").copied().unwrap_or("table") {
+        "table" => print!("{}", report),
+        "csv" => print!("{}", report.to_csv()),
+        "groups-csv" => print!("{}", report.groups_csv()),
+        _ => {
+            eprintln!("{}", FLOPS_USAGE);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+const TEXTURES_USAGE: &str = "usage: textures --hands 'AhKh QsQd' [--board 7c8c9c] \
+[--street flop|turn|river] [--samples 100000] [--format table|csv]";
+
+// a matchup's equities split by board texture on a street, e.g. how the
+// hands do on monotone against two-tone or paired flops.
+fn run_textures(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", TEXTURES_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(hands) = flags.get("hands") else {
+        eprintln!("{}", TEXTURES_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let hands: Vec<&str> = hands.split_whitespace().collect();
+    let cards: usize = match flags.get("street").copied().unwrap_or("flop") {
+        "flop" => 3,
+        "turn" => 4,
+        "river" => 5,
+        _ => {
+            eprintln!("{}", TEXTURES_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mode: SolveMode = match flags.get("samples").map(|v| v.parse::<usize>()) {
+        Some(Ok(samples)) => SolveMode::MonteCarlo { samples },
+        Some(Err(_)) => {
+            eprintln!("{}", TEXTURES_USAGE);
+            return ExitCode::FAILURE;
+        }
+        None => SolveMode::Exact,
+    };
+    let solver: Solver = Solver::builder().mode(mode).seed(0).build();
+    let board: &str = flags.get("board").copied().unwrap_or("");
+    let report: TextureReport = match solver.texture_report(&hands, board, cards) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match flags.get("format").copied().unwrap_or("table") {
+        "table" => print!("{}", report),
+        "csv" => print!("{}", report.to_csv()),
+        _ => {
+            eprintln!("{}", TEXTURES_USAGE);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+const BUCKETS_USAGE: &str = "usage: buckets --out buckets.bin [--street flop|turn|river] \
+[--boards 100] [--buckets 50] [--metric ehs|ehs2] [--bucketing width|percentile] \
+[--samples 200] [--seed 0] [--format bin|csv]";
+
+// every hand's strength on random boards, bucketed for a card abstraction
+// and written to --out. without --samples every runout is walked.
+fn run_buckets(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", BUCKETS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(out) = flags.get("out") else {
+        eprintln!("{}", BUCKETS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let number = |key: &str, default: usize| -> Option<usize> {
+        flags
+            .get(key)
+            .map_or(Some(default), |v| v.parse::<usize>().ok())
+    };
+    let mut options: BucketOptions = BucketOptions::default();
+    let (Some(boards), Some(buckets), Some(seed)) = (
+        number("boards", 100),
+        number("buckets", 50),
+        number("seed", 0),
+    ) else {
+        eprintln!("{}", BUCKETS_USAGE);
+        return ExitCode::FAILURE;
+    };
+    (options.boards, options.buckets, options.seed) = (boards, buckets, seed as u64);
+    options.cards = match flags.get("street").copied().unwrap_or("flop") {
+        "flop" => 3,
+        "turn" => 4,
+        "river" => 5,
+        _ => {
+            eprintln!("{}", BUCKETS_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    options.metric = match flags.get("metric").copied().unwrap_or("ehs") {
+        "ehs" => Metric::Ehs,
+        "ehs2" => Metric::Ehs2,
+        _ => {
+            eprintln!("{}", BUCKETS_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    options.bucketing = match flags.get("bucketing").copied().unwrap_or("width") {
+        "width" => Bucketing::Width,
+        "percentile" => Bucketing::Percentile,
+        _ => {
+            eprintln!("{}", BUCKETS_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mode: SolveMode = match flags.get("samples").map(|v| v.parse::<usize>()) {
+        Some(Ok(samples)) => SolveMode::MonteCarlo { samples },
+        Some(Err(_)) => {
+            eprintln!("{}", BUCKETS_USAGE);
+            return ExitCode::FAILURE;
+        }
+        None => SolveMode::Exact,
+    };
+    let bytes: fn(&EquityBuckets) -> Vec<u8> = match flags.get("format").copied().unwrap_or("bin") {
+        "bin" => EquityBuckets::to_bytes,
+        "csv" => |buckets| buckets.to_csv().into_bytes(),
+        _ => {
+            eprintln!("{}", BUCKETS_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let solver: Solver = Solver::builder().mode(mode).build();
+    let buckets: EquityBuckets = match solver.equity_buckets(options) {
+        Ok(buckets) => buckets,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = std::fs::write(out, bytes(&buckets)) {
+        eprintln!("Couldn't write {}: {}", out, e);
+        return ExitCode::FAILURE;
+    }
+    let hands: usize = buckets.boards.iter().map(|b| b.hands.len()).sum();
+    println!(
+        "{} hands on {} boards written to {}",
+        hands,
+        buckets.boards.len(),
+        out
+    );
+    ExitCode::SUCCESS
+}
+
+const HISTORY_USAGE: &str = "usage: history --file results.txt \
+[--hands 'AhKh QsQd'] [--board 7c8c9c] [--last 20]";
+
+// the solves recorded with --history, newest last. with --hands (and
+// --board) only that spot's, in any suits.
+fn run_history(args: &[String]) -> ExitCode {
+    let Some(flags) = flags(args) else {
+        eprintln!("{}", HISTORY_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let Some(path) = flags.get("file") else {
+        eprintln!("{}", HISTORY_USAGE);
+        return ExitCode::FAILURE;
+    };
+    let spot: Option<(Vec<String>, String)> = match flags.get("hands") {
+        Some(hands) => {
+            let hands: Vec<&str> = hands.split([' ', ',']).filter(|h| !h.is_empty()).collect();
+            match canonical_scenario(&hands, flags.get("board").copied().unwrap_or("")) {
+                Ok(spot) => Some(spot),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => None,
+    };
+    let records: Vec<StoredResult> = match ResultStore::open(path).and_then(|s| s.records()) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let matching: Vec<&StoredResult> = records
+        .iter()
+        .filter(|r| {
+            spot.as_ref()
+                .is_none_or(|(hands, board)| &r.hands == hands && &r.board == board)
+        })
+        .collect();
+    let last: usize = flags
+        .get("last")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(matching.len());
+    for r in &matching[matching.len().saturating_sub(last)..] {
+        println!(
+            "{}  {} on '{}'  {:?}  {:?}  seed {}  {} threads  {:.4}",
+            r.timestamp,
+            r.hands.join(" vs "),
+            r.board,
+            r.mode,
+            r.sampling,
+            r.seed.map_or("-".to_string(), |s| s.to_string()),
+            r.threads,
+            r.equity
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+fn load_presets(path: &str) -> Result<RangePresets, String> {
+    let text: String = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    RangePresets::parse(&text).map_err(|e| format!("{}: {}", path, e))
+}
 
-fn main() {
-    parse_input_and_solve();
+// "--key value" pairs, None if the arguments don't pair up.
+fn flags(args: &[String]) -> Option<HashMap<&str, &str>> {
+    if !args.len().is_multiple_of(2) {
+        return None;
+    }
+    args.chunks(2)
+        .map(|kv| Some((kv[0].strip_prefix("--")?, kv[1].as_str())))
+        .collect()
 }
@@ -0,0 +1,300 @@
+use crate::canonical::{cell_at, cell_name, cell_of, combo_name, CLASSES};
+use crate::range::Range;
+use crate::solver::{ladder_score, run_workers};
+use poker_odds_core::VALUES;
+use std::fmt;
+use std::sync::Arc;
+
+// how much of the villain range's weight a hero combo beat, counting ties
+// half, and how much it was up against, summed over runouts.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Score {
+    pub won: f64,
+    pub against: f64,
+}
+
+// scores weighted hero combos against a weighted villain range, one runout
+// at a time. each runout scores every combo once, and the villains are
+// sorted by score so each hero looks up how many it beats instead of going
+// through them. villains sharing a card with a hero are taken back out.
+pub(crate) struct RunoutScorer<'a> {
+    heroes: &'a [(u64, f32)],
+    villains: &'a [(u64, f32)],
+    // the villains sharing a card with each hero combo.
+    blocked: Vec<Vec<usize>>,
+    scores: Vec<Option<u64>>,
+    sorted: Vec<(u64, f64)>,
+    // the villain weight scored below each place in `sorted`.
+    below: Vec<f64>,
+}
+
+impl<'a> RunoutScorer<'a> {
+    // combos sharing a card with a runout sit it out, so the cards every
+    // runout shares are best taken out of them up front.
+    pub(crate) fn new(heroes: &'a [(u64, f32)], villains: &'a [(u64, f32)]) -> Self {
+        let blocked: Vec<Vec<usize>> = heroes
+            .iter()
+            .map(|h| {
+                (0..villains.len())
+                    .filter(|&v| villains[v].0 & h.0 != 0)
+                    .collect()
+            })
+            .collect();
+        RunoutScorer {
+            heroes,
+            villains,
+            blocked,
+            scores: vec![None; villains.len()],
+            sorted: Vec::with_capacity(villains.len()),
+            below: Vec::with_capacity(villains.len() + 1),
+        }
+    }
+
+    // adds each hero's score on the five card `board` to `out`, by hero.
+    pub(crate) fn add(&mut self, board: u64, out: &mut [Score]) {
+        self.sorted.clear();
+        for (score, &(cards, w)) in self.scores.iter_mut().zip(self.villains.iter()) {
+            *score = (cards & board == 0).then(|| ladder_score(cards | board));
+            if let Some(s) = *score {
+                self.sorted.push((s, w as f64));
+            }
+        }
+        self.sorted.sort_unstable_by_key(|&(s, _)| s);
+        self.below.clear();
+        self.below.push(0.);
+        for &(_, w) in self.sorted.iter() {
+            self.below.push(self.below.last().unwrap() + w);
+        }
+        let all: f64 = *self.below.last().unwrap();
+
+        for (h, &(cards, _)) in self.heroes.iter().enumerate() {
+            if cards & board != 0 {
+                continue;
+            }
+            let score: u64 = ladder_score(cards | board);
+            let lo: usize = self.sorted.partition_point(|&(s, _)| s < score);
+            let hi: usize = self.sorted.partition_point(|&(s, _)| s <= score);
+            let (mut beats, mut ties, mut against) =
+                (self.below[lo], self.below[hi] - self.below[lo], all);
+            for &v in self.blocked[h].iter() {
+                let Some(s) = self.scores[v] else {
+                    continue;
+                };
+                let vw: f64 = self.villains[v].1 as f64;
+                against -= vw;
+                if s < score {
+                    beats -= vw;
+                } else if s == score {
+                    ties -= vw;
+                }
+            }
+            out[h].won += beats + ties / 2.;
+            out[h].against += against;
+        }
+    }
+}
+
+// the scores of `heroes` against `villains` over `runouts`, split over
+// `nthreads` workers.
+pub(crate) fn score_runouts(
+    heroes: Vec<(u64, f32)>,
+    villains: Vec<(u64, f32)>,
+    runouts: Vec<u64>,
+    nthreads: usize,
+) -> Vec<Score> {
+    let n: usize = heroes.len();
+    let (heroes, villains, runouts) = (Arc::new(heroes), Arc::new(villains), Arc::new(runouts));
+    let workers: Vec<_> = (0..nthreads)
+        .map(|t| {
+            let (heroes, villains, runouts) = (heroes.clone(), villains.clone(), runouts.clone());
+            move |report: &mut dyn FnMut(Vec<Score>)| {
+                let mut scorer: RunoutScorer = RunoutScorer::new(&heroes, &villains);
+                let mut scores: Vec<Score> = vec![Score::default(); heroes.len()];
+                for &board in runouts.iter().skip(t).step_by(nthreads) {
+                    scorer.add(board, &mut scores);
+                }
+                report(scores);
+            }
+        })
+        .collect();
+    let mut scores: Vec<Score> = vec![Score::default(); n];
+    run_workers(workers, |worker: Vec<Score>| {
+        for (total, s) in scores.iter_mut().zip(worker) {
+            total.won += s.won;
+            total.against += s.against;
+        }
+    });
+    scores
+}
+
+// one hero combo's equity against the villain's range.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComboEquity {
+    // e.g. "AhKd".
+    pub hand: String,
+    // the starting hand it's a combo of, e.g. "AKo".
+    pub class: String,
+    pub weight: f32,
+    // None when it shares a card with the board, or every villain combo
+    // shares one with it.
+    pub equity: Option<f32>,
+}
+
+// every combo of the hero's range against the villain's range on a board,
+// for heatmaps over the 13x13 grid.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeMatrix {
+    pub hero: Range,
+    pub villain: Range,
+    pub board: String,
+    // in the order the hero's range lists them.
+    pub combos: Vec<ComboEquity>,
+}
+
+impl RangeMatrix {
+    pub(crate) fn new(hero: &Range, villain: &Range, board: &str, scores: &[Score]) -> Self {
+        let combos: Vec<ComboEquity> = hero
+            .combos()
+            .iter()
+            .filter(|c| c.weight > 0.)
+            .zip(scores)
+            .map(|(c, s)| ComboEquity {
+                hand: combo_name(c.cards()),
+                class: cell_name(cell_of_combo(c.cards())),
+                weight: c.weight,
+                equity: (s.against > 0.).then(|| (s.won / s.against) as f32),
+            })
+            .collect();
+        RangeMatrix {
+            hero: hero.clone(),
+            villain: villain.clone(),
+            board: board.to_string(),
+            combos,
+        }
+    }
+
+    // by cell of the 13x13 chart, the average of its combos' equities by
+    // weight. None for hands the hero's range doesn't hold or can't deal.
+    pub fn cells(&self) -> Vec<Option<f32>> {
+        let mut sums: Vec<(f64, f64)> = vec![(0., 0.); CLASSES];
+        for c in &self.combos {
+            let (Some(e), Some(i)) = (c.equity, cell_of(&c.class)) else {
+                continue;
+            };
+            sums[i].0 += e as f64 * c.weight as f64;
+            sums[i].1 += c.weight as f64;
+        }
+        sums.iter()
+            .map(|&(e, w)| (w > 0.).then(|| (e / w) as f32))
+            .collect()
+    }
+
+    // a line per combo: hand, starting hand, weight and equity, empty when
+    // the combo can't be dealt.
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = String::from("hand,class,weight,equity\n");
+        for c in &self.combos {
+            let equity: String = c.equity.map(|e| format!("{:.4}", e)).unwrap_or_default();
+            csv += &format!("{},{},{},{}\n", c.hand, c.class, c.weight, equity);
+        }
+        csv
+    }
+
+    // the 13x13 grid as csv, the values heading the rows and columns.
+    pub fn grid_csv(&self) -> String {
+        let values: Vec<String> = VALUES
+            .iter()
+            .rev()
+            .map(|v| (*v as char).to_string())
+            .collect();
+        let mut csv: String = format!(",{}\n", values.join(","));
+        for (row, cells) in self.cells().chunks(13).enumerate() {
+            let cells: Vec<String> = cells
+                .iter()
+                .map(|e| e.map(|e| format!("{:.4}", e)).unwrap_or_default())
+                .collect();
+            csv += &format!("{},{}\n", values[row], cells.join(","));
+        }
+        csv
+    }
+
+    // {"board", "hero", "villain", "combos": [{"hand", "class", "weight",
+    // "equity"}], "cells": {"AKs": equity, ...}}, equities null when the
+    // hand can't be dealt.
+    pub fn to_json(&self) -> String {
+        let number = |e: Option<f32>| e.map_or("null".to_string(), |e| format!("{:.4}", e));
+        let combos: Vec<String> = self
+            .combos
+            .iter()
+            .map(|c| {
+                format!(
+                    "{{\"hand\":\"{}\",\"class\":\"{}\",\"weight\":{},\"equity\":{}}}",
+                    c.hand,
+                    c.class,
+                    c.weight,
+                    number(c.equity)
+                )
+            })
+            .collect();
+        let cells: Vec<String> = self
+            .cells()
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_some())
+            .map(|(i, &e)| format!("\"{}\":{}", cell_name(i), number(e)))
+            .collect();
+        format!(
+            "{{\"board\":\"{}\",\"hero\":\"{}\",\"villain\":\"{}\",\"combos\":[{}],\"cells\":{{{}}}}}",
+            self.board,
+            json_escape(&self.hero.to_string()),
+            json_escape(&self.villain.to_string()),
+            combos.join(","),
+            cells.join(",")
+        )
+    }
+}
+
+impl fmt::Display for RangeMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.board.is_empty() {
+            true => writeln!(f, "Equity (%) of {} vs {} preflop", self.hero, self.villain)?,
+            false => writeln!(
+                f,
+                "Equity (%) of {} vs {} on {}",
+                self.hero, self.villain, self.board
+            )?,
+        }
+        write!(f, "   ")?;
+        for v in VALUES.iter().rev() {
+            write!(f, "{:>5}", *v as char)?;
+        }
+        writeln!(f)?;
+        for (row, cells) in self.cells().chunks(13).enumerate() {
+            write!(f, "{:>3}", VALUES[12 - row] as char)?;
+            for equity in cells {
+                match equity {
+                    Some(e) => write!(f, "{:>5.1}", e * 100.)?,
+                    None => write!(f, "{:>5}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// the chart cell of a combo's two cards.
+pub(crate) fn cell_of_combo(cards: u64) -> usize {
+    let (a, b) = (
+        cards.trailing_zeros() as usize,
+        63 - cards.leading_zeros() as usize,
+    );
+    let (hi, lo) = ((a / 4).max(b / 4), (a / 4).min(b / 4));
+    cell_at(hi, lo, a % 4 == b % 4)
+}
+
+fn json_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,87 @@
+#[cfg(feature = "parallel")]
+use dashmap::DashMap;
+#[cfg(not(feature = "parallel"))]
+use std::collections::HashMap;
+use std::hash::Hash;
+#[cfg(not(feature = "parallel"))]
+use std::sync::RwLock;
+
+// concurrent map behind the memo tables. dashmap when solving on many
+// threads, a plain locked hashmap otherwise (e.g. wasm).
+#[cfg(feature = "parallel")]
+#[derive(Debug)]
+pub struct Memo<K: Hash + Eq, V>(DashMap<K, V>);
+
+#[cfg(not(feature = "parallel"))]
+#[derive(Debug)]
+pub struct Memo<K, V>(RwLock<HashMap<K, V>>);
+
+#[cfg(feature = "parallel")]
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo(DashMap::new())
+    }
+
+    pub fn with_capacity_and_shard_amount(capacity: usize, shards: usize) -> Self {
+        Memo(DashMap::with_capacity_and_shard_amount(capacity, shards))
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.0.get(key).map(|v| v.clone())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.0.insert(key, value);
+    }
+
+    pub fn get_or_insert_with(&self, key: K, f: impl FnOnce() -> V) -> V {
+        self.0.entry(key).or_insert_with(f).clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn values(&self) -> Vec<V> {
+        self.0.iter().map(|e| e.value().clone()).collect()
+    }
+
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo(RwLock::new(HashMap::new()))
+    }
+
+    pub fn with_capacity_and_shard_amount(capacity: usize, _shards: usize) -> Self {
+        Memo(RwLock::new(HashMap::with_capacity(capacity)))
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.0.read().unwrap().get(key).cloned()
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.0.write().unwrap().insert(key, value);
+    }
+
+    pub fn get_or_insert_with(&self, key: K, f: impl FnOnce() -> V) -> V {
+        self.0.write().unwrap().entry(key).or_insert_with(f).clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.read().unwrap().len()
+    }
+
+    pub fn values(&self) -> Vec<V> {
+        self.0.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.0.write().unwrap().clear();
+    }
+}
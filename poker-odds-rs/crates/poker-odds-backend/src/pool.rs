@@ -0,0 +1,71 @@
+use crate::solver::default_threads;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// the threads every solve runs its workers on, one per core, started the
+// first time they're needed and kept for the life of the process. solves
+// (the gui's, the server's, a batch's) don't pay for spawning threads,
+// and however many run at once only as many workers as there are cores
+// run together. the rest wait for a thread.
+pub(crate) struct WorkerPool {
+    jobs: Sender<Job>,
+}
+
+static POOL: OnceLock<WorkerPool> = OnceLock::new();
+
+thread_local! {
+    static IN_POOL: Cell<bool> = const { Cell::new(false) };
+}
+
+impl WorkerPool {
+    pub(crate) fn global() -> &'static WorkerPool {
+        POOL.get_or_init(|| WorkerPool::new(default_threads()))
+    }
+
+    fn new(threads: usize) -> Self {
+        let (jobs, rx) = mpsc::channel::<Job>();
+        let rx: Arc<Mutex<Receiver<Job>>> = Arc::new(Mutex::new(rx));
+        for i in 0..threads.max(1) {
+            let rx = rx.clone();
+            thread::Builder::new()
+                .name(format!("poker-odds-worker-{}", i))
+                .spawn(move || {
+                    IN_POOL.with(|p| p.set(true));
+                    loop {
+                        // the lock is let go before the job runs.
+                        let job: Job = match rx.lock().unwrap().recv() {
+                            Ok(job) => job,
+                            Err(_) => return,
+                        };
+                        job();
+                    }
+                })
+                .expect("can't start a worker thread");
+        }
+        WorkerPool { jobs }
+    }
+
+    // whether the calling thread is one of the pool's. work queued from
+    // there could wait forever behind the job waiting for it.
+    pub(crate) fn on_worker() -> bool {
+        IN_POOL.with(Cell::get)
+    }
+
+    // runs `job` on the next free thread. a panic is caught and handed to
+    // `done` instead of taking the thread down, as is a clean finish.
+    pub(crate) fn execute(
+        &self,
+        job: impl FnOnce() + Send + 'static,
+        done: Sender<thread::Result<()>>,
+    ) {
+        let job: Job = Box::new(move || {
+            let _ = done.send(panic::catch_unwind(AssertUnwindSafe(job)));
+        });
+        self.jobs.send(job).expect("the worker threads are gone");
+    }
+}
@@ -0,0 +1,381 @@
+use crate::canonical::{cell_combos, cell_name, cell_of, CLASSES};
+use crate::error::Error;
+use crate::matrix::cell_of_combo;
+use crate::range::{Combo, Range};
+use crate::solver::{deal, default_threads, ladder_score, run_workers};
+use poker_odds_core::VALUES;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+// tries at dealing an opponent a combo of the range that's still live
+// before the sample is dropped.
+const DEAL_ATTEMPTS: usize = 100;
+
+// every starting hand's equity preflop, laid out like the usual 13x13
+// chart: aces in the first row and column, pairs on the diagonal, suited
+// hands above it and offsuit hands below.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreflopChart {
+    // the hands by cell, e.g. "AKs".
+    pub hands: Vec<String>,
+    // by cell, None when the hand can't be dealt against the range.
+    pub equity: Vec<Option<f32>>,
+    pub opponents: usize,
+    // the range the opponents were dealt from, random hands when None.
+    pub villain: Option<Range>,
+}
+
+impl PreflopChart {
+    // deals `samples` runouts per hand against `opponents` random hands, or
+    // hands from `villain`, by monte carlo. ten thousand keep each cell
+    // within a percent and take a fraction of a second.
+    pub fn new(
+        opponents: usize,
+        villain: Option<&Range>,
+        samples: usize,
+        seed: u64,
+    ) -> Result<Self, Error> {
+        if !(1..=9).contains(&opponents) {
+            return Err(Error::PlayerCount(opponents + 1));
+        }
+        let combos: Option<Arc<[Combo]>> = villain.map(|range| {
+            let live = range.combos().iter().filter(|c| c.weight > 0.);
+            live.cloned().collect()
+        });
+        if combos.as_ref().is_some_and(|c| c.is_empty()) {
+            return Err(Error::EmptyRange);
+        }
+
+        let nthreads: usize = default_threads();
+        let workers: Vec<_> = (0..nthreads)
+            .map(|t| {
+                let combos = combos.clone();
+                move |report: &mut dyn FnMut((usize, Option<f32>))| {
+                    for i in (t..CLASSES).step_by(nthreads) {
+                        let mut rng: StdRng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+                        let equity: Option<f32> =
+                            cell_equity(i, opponents, combos.as_deref(), samples, &mut rng);
+                        report((i, equity));
+                    }
+                }
+            })
+            .collect();
+        let mut equity: Vec<Option<f32>> = vec![None; CLASSES];
+        run_workers(workers, |(i, e): (usize, Option<f32>)| equity[i] = e);
+
+        Ok(PreflopChart {
+            hands: (0..CLASSES).map(cell_name).collect(),
+            equity,
+            opponents,
+            villain: villain.cloned(),
+        })
+    }
+
+    // e.g. "AKs".
+    pub fn equity(&self, hand: &str) -> Option<f32> {
+        self.equity[cell_of(hand)?]
+    }
+}
+
+impl fmt::Display for PreflopChart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.villain {
+            Some(range) => writeln!(f, "Equity (%) vs {} of {}", self.opponents, range)?,
+            None => writeln!(f, "Equity (%) vs {} random hands", self.opponents)?,
+        }
+        write!(f, "   ")?;
+        for v in VALUES.iter().rev() {
+            write!(f, "{:>5}", *v as char)?;
+        }
+        writeln!(f)?;
+        for (row, v) in VALUES.iter().rev().enumerate() {
+            write!(f, "{:>3}", *v as char)?;
+            for equity in &self.equity[row * 13..row * 13 + 13] {
+                match equity {
+                    Some(e) => write!(f, "{:>5.1}", e * 100.)?,
+                    None => write!(f, "{:>5}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// the equity of the hand in cell `i`, its combos taking turns. None when
+// no sample could be dealt.
+fn cell_equity(
+    i: usize,
+    opponents: usize,
+    villain: Option<&[Combo]>,
+    samples: usize,
+    rng: &mut StdRng,
+) -> Option<f32> {
+    let hero: Vec<u64> = cell_combos(i);
+    let total: f32 = villain.map_or(0., |c| c.iter().map(|c| c.weight).sum());
+    let mut won: f64 = 0.;
+    let mut dealt: usize = 0;
+    let mut hands: Vec<u64> = Vec::with_capacity(opponents);
+    for s in 0..samples {
+        let mut dead: u64 = hero[s % hero.len()];
+        hands.clear();
+        if let Some(combos) = villain {
+            for _ in 0..opponents {
+                let Some(hand) = pick(combos, total, dead, rng) else {
+                    break;
+                };
+                dead |= hand;
+                hands.push(hand);
+            }
+            if hands.len() < opponents {
+                continue;
+            }
+        }
+
+        let mut deck: Vec<usize> = (0..52).filter(|c| dead & 1 << c == 0).collect();
+        let random: usize = opponents - hands.len();
+        deal(0, &mut deck, random * 2 + 5, rng);
+        for pair in deck[..random * 2].chunks(2) {
+            hands.push(1 << pair[0] | 1 << pair[1]);
+        }
+        let board: u64 = deck[random * 2..random * 2 + 5]
+            .iter()
+            .fold(0, |acc, c| acc | 1 << c);
+
+        let score: u64 = ladder_score(hero[s % hero.len()] | board);
+        let best: u64 = hands.iter().map(|&h| ladder_score(h | board)).max()?;
+        let tied: usize = hands
+            .iter()
+            .filter(|&&h| ladder_score(h | board) == best)
+            .count();
+        won += match score.cmp(&best) {
+            Ordering::Greater => 1.,
+            Ordering::Equal => 1. / (tied + 1) as f64,
+            Ordering::Less => 0.,
+        };
+        dealt += 1;
+    }
+    (dealt > 0).then(|| (won / dealt as f64) as f32)
+}
+
+// a combo of the range by weight that shares no card with `dead`.
+fn pick(combos: &[Combo], total: f32, dead: u64, rng: &mut StdRng) -> Option<u64> {
+    for _ in 0..DEAL_ATTEMPTS {
+        let mut at: f32 = rng.gen::<f32>() * total;
+        let combo: &Combo = combos
+            .iter()
+            .find(|c| {
+                at -= c.weight;
+                at < 0.
+            })
+            .unwrap_or(&combos[combos.len() - 1]);
+        if combo.cards() & dead == 0 {
+            return Some(combo.cards());
+        }
+    }
+    None
+}
+
+// every starting hand's heads up equity against every other, e.g. "AKs"
+// against "QQ", averaged over the ways the two can be dealt together.
+// worked out once, it answers class against class preflop questions and
+// seeds push/fold solves and charts without dealing anything. it keeps to
+// a compact file, 28k for the 14196 different pairs.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreflopTable {
+    // the row hand's equity against the column hand, by cell.
+    pub(crate) equity: Vec<f32>,
+    // how many ways the two hands can be dealt together.
+    pub(crate) combos: Vec<f32>,
+    // runouts dealt per pair of hands.
+    pub samples: usize,
+}
+
+// the start of a table file, and its version.
+const TABLE_MAGIC: &[u8; 5] = b"PFTB1";
+
+impl PreflopTable {
+    // deals `samples` runouts per pair of hands, spread over their combos.
+    // twenty thousand keep every entry within a few tenths of a percent
+    // and take about half a minute on one core.
+    pub fn new(samples: usize, seed: u64) -> Self {
+        let nthreads: usize = default_threads();
+        let workers: Vec<_> = (0..nthreads)
+            .map(|t| {
+                move |report: &mut dyn FnMut((usize, Row))| {
+                    for i in (t..CLASSES).step_by(nthreads) {
+                        report((i, equity_row(i, samples, seed)));
+                    }
+                }
+            })
+            .collect();
+
+        let mut equity: Vec<f32> = vec![0.5; CLASSES * CLASSES];
+        run_workers(workers, |(i, row): (usize, Row)| {
+            for (k, e) in row.into_iter().enumerate() {
+                let j: usize = i + k;
+                equity[i * CLASSES + j] = e;
+                equity[j * CLASSES + i] = 1. - e;
+            }
+        });
+        PreflopTable {
+            equity,
+            combos: pair_counts(),
+            samples,
+        }
+    }
+
+    // the table in `path` if there is one, else worked out and saved there
+    // for next time. failing to save only costs the next run the wait.
+    pub fn load_or_new(path: impl AsRef<Path>, samples: usize, seed: u64) -> io::Result<Self> {
+        let path: &Path = path.as_ref();
+        match Self::load(path) {
+            Ok(table) => return Ok(table),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            Err(_) => {}
+        }
+        let table: PreflopTable = Self::new(samples, seed);
+        if let Err(e) = table.save(path) {
+            log::warn!(
+                "Couldn't save the preflop table to {}: {}.",
+                path.display(),
+                e
+            );
+        }
+        Ok(table)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a preflop table"))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    // the header, the samples and then each pair of different hands once,
+    // the lower cell's equity in 16 bit fixed point, row by row.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = TABLE_MAGIC.to_vec();
+        bytes.extend((self.samples as u64).to_le_bytes());
+        for i in 0..CLASSES {
+            for j in i + 1..CLASSES {
+                let e: f32 = self.equity[i * CLASSES + j].clamp(0., 1.);
+                bytes.extend(((e * u16::MAX as f32).round() as u16).to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let rest: &[u8] = bytes.strip_prefix(TABLE_MAGIC)?;
+        let (samples, rest) = rest.split_first_chunk::<8>()?;
+        if rest.len() != CLASSES * (CLASSES - 1) {
+            return None;
+        }
+        let mut values = rest
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as f32 / u16::MAX as f32);
+        let mut equity: Vec<f32> = vec![0.5; CLASSES * CLASSES];
+        for i in 0..CLASSES {
+            for j in i + 1..CLASSES {
+                let e: f32 = values.next()?;
+                equity[i * CLASSES + j] = e;
+                equity[j * CLASSES + i] = 1. - e;
+            }
+        }
+        Some(PreflopTable {
+            equity,
+            combos: pair_counts(),
+            samples: u64::from_le_bytes(*samples) as usize,
+        })
+    }
+
+    // `hero`'s equity against `villain`, e.g. "AKs" against "QQ". None
+    // when either isn't a starting hand.
+    pub fn equity(&self, hero: &str, villain: &str) -> Option<f32> {
+        Some(self.equity[cell_of(hero)? * CLASSES + cell_of(villain)?])
+    }
+
+    // `hero`'s equity against the hands of `villain`, each starting hand
+    // weighed by how many of its combos the range plays alongside the
+    // hero's. None when the range has none of those.
+    pub fn equity_vs_range(&self, hero: &str, villain: &Range) -> Option<f32> {
+        let i: usize = cell_of(hero)?;
+        let hero: Vec<u64> = cell_combos(i);
+        let (mut total, mut weight): (f64, f64) = (0., 0.);
+        for combo in villain.combos() {
+            // the hero combos the villain combo leaves.
+            let free: usize = hero.iter().filter(|&&h| h & combo.cards() == 0).count();
+            let w: f64 = combo.weight as f64 * free as f64;
+            let j: usize = cell_of_combo(combo.cards());
+            total += self.equity[i * CLASSES + j] as f64 * w;
+            weight += w;
+        }
+        (weight > 0.).then(|| (total / weight) as f32)
+    }
+}
+
+// the equity of a hand against every hand from it on.
+type Row = Vec<f32>;
+
+// the row of hand `i`. pairs of the same hand are even.
+fn equity_row(i: usize, samples: usize, seed: u64) -> Row {
+    let mut rng: StdRng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+    let hero: Vec<u64> = cell_combos(i);
+    (i..CLASSES)
+        .map(|j| {
+            let pairs: Vec<(u64, u64)> = cell_combos(j)
+                .into_iter()
+                .flat_map(|v| hero.iter().map(move |&h| (h, v)))
+                .filter(|(h, v)| h & v == 0)
+                .collect();
+            if i == j || pairs.is_empty() {
+                return 0.5;
+            }
+            let per_pair: usize = samples.div_ceil(pairs.len());
+            let mut won: f64 = 0.;
+            for &(h, v) in pairs.iter() {
+                let mut deck: Vec<usize> = (0..52).filter(|c| (h | v) & 1 << c == 0).collect();
+                for _ in 0..per_pair {
+                    let board: u64 = deal(0, &mut deck, 5, &mut rng);
+                    let (a, b) = (ladder_score(h | board), ladder_score(v | board));
+                    won += if a > b {
+                        1.
+                    } else if a == b {
+                        0.5
+                    } else {
+                        0.
+                    };
+                }
+            }
+            (won / (per_pair * pairs.len()) as f64) as f32
+        })
+        .collect()
+}
+
+// by pair of cells, how many ways the two hands can be dealt together.
+fn pair_counts() -> Vec<f32> {
+    let combos: Vec<Vec<u64>> = (0..CLASSES).map(cell_combos).collect();
+    let mut counts: Vec<f32> = vec![0.; CLASSES * CLASSES];
+    for i in 0..CLASSES {
+        for j in 0..CLASSES {
+            let n = combos[i]
+                .iter()
+                .flat_map(|&h| combos[j].iter().map(move |&v| h & v))
+                .filter(|&shared| shared == 0)
+                .count();
+            counts[i * CLASSES + j] = n as f32;
+        }
+    }
+    counts
+}
@@ -0,0 +1,89 @@
+use crate::error::Error;
+use crate::range::Range;
+use std::fmt;
+
+// ranges saved under a name, e.g. "BTN open", written one a line as
+// "BTN open = 22+,A2s+,K9s+". lines starting with '#' are comments.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangePresets {
+    // in the order they were saved.
+    presets: Vec<(String, Range)>,
+}
+
+impl RangePresets {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut presets: RangePresets = RangePresets::default();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, range) = line
+                .split_once('=')
+                .ok_or_else(|| Error::InvalidRange(line.to_string()))?;
+            presets.insert(name.trim(), Range::parse(range)?);
+        }
+        Ok(presets)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Range> {
+        self.presets
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, range)| range)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.iter().map(|(name, _)| name.as_str())
+    }
+
+    // saves `range` as `name`, replacing a preset of the same name.
+    pub fn insert(&mut self, name: &str, range: Range) {
+        match self.presets.iter_mut().find(|(n, _)| n == name) {
+            Some((_, r)) => *r = range,
+            None => self.presets.push((name.to_string(), range)),
+        }
+    }
+
+    // the preset called `s`, or else `s` read as a range.
+    pub fn resolve(&self, s: &str) -> Result<Range, Error> {
+        match self.get(s.trim()) {
+            Some(range) => Ok(range.clone()),
+            None => Range::parse(s),
+        }
+    }
+}
+
+impl fmt::Display for RangePresets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, range) in self.presets.iter() {
+            writeln!(f, "{} = {}", name, range)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_imported_range_saves_and_reads_back() {
+        let mut presets: RangePresets =
+            RangePresets::parse("# opens\nBTN open = 22+,A2s+\n").unwrap();
+        let range: Range = Range::import("[50]AKs,AQs[/50]\nJJ+").unwrap();
+        presets.insert("3bet", range.clone());
+        let read: RangePresets = RangePresets::parse(&presets.to_string()).unwrap();
+        assert_eq!(read.names().collect::<Vec<&str>>(), ["BTN open", "3bet"]);
+        assert_eq!(read.get("3bet"), Some(&range));
+        assert_eq!(read.resolve(" 3bet ").unwrap(), range);
+    }
+
+    #[test]
+    fn a_name_is_replaced_in_place() {
+        let mut presets: RangePresets = RangePresets::parse("a = AA\nb = KK").unwrap();
+        presets.insert("a", Range::parse("QQ").unwrap());
+        assert_eq!(presets.to_string(), "a = QQ\nb = KK\n");
+        assert!(RangePresets::parse("no equals sign").is_err());
+    }
+}
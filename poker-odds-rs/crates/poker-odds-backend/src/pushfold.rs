@@ -0,0 +1,319 @@
+use crate::canonical::{cell_name, cell_of, CLASSES};
+use crate::preflop::PreflopTable;
+use crate::range::Range;
+use poker_odds_core::VALUES;
+use std::fmt;
+
+// rounds of fictitious play per spot.
+const ITERATIONS: usize = 2000;
+
+// each player's share of the prize pool under the Malmuth-Harville model:
+// a player finishes first with their share of the chips, and the others
+// then finish as if that player had never been there. `payouts` are the
+// shares for first place, second place and so on.
+pub fn icm(stacks: &[f32], payouts: &[f32]) -> Vec<f32> {
+    let mut equity: Vec<f32> = vec![0.; stacks.len()];
+    let left: Vec<usize> = (0..stacks.len()).collect();
+    finish(stacks, payouts, &left, 1., &mut equity);
+    equity
+}
+
+// hands out the next place among `left`, reached with probability `p`.
+fn finish(stacks: &[f32], payouts: &[f32], left: &[usize], p: f32, equity: &mut [f32]) {
+    let Some((&payout, payouts)) = payouts.split_first() else {
+        return;
+    };
+    if left.is_empty() {
+        return;
+    }
+    let chips: f32 = left.iter().map(|&i| stacks[i]).sum();
+    for (k, &i) in left.iter().enumerate() {
+        // players without chips only finish ahead of each other.
+        let share: f32 = if chips > 0. {
+            stacks[i] / chips
+        } else {
+            1. / left.len() as f32
+        };
+        if share == 0. {
+            continue;
+        }
+        equity[i] += p * share * payout;
+        let rest: Vec<usize> = [&left[..k], &left[k + 1..]].concat();
+        finish(stacks, payouts, &rest, p * share, equity);
+    }
+}
+
+// the small blind shoves or folds and the big blind calls or folds. stacks
+// are in big blinds, before the blinds go in.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PushFoldSpot {
+    // the small blind's, then the big blind's.
+    pub stacks: [f32; 2],
+    pub small_blind: f32,
+    // the stacks of the players who folded before the blinds, e.g. in a
+    // blind vs blind spot. they only matter under ICM.
+    pub others: Vec<f32>,
+    // the prize pool's shares by finishing place, best first. with none the
+    // players play for chips.
+    pub payouts: Vec<f32>,
+}
+
+impl PushFoldSpot {
+    // heads up for chips with `stack` big blinds each.
+    pub fn heads_up(stack: f32) -> Self {
+        PushFoldSpot {
+            stacks: [stack, stack],
+            small_blind: 0.5,
+            others: Vec::new(),
+            payouts: Vec::new(),
+        }
+    }
+
+    // what `player` (0 the small blind, 1 the big blind) has once the small
+    // blind has won `won` big blinds from the big blind.
+    fn value(&self, player: usize, won: f32) -> f32 {
+        let stacks: Vec<f32> = [self.stacks[0] + won, self.stacks[1] - won]
+            .into_iter()
+            .chain(self.others.iter().copied())
+            .collect();
+        if self.payouts.is_empty() {
+            stacks[player]
+        } else {
+            icm(&stacks, &self.payouts)[player]
+        }
+    }
+}
+
+// how often each hand is shoved and called, the two strategies being a
+// best response to each other.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PushFoldStrategy {
+    // by chart cell, see `PushFold::hands`.
+    pub shove: Vec<f32>,
+    pub call: Vec<f32>,
+}
+
+impl PushFoldStrategy {
+    // how often `hand`, e.g. "AKs" or "77", is shoved.
+    pub fn shoves(&self, hand: &str) -> Option<f32> {
+        cell_of(hand).map(|i| self.shove[i])
+    }
+
+    pub fn calls(&self, hand: &str) -> Option<f32> {
+        cell_of(hand).map(|i| self.call[i])
+    }
+
+    // None when nothing is shoved.
+    pub fn shove_range(&self) -> Option<Range> {
+        to_range(&self.shove)
+    }
+
+    pub fn call_range(&self) -> Option<Range> {
+        to_range(&self.call)
+    }
+}
+
+// hands played at least 1% of the time, weighted when they're mixed.
+fn to_range(freqs: &[f32]) -> Option<Range> {
+    let tokens: Vec<String> = freqs
+        .iter()
+        .enumerate()
+        .filter(|(_, &f)| f >= 0.01)
+        .map(|(i, &f)| match f {
+            f if f > 0.99 => cell_name(i),
+            f => format!("{}:{:.2}", cell_name(i), f),
+        })
+        .collect();
+    Range::parse(&tokens.join(",")).ok()
+}
+
+// the deepest stack each hand is shoved and called at, the way push/fold
+// charts are printed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PushFoldChart {
+    // the stacks solved, in big blinds.
+    pub depths: Vec<f32>,
+    // by chart cell, None when the hand is never played.
+    pub shove: Vec<Option<f32>>,
+    pub call: Vec<Option<f32>>,
+}
+
+impl fmt::Display for PushFoldChart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let top: f32 = self.depths.iter().copied().fold(0., f32::max);
+        for (title, depths) in [("Shove", &self.shove), ("Call", &self.call)] {
+            writeln!(f, "{} (big blinds)", title)?;
+            write!(f, "   ")?;
+            for v in VALUES.iter().rev() {
+                write!(f, "{:>5}", *v as char)?;
+            }
+            writeln!(f)?;
+            for (row, v) in VALUES.iter().rev().enumerate() {
+                write!(f, "{:>3}", *v as char)?;
+                for depth in &depths[row * 13..row * 13 + 13] {
+                    let cell: String = match depth {
+                        Some(d) if *d >= top => format!("{}+", d),
+                        Some(d) => d.to_string(),
+                        None => "-".to_string(),
+                    };
+                    write!(f, "{:>5}", cell)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// solves push/fold spots from a table of every starting hand's equity
+// against every other.
+#[derive(Debug, Clone)]
+pub struct PushFold {
+    table: PreflopTable,
+}
+
+impl PushFold {
+    // estimates the table by monte carlo first, see PreflopTable::new. a
+    // few thousand samples keep every entry within a percent or so.
+    pub fn new(samples: usize, seed: u64) -> Self {
+        Self::from_table(PreflopTable::new(samples, seed))
+    }
+
+    // with a table worked out before, e.g. loaded from a file.
+    pub fn from_table(table: PreflopTable) -> Self {
+        PushFold { table }
+    }
+
+    // the hands in the order of the strategies' and charts' cells.
+    pub fn hands() -> Vec<String> {
+        (0..CLASSES).map(cell_name).collect()
+    }
+
+    // the estimated equity of `hero` against `villain`, e.g. "AKs" vs "QQ".
+    pub fn equity(&self, hero: &str, villain: &str) -> Option<f32> {
+        self.table.equity(hero, villain)
+    }
+
+    // the equilibrium of `spot` by fictitious play: each round both players
+    // best respond to the other's average strategy so far.
+    pub fn solve(&self, spot: &PushFoldSpot) -> PushFoldStrategy {
+        let effective: f32 = spot.stacks[0].min(spot.stacks[1]);
+        // the small blind's outcomes: folded, stole the blinds, won and
+        // lost the all in, and the big blind's in the same order.
+        let won: [f32; 4] = [-spot.small_blind, 1., effective, -effective];
+        let sb: [f32; 4] = won.map(|w| spot.value(0, w));
+        let bb: [f32; 4] = won.map(|w| spot.value(1, w));
+
+        let mut shove: Vec<f32> = vec![1.; CLASSES];
+        let mut call: Vec<f32> = vec![0.; CLASSES];
+        for round in 1..=ITERATIONS {
+            let step: f32 = 1. / (round + 1) as f32;
+            for (j, c) in call.iter_mut().enumerate() {
+                let (mut ev, mut weight) = (0., 0.);
+                for (i, &s) in shove.iter().enumerate() {
+                    let w: f32 = self.table.combos[i * CLASSES + j] * s;
+                    let e: f32 = self.table.equity[j * CLASSES + i];
+                    ev += w * (e * bb[3] + (1. - e) * bb[2]);
+                    weight += w;
+                }
+                let best: f32 = (weight > 0. && ev / weight > bb[1]) as u8 as f32;
+                *c += (best - *c) * step;
+            }
+            for (i, s) in shove.iter_mut().enumerate() {
+                let (mut ev, mut weight) = (0., 0.);
+                for (j, &c) in call.iter().enumerate() {
+                    let w: f32 = self.table.combos[i * CLASSES + j];
+                    let e: f32 = self.table.equity[i * CLASSES + j];
+                    let called: f32 = e * sb[2] + (1. - e) * sb[3];
+                    ev += w * ((1. - c) * sb[1] + c * called);
+                    weight += w;
+                }
+                let best: f32 = (ev / weight > sb[0]) as u8 as f32;
+                *s += (best - *s) * step;
+            }
+        }
+        PushFoldStrategy { shove, call }
+    }
+
+    // solves `spot` at each of `depths` big blinds for both players and
+    // notes the deepest one each hand is played at more often than not.
+    pub fn chart(&self, spot: &PushFoldSpot, depths: &[f32]) -> PushFoldChart {
+        let mut chart: PushFoldChart = PushFoldChart {
+            depths: depths.to_vec(),
+            shove: vec![None; CLASSES],
+            call: vec![None; CLASSES],
+        };
+        for &depth in depths {
+            let spot: PushFoldSpot = PushFoldSpot {
+                stacks: [depth, depth],
+                ..spot.clone()
+            };
+            let strategy: PushFoldStrategy = self.solve(&spot);
+            for (deepest, freqs) in [
+                (&mut chart.shove, &strategy.shove),
+                (&mut chart.call, &strategy.call),
+            ] {
+                for (d, &f) in deepest.iter_mut().zip(freqs.iter()) {
+                    if f >= 0.5 && d.is_none_or(|d| d < depth) {
+                        *d = Some(depth);
+                    }
+                }
+            }
+        }
+        chart
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_prize_is_played_for_chips() {
+        assert_eq!(icm(&[30., 10.], &[1.]), [0.75, 0.25]);
+    }
+
+    #[test]
+    fn icm_takes_from_the_chip_leader() {
+        // the leader finishes first half the time, second 0.3 * 50/70 +
+        // 0.2 * 50/80 of it and third the rest.
+        let equity: Vec<f32> = icm(&[50., 30., 20.], &[0.5, 0.3, 0.2]);
+        assert!((equity[0] - 0.3839).abs() < 1e-3);
+        assert!(equity[2] > 0.2);
+        assert!((equity.iter().sum::<f32>() - 1.).abs() < 1e-5);
+        let even: Vec<f32> = icm(&[10., 10., 10.], &[0.5, 0.3, 0.2]);
+        assert!(even.iter().all(|&e| (e - 1. / 3.).abs() < 1e-5));
+        assert_eq!(icm(&[10., 0.], &[0.7, 0.3]), [0.7, 0.3]);
+    }
+
+    #[test]
+    fn heads_up_at_ten_big_blinds() {
+        let pf: PushFold = PushFold::new(400, 1);
+        let s: PushFoldStrategy = pf.solve(&PushFoldSpot::heads_up(10.));
+        for hand in ["AA", "KK", "AKs", "A2s"] {
+            assert!(s.shoves(hand).unwrap() > 0.9, "{}", hand);
+        }
+        assert!(s.calls("AA").unwrap() > 0.9);
+        assert!(s.shoves("32o").unwrap() < 0.1);
+        assert!(s.calls("72o").unwrap() < 0.1);
+        assert_eq!(s.shoves("AKx"), None);
+        // the big blind calls tighter than the small blind shoves.
+        let (shoved, called) = (s.shove_range().unwrap(), s.call_range().unwrap());
+        assert!(called.combos().len() < shoved.combos().len());
+    }
+
+    #[test]
+    fn a_chart_keeps_the_deepest_stack_a_hand_is_played_at() {
+        let pf: PushFold = PushFold::new(400, 1);
+        let chart: PushFoldChart = pf.chart(&PushFoldSpot::heads_up(0.), &[1., 10., 20.]);
+        let cell = |hand: &str| cell_of(hand).unwrap();
+        assert_eq!(chart.shove[cell("AA")], Some(20.));
+        assert_eq!(chart.call[cell("AA")], Some(20.));
+        // with one big blind behind a shove only needs 25% to beat folding.
+        assert_eq!(chart.shove[cell("32o")], Some(1.));
+        assert!(chart.to_string().starts_with("Shove (big blinds)\n"));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::error::Error;
+use crate::history::{parse_hand_histories, HandHistory};
+use crate::range::Range;
+use crate::solver::{hand_mask, parse_board, RangeEquity, Solution, SolveMode, Solver};
+
+// monte carlo samples per combo when a range is solved preflop, where
+// exact solves of every combo would take minutes.
+const RANGE_SAMPLES: usize = 10_000;
+
+// answers a one line question like "AhKh vs QsQd on 7c8c9c", for chat bots
+// and the like. the hero comes first; the opponents are hands, or a single
+// range like "AhKh vs QQ+,AKs". the board is optional.
+pub fn answer_query(solver: &Solver, query: &str) -> Result<String, Error> {
+    let (hero, opponents, board) =
+        split_query(query).ok_or_else(|| Error::InvalidQuery(query.to_string()))?;
+    let on: String = match board.is_empty() {
+        true => String::new(),
+        false => format!(" on {}", board),
+    };
+
+    let hands: Vec<String> = opponents
+        .iter()
+        .map(|o| o.split_whitespace().collect::<String>())
+        .filter(|o| hand_mask(o).is_ok())
+        .collect();
+    if hands.len() == opponents.len() {
+        let mut all: Vec<String> = vec![hero.clone()];
+        all.extend(hands);
+        let solution: Solution = solver.try_solve(&all, &board)?;
+        return Ok(format!(
+            "{}{}: {} has {:.1}% equity.",
+            all.join(" vs "),
+            on,
+            hero,
+            solution.equity * 100.
+        ));
+    }
+
+    // anything that isn't a hand is read as a range, and only one of those.
+    let [range] = opponents.as_slice() else {
+        return Err(Error::InvalidQuery(query.to_string()));
+    };
+    let villain: Range = Range::parse(range)?;
+    let mode: SolveMode = match board.is_empty() {
+        true => SolveMode::MonteCarlo {
+            samples: RANGE_SAMPLES,
+        },
+        false => SolveMode::Exact,
+    };
+    let result: RangeEquity = solver.equity_vs_range_detailed(&hero, &villain, &board, mode)?;
+    // a weighted range counts its combos by weight too.
+    let weighted: String = match result.weight == result.combos as f32 {
+        true => String::new(),
+        false => format!(", {:.1} by weight", result.weight),
+    };
+    Ok(format!(
+        "{} vs {} ({} combos{}){}: {} has {:.1}% equity.",
+        hero,
+        range,
+        result.combos,
+        weighted,
+        on,
+        hero,
+        result.equity * 100.
+    ))
+}
+
+// one spot to solve, e.g. a line of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scenario {
+    // the hero's first.
+    pub hands: Vec<String>,
+    // empty preflop.
+    pub board: String,
+}
+
+impl Scenario {
+    pub fn new(hands: &[impl AsRef<str>], board: &str) -> Self {
+        Scenario {
+            hands: hands.iter().map(|h| h.as_ref().to_string()).collect(),
+            board: board.to_string(),
+        }
+    }
+
+    // a scenario written the way parse_scenario reads them.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let (hands, board) = parse_scenario(text)?;
+        Ok(Scenario { hands, board })
+    }
+}
+
+// the hands, the hero's first, and the board of a pasted hand: either a
+// PokerStars hand history, whose last hand is taken, or "AhKh vs QsQd on
+// 7c8c9c". a history snippet doesn't need the hand's header, and may show
+// only the hero's hand.
+pub fn parse_scenario(text: &str) -> Result<(Vec<String>, String), Error> {
+    // hand histories write their cards in brackets.
+    if !text.contains('[') {
+        let (hero, opponents, board) =
+            split_query(text.trim()).ok_or_else(|| Error::InvalidQuery(text.trim().to_string()))?;
+        let hands: Vec<String> = std::iter::once(hero)
+            .chain(opponents.iter().map(|o| o.split_whitespace().collect()))
+            .collect();
+        for hand in hands.iter() {
+            hand_mask(hand)?;
+        }
+        parse_board(&board)?;
+        return Ok((hands, board));
+    }
+
+    let text: String = match text.contains("PokerStars Hand #") {
+        true => text.to_string(),
+        false => format!("PokerStars Hand #0:\n{}", text),
+    };
+    let hand: HandHistory = parse_hand_histories(&text)?.pop().unwrap_or_default();
+    let mut shown: Vec<(&String, &String)> = hand
+        .hole_cards
+        .iter()
+        .filter(|(player, _)| Some(*player) != hand.hero.as_ref())
+        .collect();
+    shown.sort();
+    let hands: Vec<String> = hand
+        .hero
+        .as_ref()
+        .and_then(|hero| hand.hole_cards.get(hero))
+        .into_iter()
+        .chain(shown.into_iter().map(|(_, cards)| cards))
+        .cloned()
+        .collect();
+    if hands.is_empty() {
+        return Err(Error::InvalidHistory("no hole cards".to_string()));
+    }
+    Ok((hands, hand.board))
+}
+
+// the hero, the opponents and the board of "AhKh vs QsQd on 7c8c9c", with
+// the spaces inside hands and the board taken out. None without a " vs ".
+fn split_query(query: &str) -> Option<(String, Vec<&str>, String)> {
+    let (players, board) = match query.split_once(" on ") {
+        Some((players, board)) => (players, board.split_whitespace().collect::<String>()),
+        None => (query, String::new()),
+    };
+    let players: Vec<&str> = players.split(" vs ").map(str::trim).collect();
+    let [hero, opponents @ ..] = players.as_slice() else {
+        return None;
+    };
+    if opponents.is_empty() {
+        return None;
+    }
+    Some((hero.split_whitespace().collect(), opponents.to_vec(), board))
+}
@@ -0,0 +1,333 @@
+use crate::canonical::{cell_at, cell_combos, combo_name};
+use crate::error::Error;
+use crate::solver::hand_mask;
+use poker_odds_core::{SUITS, VALUES};
+use std::fmt;
+use std::str::FromStr;
+
+// one way to be dealt a hand of a range, e.g. "AhKd", and how often it's
+// played relative to the range's other combos.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Combo {
+    pub hand: String,
+    pub weight: f32,
+    cards: u64,
+}
+
+impl Combo {
+    // the combo's two cards as a card mask.
+    pub fn cards(&self) -> u64 {
+        self.cards
+    }
+}
+
+// the hands a player can have, written in the usual shorthand and
+// separated by commas: pairs ("QQ", "QQ+", "QQ-88"), suited and offsuit
+// hands ("AKs", "AJo+", "KTs-K8s", "AK" for both), single combos
+// ("AhKd") and the strongest share of all hands ("20%"). a ":weight"
+// suffix plays the hands only that often, e.g. "22-55:0.5".
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Range {
+    text: String,
+    combos: Vec<Combo>,
+}
+
+impl Range {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let mut range: Range = Range {
+            text: s.trim().to_string(),
+            combos: Vec::new(),
+        };
+        for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let invalid = || Error::InvalidRange(token.to_string());
+            let (hands, weight) = match token.split_once(':') {
+                Some((hands, w)) => (hands, w.trim().parse::<f32>().map_err(|_| invalid())?),
+                None => (token, 1.),
+            };
+            if !(0.0..=1.).contains(&weight) {
+                return Err(invalid());
+            }
+            for hand in expand(hands.trim()).ok_or_else(invalid)? {
+                range.add(hand, weight)?;
+            }
+        }
+        if range.combos.is_empty() {
+            return Err(Error::InvalidRange(s.to_string()));
+        }
+        Ok(range)
+    }
+
+    // a range exported from a tracker or a solver: the usual shorthand, one
+    // hand a line or comma separated, weighted either "AKs:0.5" or with
+    // percentages in brackets like Equilab and Flopzilla write them,
+    // "[50]AKs,AQs[/50]". the range reads back as plain shorthand.
+    pub fn import(text: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidRange(text.trim().to_string());
+        let mut hands: Vec<String> = Vec::new();
+        let mut rest: &str = text;
+        while let Some((outside, bracketed)) = rest.split_once('[') {
+            hands.extend(tokens(outside));
+            let (percent, bracketed) = bracketed.split_once(']').ok_or_else(invalid)?;
+            let weight: f32 = percent.trim().parse::<f32>().map_err(|_| invalid())? / 100.;
+            let (inside, after) = bracketed.split_once("[/").ok_or_else(invalid)?;
+            hands.extend(tokens(inside).map(|h| format!("{}:{}", h, weight)));
+            rest = after.split_once(']').ok_or_else(invalid)?.1;
+        }
+        hands.extend(tokens(rest));
+        Range::parse(&hands.join(","))
+    }
+
+    pub fn combos(&self) -> &[Combo] {
+        &self.combos
+    }
+
+    // the combos that share no card with `dead`, e.g. the board and the
+    // hero's hand.
+    pub fn live(&self, dead: u64) -> impl Iterator<Item = &Combo> {
+        self.combos.iter().filter(move |c| c.cards & dead == 0)
+    }
+
+    // a combo listed twice keeps its last weight.
+    fn add(&mut self, hand: String, weight: f32) -> Result<(), Error> {
+        let cards: u64 = hand_mask(&hand)?;
+        match self.combos.iter_mut().find(|c| c.cards == cards) {
+            Some(c) => c.weight = weight,
+            None => self.combos.push(Combo {
+                hand,
+                weight,
+                cards,
+            }),
+        }
+        Ok(())
+    }
+}
+
+// the 169 starting hands from strongest to weakest by equity against a
+// single random hand, the order "top 20%" ranges are taken in.
+const BY_STRENGTH: [&str; 169] = [
+    "AA", "KK", "QQ", "JJ", "TT", "99", "88", "AKs", "77", "AQs", "AKo", "AJs", "ATs", "AQo",
+    "KQs", "AJo", "66", "ATo", "A9s", "KJs", "A8s", "KTs", "KQo", "A7s", "A9o", "KJo", "55", "QJs",
+    "K9s", "A5s", "A8o", "A6s", "KTo", "QTs", "A4s", "A7o", "QJo", "K8s", "A3s", "K9o", "Q9s",
+    "A5o", "JTs", "K7s", "A6o", "A2s", "44", "QTo", "A4o", "K6s", "K8o", "Q8s", "A3o", "K5s",
+    "J9s", "Q9o", "K7o", "JTo", "A2o", "K4s", "Q7s", "K6o", "K3s", "J8s", "T9s", "33", "Q6s",
+    "Q8o", "J9o", "K5o", "K2s", "Q5s", "J7s", "K4o", "T8s", "Q7o", "Q4s", "T9o", "J8o", "K3o",
+    "Q3s", "Q6o", "98s", "J6s", "T7s", "K2o", "22", "Q5o", "Q2s", "J5s", "J7o", "T8o", "J4s",
+    "Q4o", "97s", "T6s", "J3s", "Q3o", "98o", "T7o", "87s", "J6o", "T5s", "J2s", "96s", "Q2o",
+    "J5o", "T4s", "97o", "86s", "J4o", "T6o", "95s", "T3s", "76s", "J3o", "87o", "T2s", "96o",
+    "85s", "T5o", "J2o", "94s", "75s", "T4o", "86o", "93s", "65s", "95o", "84s", "T3o", "92s",
+    "76o", "74s", "T2o", "54s", "85o", "64s", "83s", "94o", "75o", "82s", "93o", "73s", "65o",
+    "63s", "53s", "84o", "92o", "43s", "74o", "54o", "72s", "64o", "52s", "62s", "83o", "82o",
+    "42s", "73o", "53o", "63o", "32s", "43o", "72o", "52o", "62o", "42o", "32o",
+];
+
+impl FromStr for Range {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Range::parse(s)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// the hands of an export, which puts them on lines of their own or
+// separates them with commas, without any spaces, e.g. "AKo: 0.25".
+fn tokens(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split([',', '\n', '\r'])
+        .map(|t| t.split_whitespace().collect::<String>())
+        .filter(|t| !t.is_empty())
+}
+
+// the combos of one token without its weight, None if it's not valid.
+fn expand(token: &str) -> Option<Vec<String>> {
+    if let Some(percent) = token.strip_suffix('%') {
+        return top(percent.trim().parse::<f32>().ok()?);
+    }
+    let b: &[u8] = token.as_bytes();
+    // a single combo.
+    if b.len() == 4 && SUITS.contains(&b[1]) && SUITS.contains(&b[3]) {
+        return Some(vec![token.to_string()]);
+    }
+
+    let (first, last) = match token.split_once('-') {
+        Some((first, last)) => (first, Some(last)),
+        None => (token, None),
+    };
+    let (plus, first) = match first.strip_suffix('+') {
+        Some(first) => (true, first),
+        None => (false, first),
+    };
+    let (hi, lo, kind) = shape(first)?;
+
+    // the lower card walks from `lo` up to `top`, or for pairs both do.
+    let (from, top): (usize, usize) = match (plus, last) {
+        (false, None) => (lo, lo),
+        (true, None) if hi == lo => (lo, 12),
+        (true, None) => (lo, hi - 1),
+        (false, Some(last)) => {
+            let (last_hi, last_lo, last_kind) = shape(last)?;
+            let same_shape: bool = last_kind == kind && (last_hi == last_lo) == (hi == lo);
+            if !same_shape || (hi != lo && last_hi != hi) {
+                return None;
+            }
+            (lo.min(last_lo), lo.max(last_lo))
+        }
+        (true, Some(_)) => return None,
+    };
+
+    let mut hands: Vec<String> = Vec::new();
+    for l in from..=top {
+        let h: usize = if hi == lo { l } else { hi };
+        let cells: Vec<usize> = match kind {
+            _ if h == l => vec![cell_at(h, l, false)],
+            Kind::Suited => vec![cell_at(h, l, true)],
+            Kind::Offsuit => vec![cell_at(h, l, false)],
+            Kind::Any => vec![cell_at(h, l, true), cell_at(h, l, false)],
+        };
+        for cell in cells {
+            hands.extend(cell_combos(cell).into_iter().map(combo_name));
+        }
+    }
+    Some(hands)
+}
+
+// the strongest starting hands, taken whole, until they hold at least
+// `percent` of the 1326 combos. None outside (0, 100].
+fn top(percent: f32) -> Option<Vec<String>> {
+    if !(percent > 0. && percent <= 100.) {
+        return None;
+    }
+    let target: f32 = percent / 100. * 1326.;
+    let mut hands: Vec<String> = Vec::new();
+    for class in BY_STRENGTH {
+        if hands.len() as f32 >= target {
+            break;
+        }
+        hands.extend(expand(class)?);
+    }
+    Some(hands)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Suited,
+    Offsuit,
+    Any,
+}
+
+// "AKs" as the values of its cards (deuce 0), higher first, and whether
+// it's suited. pairs are always Any.
+fn shape(s: &str) -> Option<(usize, usize, Kind)> {
+    let b: &[u8] = s.as_bytes();
+    let value = |c: u8| VALUES.iter().position(|&v| v == c.to_ascii_uppercase());
+    let (a, c) = (value(*b.first()?)?, value(*b.get(1)?)?);
+    let kind: Kind = match (b.get(2), b.len()) {
+        (None, 2) => Kind::Any,
+        (Some(b's'), 3) if a != c => Kind::Suited,
+        (Some(b'o'), 3) if a != c => Kind::Offsuit,
+        _ => return None,
+    };
+    Some((a.max(c), a.min(c), kind))
+}
+
+// one to four tokens of the shorthand, each a starting hand with or
+// without a "+", a run of pairs, a single combo or a share of all hands,
+// some of them weighted, e.g. "AJo+,77-44:0.5,KhQd".
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Range {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let hands: Vec<String> = crate::canonical::starting_hands();
+        let mut tokens: Vec<String> = Vec::new();
+        for _ in 0..u.int_in_range(1..=4)? {
+            let mut token: String = match u.int_in_range(0..=4)? {
+                0 => u.choose(&hands)?.clone(),
+                1 => format!("{}+", u.choose(&hands)?),
+                2 => {
+                    let (a, b) = (u.int_in_range(0..=12)?, u.int_in_range(0..=12)?);
+                    let pair = |v: usize| (VALUES[v] as char).to_string().repeat(2);
+                    format!("{}-{}", pair(a), pair(b))
+                }
+                3 => combo_name(crate::cards::arbitrary_cards(u, 2)?),
+                _ => format!("{}%", u.int_in_range(1..=100)?),
+            };
+            if u.ratio(1, 4)? {
+                token += u.choose(&[":0.25", ":0.5", ":0.75"])?;
+            }
+            tokens.push(token);
+        }
+        Range::parse(&tokens.join(",")).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weight(range: &Range, hand: &str) -> Option<f32> {
+        let cards: u64 = hand_mask(hand).unwrap();
+        range
+            .combos()
+            .iter()
+            .find(|c| c.cards == cards)
+            .map(|c| c.weight)
+    }
+
+    #[test]
+    fn a_share_takes_whole_hands_from_the_strongest() {
+        // 5% is 66.3 combos: the pairs down to 77, AKs and AQs make 56 and
+        // AKo goes over.
+        let range: Range = Range::parse("5%").unwrap();
+        assert_eq!(range.combos().len(), 68);
+        assert_eq!(weight(&range, "AhKd"), Some(1.));
+        assert_eq!(weight(&range, "AhJh"), None);
+        assert_eq!(weight(&range, "6h6d"), None);
+        assert_eq!(Range::parse("100%").unwrap().combos().len(), 1326);
+    }
+
+    #[test]
+    fn a_share_can_be_weighted_and_mixed() {
+        let range: Range = Range::parse("1%:0.5, 72o").unwrap();
+        // 13.3 combos: AA, KK and QQ.
+        assert_eq!(range.combos().len(), 18 + 12);
+        assert_eq!(weight(&range, "AsAc"), Some(0.5));
+        assert_eq!(weight(&range, "7h2d"), Some(1.));
+    }
+
+    #[test]
+    fn a_share_outside_zero_to_a_hundred_is_rejected() {
+        for s in ["0%", "101%", "-5%", "x%"] {
+            assert!(Range::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn imports_bracketed_weights() {
+        let range: Range = Range::import("QQ+,[50]AKs,AQs[/50],[25]JJ[/25]").unwrap();
+        assert_eq!(range.combos().len(), 18 + 8 + 6);
+        assert_eq!(weight(&range, "KhKd"), Some(1.));
+        assert_eq!(weight(&range, "AhKh"), Some(0.5));
+        assert_eq!(weight(&range, "AdQd"), Some(0.5));
+        assert_eq!(weight(&range, "JhJd"), Some(0.25));
+    }
+
+    #[test]
+    fn imports_a_hand_a_line() {
+        let range: Range = Range::import("AA\r\nAKo: 0.25\n\n  KQs\n").unwrap();
+        assert_eq!(range.combos().len(), 6 + 12 + 4);
+        assert_eq!(weight(&range, "AhKd"), Some(0.25));
+        assert_eq!(range.to_string(), "AA,AKo:0.25,KQs");
+    }
+
+    #[test]
+    fn an_unclosed_bracket_is_rejected() {
+        for text in ["[50]AKs", "[50AKs[/50]", "[half]AKs[/50]", "AKs[/50]x[50]"] {
+            assert!(Range::import(text).is_err(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::deck::Deck;
+use crate::solver::{ladder_score, run_workers};
+
+// the nine kinds of five card hands, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    Trips,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
+// how likely the hero is to end up with a category, and how sure that is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CategoryEstimate {
+    pub probability: f32,
+    // the standard error of `probability`.
+    pub std_err: f32,
+    // runouts dealt over all the strata.
+    pub samples: usize,
+}
+
+// C(n, k) as a float, the counts get too big for integers past the flop
+// with a full deck.
+fn choose(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.;
+    }
+    (0..k).fold(1., |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+// the cards that help the hero towards `category`: more of their values
+// for paired hands, their suits for flushes and values close to theirs
+// for straights.
+fn useful(hero: u64, category: Category) -> u64 {
+    let cards = || (0..52).filter(move |i| hero >> i & 1 == 1);
+    match category {
+        Category::Flush | Category::StraightFlush => {
+            cards().fold(0, |acc, i| acc | 0x1_1111_1111_1111 << (i % 4))
+        }
+        Category::Straight => cards().fold(0, |acc, i| {
+            let value: usize = i / 4;
+            // the ace also plays low, in the wheel.
+            let wheel = |v: usize| (v == 12 && value <= 3) || (value == 12 && v <= 3);
+            (0..13usize)
+                .filter(|&v| v.abs_diff(value) <= 4 || wheel(v))
+                .fold(acc, |acc, v| acc | 0xf << (v * 4))
+        }),
+        _ => cards().fold(0, |acc, i| acc | 0xf << (i / 4 * 4)),
+    }
+}
+
+// the chance `hero` ends up with exactly `category` on a runout of
+// `board`. plain sampling barely ever sees rare hands like quads, so the
+// runouts are split by how many of their cards help towards the category.
+// each split gets the same number of samples, and is weighted by how
+// likely it is to be dealt, which is known exactly. the estimate stays
+// unbiased while the splits with the most useful cards, where the rare
+// hands are, get sampled far more often than they come up.
+pub(crate) fn category_odds(
+    hero: u64,
+    board: u64,
+    category: Category,
+    samples: usize,
+    seed: u64,
+) -> CategoryEstimate {
+    let need: usize = 5 - board.count_ones() as usize;
+    let dead: u64 = hero | board;
+    let helps: u64 = useful(hero, category);
+    let (useful, others): (Vec<usize>, Vec<usize>) = (0..52)
+        .filter(|i| dead >> i & 1 == 0)
+        .partition(|i| helps >> i & 1 == 1);
+
+    let all: f64 = choose(useful.len() + others.len(), need);
+    // the splits that can be dealt, by their number of useful cards.
+    let strata: Vec<(usize, f64)> = (0..=need)
+        .map(|k| {
+            let p: f64 = choose(useful.len(), k) * choose(others.len(), need - k) / all;
+            (k, p)
+        })
+        .filter(|&(_, p)| p > 0.)
+        .collect();
+    let per: usize = samples.div_ceil(strata.len()).max(1);
+
+    let workers: Vec<_> = strata
+        .iter()
+        .map(|&(k, p)| {
+            let (useful, others) = (useful.clone(), others.clone());
+            move |report: &mut dyn FnMut((f64, usize, usize))| {
+                // one deck per kind of card, each from its own seed.
+                let mut a: Deck = Deck::batch(&useful, seed, 2 * k);
+                let mut b: Deck = Deck::batch(&others, seed, 2 * k + 1);
+                let mut hits: usize = 0;
+                for _ in 0..per {
+                    let runout: u64 = a
+                        .draw(k)
+                        .iter()
+                        .chain(b.draw(need - k))
+                        .fold(board, |acc, c| acc | 1 << c);
+                    if ladder_score(hero | runout) >> 26 == category as u64 {
+                        hits += 1;
+                    }
+                }
+                report((p, hits, per));
+            }
+        })
+        .collect();
+
+    let (mut probability, mut variance): (f64, f64) = (0., 0.);
+    run_workers(workers, |(p, hits, n): (f64, usize, usize)| {
+        let q: f64 = hits as f64 / n as f64;
+        probability += p * q;
+        variance += p * p * q * (1. - q) / n as f64;
+    });
+    CategoryEstimate {
+        probability: probability as f32,
+        std_err: variance.sqrt() as f32,
+        samples: per * strata.len(),
+    }
+}
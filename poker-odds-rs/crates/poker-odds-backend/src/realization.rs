@@ -0,0 +1,101 @@
+use crate::error::Error;
+use crate::solver::hole_values;
+
+// whether the hero acts last after the flop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Position {
+    InPosition,
+    OutOfPosition,
+}
+
+// starting hands grouped by how easy they are to play after the flop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HandClass {
+    Pair,
+    // both cards ten or higher.
+    SuitedBroadway,
+    // adjacent values, e.g. 87s.
+    SuitedConnector,
+    Suited,
+    OffsuitBroadway,
+    Offsuit,
+}
+
+impl HandClass {
+    pub fn of(hand: &str) -> Result<Self, Error> {
+        let (hi, lo, suited) = hole_values(hand)?;
+        Ok(if hi == lo {
+            HandClass::Pair
+        } else if lo >= 10 && suited {
+            HandClass::SuitedBroadway
+        } else if lo >= 10 {
+            HandClass::OffsuitBroadway
+        } else if hi - lo == 1 && suited {
+            HandClass::SuitedConnector
+        } else if suited {
+            HandClass::Suited
+        } else {
+            HandClass::Offsuit
+        })
+    }
+}
+
+// turns raw equity into the share of the pot a hand can expect to win once
+// it has to be played out, with one multiplier for position and one for
+// the hand class. the defaults are rough rules of thumb: hands that make
+// strong draws and the nut hands realize more, offsuit trash and hands out
+// of position less. tune them to the game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Realization {
+    pub in_position: f32,
+    pub out_of_position: f32,
+    pub pair: f32,
+    pub suited_broadway: f32,
+    pub suited_connector: f32,
+    pub suited: f32,
+    pub offsuit_broadway: f32,
+    pub offsuit: f32,
+}
+
+impl Default for Realization {
+    fn default() -> Self {
+        Realization {
+            in_position: 1.1,
+            out_of_position: 0.85,
+            pair: 1.,
+            suited_broadway: 1.05,
+            suited_connector: 1.05,
+            suited: 0.95,
+            offsuit_broadway: 0.95,
+            offsuit: 0.8,
+        }
+    }
+}
+
+impl Realization {
+    pub fn multiplier(&self, class: HandClass, position: Position) -> f32 {
+        let class: f32 = match class {
+            HandClass::Pair => self.pair,
+            HandClass::SuitedBroadway => self.suited_broadway,
+            HandClass::SuitedConnector => self.suited_connector,
+            HandClass::Suited => self.suited,
+            HandClass::OffsuitBroadway => self.offsuit_broadway,
+            HandClass::Offsuit => self.offsuit,
+        };
+        let position: f32 = match position {
+            Position::InPosition => self.in_position,
+            Position::OutOfPosition => self.out_of_position,
+        };
+        class * position
+    }
+
+    // the realized equity of `hand`, e.g. "AhKh", given its raw equity.
+    // never more than the whole pot.
+    pub fn realize(&self, equity: f32, hand: &str, position: Position) -> Result<f32, Error> {
+        let class: HandClass = HandClass::of(hand)?;
+        Ok((equity * self.multiplier(class, position)).clamp(0., 1.))
+    }
+}
@@ -0,0 +1,281 @@
+use crate::canonical::{cell_combos, CLASSES};
+use crate::matrix::cell_of_combo;
+use crate::preflop::PreflopTable;
+use crate::solver::ladder_score;
+use rand::Rng;
+
+// how a monte carlo solve deals its runouts. every way gives an unbiased
+// estimate but the control variate's, which leans on the preflop table's;
+// they differ in how much the luck of the draw moves it, which the solve's
+// stats measure, see SolveStats::variance_reduction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sampling {
+    // every runout at random.
+    #[default]
+    Plain,
+    // every card that can come next dealt first in as many samples, the
+    // rest at random. takes the luck of the next card out.
+    Stratified,
+    // every random runout dealt again with its suits swapped round within
+    // each value, and the two averaged. the swap turns one suit's cards into
+    // another's, so it pays off where flushes decide the equity and costs
+    // where they don't.
+    Antithetic,
+    // heads up preflop, each runout is also dealt to a random other combo
+    // pair of the same two starting hands, whose average equity the
+    // preflop table knows, and the hero's equity is corrected by how far
+    // the pair's strays from it. the answer is only as good as the table's
+    // entry, whose error goes into the standard error, so it pays off for
+    // solves with fewer samples than the table was worked out with. needs
+    // Solver::with_preflop_table, and samples plainly without it or in
+    // other spots.
+    ControlVariate,
+}
+
+// a solve's sampling with what it needs worked out for the spot.
+#[derive(Debug, Clone)]
+pub(crate) enum Sampler {
+    Plain,
+    Stratified,
+    // the card each card is swapped for, by card.
+    Antithetic {
+        swap: Vec<usize>,
+    },
+    // every way to deal the hero's and the villain's starting hands
+    // together, the hero's equity over them by the table and that
+    // entry's variance.
+    ControlVariate {
+        pairs: Vec<(u64, u64)>,
+        mean: f64,
+        table_variance: f64,
+    },
+}
+
+impl Sampler {
+    // `deck` holds the cards left to deal onto `board`, the hero holds
+    // `hero` against `opponents`. with the board complete there's nothing
+    // to stratify by or swap, and the control variate only goes heads up
+    // preflop.
+    pub(crate) fn new(
+        sampling: Sampling,
+        table: Option<&PreflopTable>,
+        hero: u64,
+        opponents: &[u64],
+        board: u64,
+        deck: &[usize],
+    ) -> Self {
+        match sampling {
+            Sampling::Stratified if board.count_ones() < 5 => Sampler::Stratified,
+            Sampling::Antithetic if board.count_ones() < 5 => Sampler::Antithetic {
+                swap: suit_swap(deck),
+            },
+            Sampling::ControlVariate if board == 0 && opponents.len() == 1 => match table {
+                Some(table) => {
+                    let (i, j) = (cell_of_combo(hero), cell_of_combo(opponents[0]));
+                    let mut pairs: Vec<(u64, u64)> = Vec::new();
+                    for h in cell_combos(i) {
+                        for v in cell_combos(j) {
+                            if h & v == 0 {
+                                pairs.push((h, v));
+                            }
+                        }
+                    }
+                    let mean: f64 = table.equity[i * CLASSES + j] as f64;
+                    Sampler::ControlVariate {
+                        pairs,
+                        mean,
+                        table_variance: mean * (1. - mean) / table.samples.max(1) as f64,
+                    }
+                }
+                None => Sampler::Plain,
+            },
+            _ => Sampler::Plain,
+        }
+    }
+
+    pub(crate) fn is_plain(&self) -> bool {
+        matches!(self, Sampler::Plain)
+    }
+
+    // the control's equity on the runout `board`: a random pair of the
+    // starting hands' combos that shares no card with it played out.
+    // the real hands are one of them, so there's always one.
+    pub(crate) fn control(&self, board: u64, rng: &mut impl Rng) -> f64 {
+        let Sampler::ControlVariate { pairs, .. } = self else {
+            return 0.;
+        };
+        let live: Vec<&(u64, u64)> = pairs
+            .iter()
+            .filter(|&&(h, v)| (h | v) & board == 0)
+            .collect();
+        let &(h, v) = live[rng.gen_range(0..live.len())];
+        let (hero, villain) = (ladder_score(h | board), ladder_score(v | board));
+        match hero.cmp(&villain) {
+            std::cmp::Ordering::Greater => 1.,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.,
+        }
+    }
+}
+
+// every live card swapped for the next live card of its value in suit
+// order, going round. it only trades cards of the deck for each other, so
+// a runout dealt at random and swapped is just as random.
+fn suit_swap(deck: &[usize]) -> Vec<usize> {
+    let mut swap: Vec<usize> = (0..52).collect();
+    for value in 0..13 {
+        let live: Vec<usize> = (value * 4..value * 4 + 4)
+            .filter(|c| deck.contains(c))
+            .collect();
+        for (k, &c) in live.iter().enumerate() {
+            swap[c] = live[(k + 1) % live.len()];
+        }
+    }
+    swap
+}
+
+// `board` with the cards dealt onto `dealt` swapped.
+pub(crate) fn swapped(board: u64, dealt: u64, swap: &[usize]) -> u64 {
+    let runout: u64 = board & !dealt;
+    (0..52)
+        .filter(|&c| runout >> c & 1 == 1)
+        .fold(dealt, |acc, c| acc | 1 << swap[c])
+}
+
+// what a monte carlo equity came out as and how sure it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Estimate {
+    pub equity: f64,
+    pub std_err: f64,
+    // see SolveStats::variance_reduction.
+    pub reduction: Option<f32>,
+}
+
+// the running sums a monte carlo solve's estimate is worked out from.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Moments {
+    // by stratum, one unless stratified: how many showdowns there were,
+    // their equities added up and their squares added up. the strata get
+    // the same number of samples each.
+    strata: Vec<[f64; 3]>,
+    // antithetic pairs: how many, their average equities added up and
+    // their squares added up.
+    pairs: [f64; 3],
+    // the control's equities added up, their squares, and each times the
+    // hero's, added up.
+    control: [f64; 3],
+}
+
+impl Moments {
+    pub(crate) fn new(strata: usize) -> Self {
+        Moments {
+            strata: vec![[0.; 3]; strata.max(1)],
+            ..Moments::default()
+        }
+    }
+
+    pub(crate) fn add(&mut self, stratum: usize, equity: f64) {
+        let m: &mut [f64; 3] = &mut self.strata[stratum];
+        *m = [m[0] + 1., m[1] + equity, m[2] + equity * equity];
+    }
+
+    // a runout and its swapped twin.
+    pub(crate) fn add_pair(&mut self, a: f64, b: f64) {
+        self.add(0, a);
+        self.add(0, b);
+        let mean: f64 = (a + b) / 2.;
+        let p: &mut [f64; 3] = &mut self.pairs;
+        *p = [p[0] + 1., p[1] + mean, p[2] + mean * mean];
+    }
+
+    // the hero's equity on a runout and the control's.
+    pub(crate) fn add_control(&mut self, equity: f64, control: f64) {
+        self.add(0, equity);
+        let c: &mut [f64; 3] = &mut self.control;
+        *c = [
+            c[0] + control,
+            c[1] + control * control,
+            c[2] + control * equity,
+        ];
+    }
+
+    pub(crate) fn merge(&mut self, other: &Moments) {
+        for (m, o) in self.strata.iter_mut().zip(other.strata.iter()) {
+            *m = add(*m, *o);
+        }
+        self.pairs = add(self.pairs, other.pairs);
+        self.control = add(self.control, other.control);
+    }
+
+    // the estimate under `sampler`, its standard error, and how many times
+    // smaller its variance is than plain sampling's with as many showdowns.
+    pub(crate) fn estimate(&self, sampler: &Sampler) -> Estimate {
+        let all: [f64; 3] = self.strata.iter().fold([0.; 3], |acc, &m| add(acc, m));
+        let n: f64 = all[0].max(1.);
+        let mean: f64 = all[1] / n;
+        let plain: f64 = variance(all);
+        let (equity, error) = match sampler {
+            Sampler::Plain => (mean, plain),
+            // each stratum is sampled alike, so only the variance within
+            // them is left.
+            Sampler::Stratified => {
+                let within: f64 = self.strata.iter().map(|&m| variance(m)).sum::<f64>()
+                    / self.strata.len() as f64;
+                (mean, within)
+            }
+            // a pair counts as one sample of its average, standing in for
+            // two plain ones.
+            Sampler::Antithetic { .. } => {
+                let pairs: f64 = self.pairs[0].max(1.);
+                (self.pairs[1] / pairs, 2. * variance(self.pairs))
+            }
+            // the hero's equity less the share of the control's error it
+            // follows, whose variance is what the control can't explain.
+            // the table's entry is itself an estimate, and its error
+            // carries over as far as the hero follows the control.
+            Sampler::ControlVariate {
+                mean: expected,
+                table_variance,
+                ..
+            } => {
+                let [sum, squares, product] = self.control;
+                let control: f64 = variance([all[0], sum, squares]);
+                let covariance: f64 = match all[0] > 1. {
+                    true => (product - all[1] * sum / n) / (n - 1.),
+                    false => 0.,
+                };
+                match control > 0. {
+                    true => {
+                        let beta: f64 = covariance / control;
+                        let equity: f64 = mean - beta * (sum / n - expected);
+                        let left: f64 = plain - covariance * covariance / control;
+                        (equity, left + n * beta * beta * table_variance)
+                    }
+                    false => (mean, plain),
+                }
+            }
+        };
+        let error: f64 = error.max(0.);
+        Estimate {
+            equity: equity.clamp(0., 1.),
+            std_err: (error / n).sqrt(),
+            reduction: match sampler {
+                Sampler::Plain => None,
+                _ => (error > 0.).then(|| (plain / error) as f32),
+            },
+        }
+    }
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+// the sample variance of `[n, sum, squares]`.
+fn variance([n, sum, squares]: [f64; 3]) -> f64 {
+    match n > 1. {
+        true => (squares - sum * sum / n) / (n - 1.),
+        false => 0.,
+    }
+}
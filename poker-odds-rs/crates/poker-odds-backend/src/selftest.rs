@@ -0,0 +1,136 @@
+#[cfg(feature = "simd")]
+use crate::batch;
+use crate::solver::{default_threads, ladder_score, run_workers};
+use poker_odds_core::{card_names, describe, reference};
+#[cfg(feature = "simd")]
+use std::simd::u64x16;
+
+// mismatches kept with their cards, the rest are only counted.
+const EXAMPLES: usize = 20;
+
+// a hand an evaluator scored differently from the reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Mismatch {
+    pub cards: String,
+    pub evaluator: &'static str,
+    pub expected: String,
+    pub got: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SelfTest {
+    pub hands: u64,
+    pub mismatches: u64,
+    // the first few mismatches found.
+    pub examples: Vec<Mismatch>,
+}
+
+impl SelfTest {
+    pub fn passed(&self) -> bool {
+        self.mismatches == 0
+    }
+
+    pub(crate) fn check(&mut self, evaluator: &'static str, cards: u64, expected: u64, got: u64) {
+        if expected == got {
+            return;
+        }
+        self.mismatches += 1;
+        if self.examples.len() < EXAMPLES {
+            self.examples.push(Mismatch {
+                cards: card_names(cards),
+                evaluator,
+                expected: describe(expected),
+                got: describe(got),
+            });
+        }
+    }
+
+    fn merge(&mut self, other: SelfTest) {
+        self.hands += other.hands;
+        self.mismatches += other.mismatches;
+        let room: usize = EXAMPLES - self.examples.len();
+        self.examples.extend(other.examples.into_iter().take(room));
+    }
+}
+
+// scores every one of the C(52, 7) seven card hands with the lookup tables
+// (and the batch evaluator when there's simd), and every five and six card
+// hand, which flops and turns are scored as, with the tables too, and
+// checks them against a reference that tries every five card hand. takes
+// about a minute on one core, `progress` gets the fraction of hands done.
+pub fn selftest(mut progress: impl FnMut(f32)) -> SelfTest {
+    const TOTAL: u64 = 2_598_960 + 20_358_520 + 133_784_560;
+
+    let nthreads: usize = default_threads();
+    // the hands are split up by their size and lowest card.
+    let parts: Vec<(u32, usize)> = (5..=7)
+        .flat_map(|n| (0..=52 - n as usize).map(move |low| (n, low)))
+        .collect();
+    let workers: Vec<_> = (0..nthreads)
+        .map(|t| {
+            let parts: Vec<(u32, usize)> = parts.clone();
+            move |report: &mut dyn FnMut(SelfTest)| {
+                for &(n, low) in parts.iter().skip(t).step_by(nthreads) {
+                    report(check_lowest(n, low));
+                }
+            }
+        })
+        .collect();
+
+    let mut result: SelfTest = SelfTest::default();
+    run_workers(workers, |part: SelfTest| {
+        result.merge(part);
+        progress(result.hands as f32 / TOTAL as f32);
+    });
+    result
+}
+
+// every `n` card hand whose lowest card is `low`. only seven cards go
+// through the batch evaluator, showdowns are all it scores.
+fn check_lowest(n: u32, low: usize) -> SelfTest {
+    let mut result: SelfTest = SelfTest::default();
+    #[cfg(feature = "simd")]
+    let mut pending: Vec<(u64, u64)> = Vec::with_capacity(batch::LANES);
+
+    // the other cards walk through every combination of the cards above
+    // `low`, counted from the one just above it.
+    let mut rest: u64 = (1 << (n - 1)) - 1;
+    while rest < 1 << (51 - low) {
+        let cards: u64 = 1 << low | rest << (low + 1);
+        let expected: u64 = reference(cards);
+        result.hands += 1;
+        result.check("ladder", cards, expected, ladder_score(cards));
+        #[cfg(feature = "simd")]
+        if n == 7 {
+            pending.push((cards, expected));
+            if pending.len() == batch::LANES {
+                check_batch(&mut result, &mut pending);
+            }
+        }
+        rest = next_combination(rest);
+    }
+    #[cfg(feature = "simd")]
+    check_batch(&mut result, &mut pending);
+    result
+}
+
+#[cfg(feature = "simd")]
+fn check_batch(result: &mut SelfTest, pending: &mut Vec<(u64, u64)>) {
+    let mut lanes: [u64; batch::LANES] = [0; batch::LANES];
+    for (lane, (cards, _)) in lanes.iter_mut().zip(pending.iter()) {
+        *lane = *cards;
+    }
+    let scores: [u64; batch::LANES] = batch::scores(u64x16::from_array(lanes)).to_array();
+    for ((cards, expected), got) in pending.drain(..).zip(scores) {
+        result.check("batch", cards, expected, got);
+    }
+}
+
+// the next larger mask with as many bits set.
+fn next_combination(x: u64) -> u64 {
+    let low: u64 = x & x.wrapping_neg();
+    let ripple: u64 = x + low;
+    (((ripple ^ x) >> 2) / low) | ripple
+}
@@ -0,0 +1,271 @@
+use crate::error::Error;
+use crate::history::HandHistory;
+use crate::solver::{Solution, SolveMode, Solver};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// buckets in the histogram of simulated sessions.
+const BUCKETS: usize = 20;
+
+// an all in before the river that went to showdown, and what the hero's
+// equity entitled them to against what the runout gave them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllIn {
+    pub hand_id: String,
+    // the hero's cards first, then those shown against them.
+    pub hands: Vec<String>,
+    // the board when the money went in.
+    pub board: String,
+    pub equity: f32,
+    // the pot after rake. side pots are counted as part of it.
+    pub pot: f32,
+    // `equity` of the pot.
+    pub expected: f32,
+    // what the hero collected.
+    pub won: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionReport {
+    // hands the hero was dealt into.
+    pub hands: usize,
+    pub all_ins: Vec<AllIn>,
+    // the hero's winnings over the session.
+    pub winnings: f32,
+    // the same with every all in paid out by equity instead of by the
+    // runout, "all-in adjusted winnings".
+    pub adjusted: f32,
+}
+
+impl SessionReport {
+    // how much the runouts of the all ins ran above (or below) equity.
+    pub fn luck(&self) -> f32 {
+        self.winnings - self.adjusted
+    }
+
+    // replays the all ins `trials` times, each one won whole with the
+    // probability of the hero's equity, and says where the hero's actual
+    // result falls among them. splits and side pots are taken as all or
+    // nothing, so the spread is a little wider than it really was.
+    pub fn variance(&self, trials: usize, seed: u64) -> Variance {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let outcomes: Vec<f32> = (0..trials)
+            .map(|_| {
+                self.all_ins
+                    .iter()
+                    .filter(|a| rng.gen::<f32>() < a.equity)
+                    .map(|a| a.pot)
+                    .sum()
+            })
+            .collect();
+
+        let actual: f32 = self.all_ins.iter().map(|a| a.won).sum();
+        let below: usize = outcomes.iter().filter(|&&o| o < actual).count();
+        let equal: usize = outcomes.iter().filter(|&&o| o == actual).count();
+        let low: f32 = outcomes.iter().copied().fold(f32::INFINITY, f32::min);
+        let high: f32 = outcomes.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mut counts: Vec<u32> = vec![0; BUCKETS];
+        for &o in outcomes.iter() {
+            counts[bucket(o, low, high)] += 1;
+        }
+
+        Variance {
+            expected: self.all_ins.iter().map(|a| a.expected).sum(),
+            std_dev: self
+                .all_ins
+                .iter()
+                .map(|a| a.pot * a.pot * a.equity * (1. - a.equity))
+                .sum::<f32>()
+                .sqrt(),
+            actual,
+            percentile: (below as f32 + equal as f32 / 2.) / trials.max(1) as f32,
+            low: low.min(high),
+            high: high.max(low),
+            counts,
+        }
+    }
+}
+
+// how the all ins of a session could have gone.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variance {
+    // what the all ins were worth by equity, and the standard deviation of
+    // what they pay out.
+    pub expected: f32,
+    pub std_dev: f32,
+    // what the hero collected from them.
+    pub actual: f32,
+    // the share of simulated sessions that did worse than the hero, ties
+    // counting half. near 0 is running bad, near 1 running good.
+    pub percentile: f32,
+    // the simulated winnings, counted in equal buckets from `low` to `high`.
+    pub low: f32,
+    pub high: f32,
+    pub counts: Vec<u32>,
+}
+
+impl Variance {
+    // the bucket of `counts` that `winnings` falls in.
+    pub fn bucket(&self, winnings: f32) -> usize {
+        bucket(winnings, self.low, self.high)
+    }
+}
+
+fn bucket(winnings: f32, low: f32, high: f32) -> usize {
+    if high <= low {
+        return 0;
+    }
+    let i: f32 = (winnings - low) / (high - low) * BUCKETS as f32;
+    (i.max(0.) as usize).min(BUCKETS - 1)
+}
+
+// the hero's winnings over `histories` next to their all-in adjusted
+// winnings. every all in is solved with `mode`.
+pub fn all_in_report(
+    solver: &Solver,
+    histories: &[HandHistory],
+    mode: SolveMode,
+) -> Result<SessionReport, Error> {
+    let mut report: SessionReport = SessionReport::default();
+    for hand in histories {
+        let Some(hero) = &hand.hero else {
+            continue;
+        };
+        let net: f32 = hand.net(hero);
+        report.hands += 1;
+        report.winnings += net;
+        report.adjusted += net;
+
+        let Some((hands, board)) = all_in_spot(hand, hero) else {
+            continue;
+        };
+        let solution: Solution = solver.try_solve_with_progress(&hands, &board, mode, |_| {})?;
+        let pot: f32 = hand.total_pot - hand.rake;
+        let all_in: AllIn = AllIn {
+            hand_id: hand.id.clone(),
+            hands,
+            board,
+            equity: solution.equity,
+            pot,
+            expected: solution.equity * pot,
+            won: hand.collected.get(hero).copied().unwrap_or(0.),
+        };
+        report.adjusted += all_in.expected - all_in.won;
+        report.all_ins.push(all_in);
+    }
+    Ok(report)
+}
+
+// the hands that saw the showdown, the hero's first, and the board when
+// the last chips went in, if that was an all in before the river.
+fn all_in_spot(hand: &HandHistory, hero: &str) -> Option<(Vec<String>, String)> {
+    if hand.folded(hero) {
+        return None;
+    }
+    let last_bet = hand.actions.iter().rfind(|a| a.amount > 0.)?;
+    let all_in: bool = hand
+        .actions
+        .iter()
+        .any(|a| a.all_in && a.board_cards == last_bet.board_cards);
+    if !all_in || last_bet.board_cards >= 5 {
+        return None;
+    }
+
+    let mut opponents: Vec<(&String, &String)> = hand
+        .hole_cards
+        .iter()
+        .filter(|(player, _)| *player != hero && !hand.folded(player))
+        .collect();
+    opponents.sort();
+    let mut hands: Vec<String> = vec![hand.hole_cards.get(hero)?.clone()];
+    hands.extend(opponents.into_iter().map(|(_, cards)| cards.clone()));
+    if hands.len() < 2 {
+        return None;
+    }
+    Some((hands, hand.board[..last_bet.board_cards * 2].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::parse_hand_histories;
+
+    // aces all in against kings on the flop, and the kings spike a set.
+    const COOLER: &str = "\
+PokerStars Hand #2001: Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:00:00 ET
+Alice: posts small blind $0.50
+Bob: posts big blind $1
+Dealt to Alice [Ah Ad]
+Alice: raises $2 to $3
+Bob: calls $2
+*** FLOP *** [2c 7d 9s]
+Bob: bets $47 and is all-in
+Alice: calls $47
+*** TURN *** [2c 7d 9s] [Kc]
+*** RIVER *** [2c 7d 9s Kc] [3h]
+Bob: shows [Kh Kd] (three of a kind, Kings)
+Alice: shows [Ah Ad] (a pair of Aces)
+Bob collected $98.50 from pot
+Total pot $100 | Rake $1.50
+";
+
+    #[test]
+    fn pays_an_all_in_out_by_equity() {
+        let histories: Vec<HandHistory> = parse_hand_histories(COOLER).unwrap();
+        let report: SessionReport =
+            all_in_report(&Solver::new(), &histories, SolveMode::Exact).unwrap();
+        assert_eq!(report.hands, 1);
+        assert_eq!(report.winnings, -50.);
+        let all_in: &AllIn = &report.all_ins[0];
+        assert_eq!(all_in.hands, ["AhAd", "KhKd"]);
+        assert_eq!(all_in.board, "2c7d9s");
+        // of the 990 runouts the kings need one of the two kings left, and
+        // still lose the 4 that bring an ace with it.
+        let equity: f32 = (903. + 4.) / 990.;
+        assert!((all_in.equity - equity).abs() < 1e-4);
+        assert_eq!(all_in.pot, 98.5);
+        assert_eq!(all_in.won, 0.);
+        assert!((report.adjusted - (98.5 * equity - 50.)).abs() < 1e-2);
+        assert!(report.luck() < -80.);
+    }
+
+    #[test]
+    fn a_fold_is_no_all_in() {
+        let folded: String = COOLER.replace("Alice: calls $47", "Alice: folds");
+        let histories: Vec<HandHistory> = parse_hand_histories(&folded).unwrap();
+        let report: SessionReport =
+            all_in_report(&Solver::new(), &histories, SolveMode::Exact).unwrap();
+        assert!(report.all_ins.is_empty());
+        assert_eq!(report.winnings, report.adjusted);
+    }
+
+    #[test]
+    fn a_losing_runout_sits_low_among_the_simulated_ones() {
+        let all_in = |won: f32| AllIn {
+            hand_id: String::new(),
+            hands: Vec::new(),
+            board: String::new(),
+            equity: 0.9,
+            pot: 100.,
+            expected: 90.,
+            won,
+        };
+        let report: SessionReport = SessionReport {
+            all_ins: vec![all_in(0.), all_in(0.)],
+            ..SessionReport::default()
+        };
+        let v: Variance = report.variance(2000, 7);
+        assert_eq!(v.expected, 180.);
+        assert_eq!(v.actual, 0.);
+        // both lost is 1% of sessions, counted half as a tie.
+        assert!(v.percentile < 0.02);
+        assert_eq!((v.low, v.high), (0., 200.));
+        assert_eq!(v.counts.iter().sum::<u32>(), 2000);
+        assert_eq!(v.bucket(0.), 0);
+        assert_eq!(v.bucket(200.), BUCKETS - 1);
+        assert_eq!(v, report.variance(2000, 7));
+    }
+}
@@ -0,0 +1,168 @@
+// scores every player's best five cards on a batch of runouts and tallies
+// the hero's wins and split pots. cards are bits in a 64 bit mask, split
+// in two words, with card i being rank i / 4 (deuce first) and suit i % 4.
+
+struct Params {
+    nplayers: u32,
+    hero: u32,
+    nrunouts: u32,
+    pad: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> hands: array<vec2<u32>>;
+// complete five card boards.
+@group(0) @binding(2) var<storage, read> runouts: array<vec2<u32>>;
+// runouts won outright, then the hero's share of split pots in 1/SHARE pots.
+@group(0) @binding(3) var<storage, read_write> tally: array<atomic<u32>, 2>;
+
+// divisible by every split from 2 to 10 ways.
+const SHARE: u32 = 2520u;
+
+var<workgroup> local_wins: atomic<u32>;
+var<workgroup> local_ties: atomic<u32>;
+
+// top card of the best straight in a rank mask plus one, 0 if there's none.
+fn straight_high(ranks: u32) -> u32 {
+    // the ace also plays below the deuce.
+    let m: u32 = (ranks << 1u) | ((ranks >> 12u) & 1u);
+    for (var hi: i32 = 13; hi >= 4; hi = hi - 1) {
+        if (((m >> u32(hi - 4)) & 0x1fu) == 0x1fu) {
+            return u32(hi);
+        }
+    }
+    return 0u;
+}
+
+// keeps the n highest ranks of a mask.
+fn top(ranks: u32, n: u32) -> u32 {
+    var m: u32 = ranks;
+    var out: u32 = 0u;
+    for (var i: u32 = 0u; i < n && m != 0u; i = i + 1u) {
+        let b: u32 = 1u << firstLeadingBit(m);
+        out = out | b;
+        m = m & ~b;
+    }
+    return out;
+}
+
+// category in the top bits, then two 13 bit tie breakers.
+fn score(cat: u32, a: u32, b: u32) -> u32 {
+    return (cat << 26u) | (a << 13u) | b;
+}
+
+fn evaluate(lo: u32, hi: u32) -> u32 {
+    var suits = array<u32, 4>(0u, 0u, 0u, 0u);
+    var w: u32 = lo;
+    while (w != 0u) {
+        let i: u32 = firstTrailingBit(w);
+        w = w & (w - 1u);
+        suits[i % 4u] = suits[i % 4u] | (1u << (i / 4u));
+    }
+    w = hi;
+    while (w != 0u) {
+        let i: u32 = firstTrailingBit(w) + 32u;
+        w = w & (w - 1u);
+        suits[i % 4u] = suits[i % 4u] | (1u << (i / 4u));
+    }
+
+    for (var s: u32 = 0u; s < 4u; s = s + 1u) {
+        if (countOneBits(suits[s]) >= 5u) {
+            let sf: u32 = straight_high(suits[s]);
+            if (sf != 0u) {
+                return score(8u, sf, 0u);
+            }
+            // seven cards can't hold a flush and quads or a full house.
+            return score(5u, top(suits[s], 5u), 0u);
+        }
+    }
+
+    let ranks: u32 = suits[0] | suits[1] | suits[2] | suits[3];
+    var quads: u32 = 0u;
+    var trips: u32 = 0u;
+    var pairs: u32 = 0u;
+    for (var r: u32 = 0u; r < 13u; r = r + 1u) {
+        let c: u32 = ((suits[0] >> r) & 1u) + ((suits[1] >> r) & 1u)
+            + ((suits[2] >> r) & 1u) + ((suits[3] >> r) & 1u);
+        if (c == 4u) {
+            quads = quads | (1u << r);
+        } else if (c == 3u) {
+            trips = trips | (1u << r);
+        } else if (c == 2u) {
+            pairs = pairs | (1u << r);
+        }
+    }
+
+    if (quads != 0u) {
+        return score(7u, quads, top(ranks & ~quads, 1u));
+    }
+    if (trips != 0u) {
+        let t: u32 = top(trips, 1u);
+        let p: u32 = top((trips & ~t) | pairs, 1u);
+        if (p != 0u) {
+            return score(6u, t, p);
+        }
+    }
+    let st: u32 = straight_high(ranks);
+    if (st != 0u) {
+        return score(4u, st, 0u);
+    }
+    if (trips != 0u) {
+        return score(3u, trips, top(ranks & ~trips, 2u));
+    }
+    if (countOneBits(pairs) >= 2u) {
+        let two: u32 = top(pairs, 2u);
+        return score(2u, two, top(ranks & ~two, 1u));
+    }
+    if (pairs != 0u) {
+        return score(1u, pairs, top(ranks & ~pairs, 3u));
+    }
+    return score(0u, top(ranks, 5u), 0u);
+}
+
+@compute @workgroup_size(256)
+fn main(
+    @builtin(global_invocation_id) gid: vec3<u32>,
+    @builtin(local_invocation_index) lid: u32,
+) {
+    if (lid == 0u) {
+        atomicStore(&local_wins, 0u);
+        atomicStore(&local_ties, 0u);
+    }
+    workgroupBarrier();
+
+    let i: u32 = gid.x;
+    if (i < params.nrunouts) {
+        let board: vec2<u32> = runouts[i];
+        let hole: vec2<u32> = hands[params.hero];
+        let hero: u32 = evaluate(board.x | hole.x, board.y | hole.y);
+        var lost: bool = false;
+        var tied: u32 = 0u;
+        for (var p: u32 = 0u; p < params.nplayers; p = p + 1u) {
+            if (p == params.hero) {
+                continue;
+            }
+            let h: vec2<u32> = hands[p];
+            let theirs: u32 = evaluate(board.x | h.x, board.y | h.y);
+            if (theirs > hero) {
+                lost = true;
+                break;
+            }
+            if (theirs == hero) {
+                tied = tied + 1u;
+            }
+        }
+        if (!lost && tied == 0u) {
+            atomicAdd(&local_wins, 1u);
+        } else if (!lost) {
+            atomicAdd(&local_ties, SHARE / (tied + 1u));
+        }
+    }
+
+    // one global atomic per workgroup instead of one per runout.
+    workgroupBarrier();
+    if (lid == 0u) {
+        atomicAdd(&tally[0], atomicLoad(&local_wins));
+        atomicAdd(&tally[1], atomicLoad(&local_ties));
+    }
+}
@@ -1,14 +1,56 @@
-use dashmap::DashMap;
-use num_cpus;
-use std::collections::HashMap;
-use std::io;
-use std::simd::cmp::{SimdPartialEq, SimdPartialOrd};
-use std::simd::num::SimdUint;
-use std::simd::{u64x16, u64x4};
+#[cfg(feature = "simd")]
+use crate::batch;
+use crate::buckets::{BucketOptions, EquityBuckets};
+use crate::cards::{Board, HoleCards};
+use crate::deck::{sample_runouts, Combinations, Deck};
+use crate::error::Error;
+use crate::ev::{break_even_fold, BluffCheck};
+#[cfg(feature = "gpu")]
+use crate::gpu::GpuEvaluator;
+use crate::isomorphism::{canonical, permute};
+use crate::lookup;
+use crate::matrix::{score_runouts, RangeMatrix, Score};
+use crate::memo::Memo;
+#[cfg(feature = "parallel")]
+use crate::pool::WorkerPool;
+use crate::preflop::PreflopTable;
+use crate::query::Scenario;
+use crate::range::Range;
+use crate::rare::{category_odds, Category, CategoryEstimate};
+use crate::sampling::{swapped, Estimate, Moments, Sampler, Sampling};
+#[cfg(feature = "verify")]
+use crate::selftest::SelfTest;
+use crate::store::ResultStore;
+use crate::strength::{
+    expected_strength, hero_and_board, random_equity, HandStrength, RANDOM_SAMPLES,
+};
+use crate::texture::TextureReport;
+#[cfg(feature = "verify")]
+use crate::verify::Verifier;
+use ahash::AHashMap;
+use log::debug;
+#[cfg(feature = "gpu")]
+use log::warn;
+use poker_odds_core::{BitSet, Card, CATEGORIES};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+#[cfg(feature = "parallel")]
+use std::panic;
+#[cfg(feature = "simd")]
+use std::simd::cmp::{SimdOrd, SimdPartialEq, SimdPartialOrd};
+#[cfg(feature = "simd")]
+use std::simd::{u64x16, Select};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+#[cfg(feature = "parallel")]
+use std::sync::mpsc;
 use std::sync::Arc;
+#[cfg(feature = "simd")]
+use std::sync::OnceLock;
+#[cfg(feature = "parallel")]
 use std::thread;
-use std::time::SystemTime;
-use strum_macros::EnumIter;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 enum Rank {
@@ -24,105 +66,92 @@ enum Rank {
     RoyalFlush = 9,
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy, EnumIter)]
-enum Suits {
-    Clubs,
-    Hearts,
-    Spades,
-    Diamonds,
-}
-
-impl Suits {
-    fn from_char(c: char) -> Self {
-        match c {
-            'c' => Suits::Clubs,
-            'h' => Suits::Hearts,
-            's' => Suits::Spades,
-            'd' => Suits::Diamonds,
-            _ => panic!("not a valid char"),
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, EnumIter)]
-enum Value {
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    Seven = 7,
-    Eight = 8,
-    Nine = 9,
-    Ten = 10,
-    Jack = 11,
-    Queen = 12,
-    King = 13,
-    Ace = 14,
-}
-
-impl From<u8> for Value {
-    fn from(value: u8) -> Self {
-        match value {
-            2 => Value::Two,
-            3 => Value::Three,
-            4 => Value::Four,
-            5 => Value::Five,
-            6 => Value::Six,
-            7 => Value::Seven,
-            8 => Value::Eight,
-            9 => Value::Nine,
-            10 => Value::Ten,
-            11 => Value::Jack,
-            12 => Value::Queen,
-            13 => Value::King,
-            14 => Value::Ace,
-            _ => panic!("Invalid card value"),
-        }
-    }
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-struct Card {
-    value: Value,
-    suit: Suits,
-    idx: usize,
-}
-
-impl Card {
-    fn new(value: Value, suit: Suits) -> Self {
-        let mut _idx = value as usize * 4 - 8;
-        for (i, s) in [Suits::Clubs, Suits::Hearts, Suits::Spades, Suits::Diamonds]
-            .iter()
-            .enumerate()
+impl Rank {
+    // a score's category, with the ace high straight flush told apart.
+    fn of(category: u32, kicker: u32) -> Self {
+        match category {
+            0 => Rank::HighCard,
+            1 => Rank::Pair,
+            2 => Rank::TwoPair,
+            3 => Rank::Trips,
+            4 => Rank::Straight,
+            5 => Rank::Flush,
+            6 => Rank::FullHouse,
+            7 => Rank::Quads,
+            _ if kicker == 14 << 13 => Rank::RoyalFlush,
+            _ => Rank::StraightFlush,
+        }
+    }
+}
+
+// instruction sets the evaluators have been specialized for, so one
+// build runs well on any cpu without -C target-cpu=native.
+#[cfg(feature = "simd")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SimdLevel {
+    #[cfg(target_arch = "x86_64")]
+    Avx512,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    // popcnt and u64 lane compares, on about every x86 cpu since 2008.
+    #[cfg(target_arch = "x86_64")]
+    Sse42,
+    #[cfg(target_arch = "aarch64")]
+    Neon,
+    // whatever the build target guarantees.
+    Baseline,
+}
+
+#[cfg(feature = "simd")]
+impl SimdLevel {
+    // checked once, the answer can't change while we run.
+    fn detect() -> Self {
+        static LEVEL: OnceLock<SimdLevel> = OnceLock::new();
+        *LEVEL.get_or_init(|| {
+            let level: SimdLevel = Self::probe();
+            debug!("Using the {:?} hand evaluator.", level);
+            level
+        })
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn probe() -> Self {
+        if is_x86_feature_detected!("avx512f")
+            && is_x86_feature_detected!("avx512bw")
+            && is_x86_feature_detected!("avx512vl")
+            && is_x86_feature_detected!("avx512cd")
+            && Self::has_bit_ops()
         {
-            if suit == *s {
-                _idx += i;
-                break;
-            }
+            SimdLevel::Avx512
+        } else if is_x86_feature_detected!("avx2") && Self::has_bit_ops() {
+            SimdLevel::Avx2
+        } else if is_x86_feature_detected!("sse4.2") && is_x86_feature_detected!("popcnt") {
+            SimdLevel::Sse42
+        } else {
+            SimdLevel::Baseline
         }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn has_bit_ops() -> bool {
+        is_x86_feature_detected!("popcnt")
+            && is_x86_feature_detected!("lzcnt")
+            && is_x86_feature_detected!("bmi1")
+            && is_x86_feature_detected!("bmi2")
+    }
 
-        Card {
-            value,
-            suit,
-            idx: _idx,
+    #[cfg(target_arch = "aarch64")]
+    fn probe() -> Self {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            SimdLevel::Neon
+        } else {
+            SimdLevel::Baseline
         }
     }
 
-    fn from_string(s: String) -> Self {
-        let s: Vec<u8> = s.chars().map(|x| x as u8).collect();
-        let value: u8 = match s[0] {
-            65 => 14,
-            75 => 13,
-            81 => 12,
-            74 => 11,
-            84 => 10,
-            50..=57 => s[0] - 48,
-            _ => panic!("Not a valid value"),
-        };
-        let suit: Suits = Suits::from_char(s[1] as char);
-        Self::new(Value::from(value), suit)
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    fn probe() -> Self {
+        SimdLevel::Baseline
     }
 }
 
@@ -130,627 +159,430 @@ impl Card {
 struct Hand {
     hole: (Card, Card),
     hole_b: u64,
-    memo: HashMap<u64, Rank>,
+    // rank and kicker by seven card set. fx hash is no good here, the
+    // low bits of the keys barely change so everything lands in a few buckets.
+    memo: AHashMap<u64, (Rank, u32)>,
+    memoize: bool,
     kicker: u32,
 }
 
 impl Hand {
     fn new(hole: (Card, Card)) -> Self {
         Hand {
-            hole: hole,
+            hole,
             hole_b: 1 << hole.0.idx | 1 << hole.1.idx,
-            memo: HashMap::new(),
+            memo: AHashMap::new(),
+            memoize: true,
             kicker: 0,
         }
     }
 
-    fn rank(&mut self, board: &u64) -> Rank {
-        let cards_key: u64 = self.hole_b | *board;
-
-        if self.memo.contains_key(&cards_key) {
-            return self.memo[&cards_key];
-        }
-
-        let mut _rank: Rank = Rank::HighCard;
-
-        // TODO [optimization]:
-        // The lower down the if-else statement,
-        // the more likely the hand is. We are doing quite
-        // a bit of branching here, and perhaps branch
-        // mispredictions.
-
-        let cards_vec: u64x16 = u64x16::splat(cards_key);
-
-        if self.is_royal_flush(&cards_key) {
-            _rank = Rank::RoyalFlush;
-        } else if self.is_straight_flush_simd(&cards_vec) {
-            _rank = Rank::StraightFlush;
-        } else if self.is_quads_simd(&cards_vec) {
-            _rank = Rank::Quads;
-        } else if self.is_fullhouse_simd(&cards_vec) {
-            _rank = Rank::FullHouse;
-        } else if self.is_flush_simd(&cards_key) {
-            _rank = Rank::Flush;
-        } else if self.is_straight_simd(&cards_vec) {
-            _rank = Rank::Straight;
-        } else if self.is_three_of_a_kind_simd(&cards_vec) {
-            _rank = Rank::Trips;
-        } else if self.is_two_pair_simd(&cards_vec) {
-            _rank = Rank::TwoPair;
-        } else if self.is_pair_simd(&cards_vec) {
-            _rank = Rank::Pair;
-        } else {
-            // _rank is Rank::HighCard.
-            self.compute_kicker_for_high_card(&cards_key);
+    // the same hole cards with a clean rank memo.
+    fn fork(&self) -> Self {
+        Hand {
+            memoize: self.memoize,
+            ..Hand::new(self.hole)
         }
-        self.memo.insert(cards_key, _rank);
-        _rank
     }
 
-    fn is_royal_flush(&self, cards: &u64) -> bool {
-        // mask := cards in a royal flush of suit clubs. shift left for next suit.
-        let mut mask: u64 = 1 << 32 | 1 << 36 | 1 << 40 | 1 << 44 | 1 << 48;
-        (0..4).fold(false, |acc, x| {
-            mask <<= (x != 0) as u64; // shift by 1 if it's not the first iteration.
-            acc | ((mask & *cards) == mask)
-        })
-    }
+    #[inline(always)]
+    fn rank(&mut self, board: &u64) -> Rank {
+        let cards_key: u64 = self.hole_b | *board;
 
-    #[allow(dead_code)]
-    fn is_straight_flush(&mut self, cards: &u64) -> bool {
-        // start at king high straight flush of suit club.
-        // no need to check royal flush as we check that before.
-        let mut mask: u64 = 1 << 28 | 1 << 32 | 1 << 36 | 1 << 40 | 1 << 44;
-        let aces: u64 = 1 << 48 | 1 << 49 | 1 << 50 | 1 << 51;
-
-        for i in 0..9 {
-            for sh in 0..4 {
-                let valid: bool = mask & *cards == mask;
-                if (i < 8 && valid)
-                    || (i == 8 && valid && ((*cards & aces) & (1 << (48 + sh)) != 0))
-                {
-                    self.kicker = 13 - i as u32;
-                    return true;
-                }
-                mask <<= 1;
+        if self.memoize {
+            if let Some(&(rank, kicker)) = self.memo.get(&cards_key) {
+                self.kicker = kicker;
+                return rank;
             }
-            // go to next largest straight flush
-            mask >>= 8;
-        }
-        false
-    }
-
-    fn is_straight_flush_simd(&mut self, cards_vec: &u64x16) -> bool {
-        let mut base_mask: u64 = 1 << 28 | 1 << 32 | 1 << 36 | 1 << 40 | 1 << 44;
-        let mut aces: u64 = 1 << 48;
-
-        const ZERO_OUT_MASK: u64 = 0b1111111 << 9;
-
-        for _ in 0..4 {
-            let lanes: u64x16 = u64x16::from_array([
-                base_mask >> 32 | aces,
-                base_mask >> 28,
-                base_mask >> 24,
-                base_mask >> 20,
-                base_mask >> 16,
-                base_mask >> 12,
-                base_mask >> 8,
-                base_mask >> 4,
-                base_mask,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-            ]);
-
-            let hits: u64x16 = *cards_vec & lanes;
-            let mut mask: u64 = hits.simd_eq(lanes).to_bitmask();
-            // zero out first 7 bits in the last 16 bit chunk
-            mask ^= ZERO_OUT_MASK;
-
-            if mask == 0 {
-                base_mask <<= 1;
-                aces <<= 1;
-                continue;
-            }
-            self.kicker = 64 - mask.leading_zeros() as u32;
-            return true;
         }
-        false
-    }
 
-    #[allow(dead_code)]
-    fn is_quads(&mut self, cards: &u64) -> bool {
-        let mut mask: u64 = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        for i in 0..13 {
-            if mask & *cards == mask {
-                self.kicker = 14 - i as u32;
-                return true;
-            }
-            mask >>= 4;
-        }
-        false
-    }
-
-    fn is_quads_simd(&mut self, cards_vec: &u64x16) -> bool {
-        let lanes: u64x16 = u64x16::from_array([
-            0xF,
-            0xF << 4,
-            0xF << 8,
-            0xF << 12,
-            0xF << 16,
-            0xF << 20,
-            0xF << 24,
-            0xF << 28,
-            0xF << 32,
-            0xF << 36,
-            0xF << 40,
-            0xF << 44,
-            0xF << 48,
-            0,
-            0,
-            0,
-        ]);
-
-        let hits: u64x16 = *cards_vec & lanes;
-        let mut mask: u64 = hits.simd_eq(lanes).to_bitmask();
-        // zero out the top 3 set bits.
-        mask ^= 0b111 << 13;
-
-        if mask == 0 {
-            // more likely
-            return false;
-        }
-        self.kicker = 64 - mask.leading_zeros() as u32;
-        true
-    }
-
-    #[allow(dead_code)]
-    fn is_fullhouse(&mut self, cards: &u64) -> bool {
-        let mut mask: u64 = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        let mut tmp: u32 = 0;
-
-        for i in 0..13 {
-            if (mask & *cards).count_ones() == 3 {
-                tmp = 14 - i;
-                break;
-            }
-            mask >>= 4;
+        let score: u32 = lookup::score(cards_key);
+        self.kicker = score & ((1 << 26) - 1);
+        let rank: Rank = Rank::of(score >> 26, self.kicker);
+        if self.memoize {
+            self.memo.insert(cards_key, (rank, self.kicker));
         }
+        rank
+    }
 
-        if tmp == 0 {
-            return false;
-        }
+    // the rank and kicker in one number, see ladder_score.
+    #[cfg(feature = "verify")]
+    fn score(&mut self, board: &u64) -> u64 {
+        // a royal flush is just the best straight flush.
+        let rank: Rank = self.rank(board).min(Rank::StraightFlush);
+        (rank as u64) << 26 | self.kicker as u64
+    }
 
-        mask = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        for i in 0..13 {
-            // not the three of a kind
-            if i + tmp != 14 {
-                if (mask & *cards).count_ones() >= 2 {
-                    self.kicker = tmp * 100 + 14 - i;
-                    return true;
-                }
-            }
-            mask >>= 4;
-        }
-        false
-    }
-
-    fn is_fullhouse_simd(&mut self, cards_vec: &u64x16) -> bool {
-        let lanes: u64x16 = u64x16::from_array([
-            0xF,
-            0xF << 4,
-            0xF << 8,
-            0xF << 12,
-            0xF << 16,
-            0xF << 20,
-            0xF << 24,
-            0xF << 28,
-            0xF << 32,
-            0xF << 36,
-            0xF << 40,
-            0xF << 44,
-            0xF << 48,
-            0,
-            0,
-            0,
-        ]);
-
-        let hits_count_set: u64x16 = (*cards_vec & lanes).count_ones();
-        let eq3: u64 = hits_count_set.simd_eq(u64x16::splat(3)).to_bitmask();
-        let ge2: u64 = hits_count_set.simd_ge(u64x16::splat(2)).to_bitmask();
-
-        if eq3 == 0 {
-            return false;
-        }
-        let shift_eq3: u64 = 63 - eq3.leading_zeros() as u64;
-        // xor to zero out the top value with 3 occurences
-        let ge2_xor_eq3_mask: u64 = ge2 ^ (1 << shift_eq3);
-        if ge2_xor_eq3_mask == 0 {
-            return false;
-        }
-        let shift_ge2: u64 = 63 - ge2_xor_eq3_mask.leading_zeros() as u64;
-
-        self.kicker = (shift_eq3 * 100 + shift_ge2) as u32;
-        true
-    }
-
-    #[allow(dead_code)]
-    fn is_flush(&mut self, cards: &u64) -> bool {
-        // start with clubs
-        let mut mask: u64 = (0..52).step_by(4).fold(0, |acc, x| acc | (1 << x));
-        for _ in 0..4 {
-            let m: u64 = mask & *cards;
-            if m.count_ones() >= 5 {
-                // this won't return the exact highest card value, but its a monotonic
-                // function and we save some instructions by avoiding needing to call %
-                // to compute exact value.
-                self.kicker = 64 - m.leading_zeros();
-                return true;
-            }
-            mask <<= 1;
+    fn parse(s: &str) -> Result<Self, Error> {
+        if s.len() != 4 || !s.is_ascii() {
+            return Err(Error::InvalidHand(s.to_string()));
         }
-        false
+        let (h1, h2) = s.split_at(2);
+        Ok(Hand::new((parse_card(h1)?, parse_card(h2)?)))
     }
+}
 
-    fn is_flush_simd(&mut self, cards: &u64) -> bool {
-        let suit_mask: u64 = (0..52).step_by(4).fold(0, |acc, x| acc | (1 << x));
+// scores a five to seven card mask as Hand::score does, in the batch evaluator's
+// format: the category from bit 26 up, then the tie breakers.
+pub(crate) fn ladder_score(cards: u64) -> u64 {
+    lookup::score(cards) as u64
+}
 
-        let lanes: u64x4 =
-            u64x4::from_array([suit_mask, suit_mask << 1, suit_mask << 2, suit_mask << 3]);
+// the values of a hand's cards, highest first, and whether they're suited.
+pub(crate) fn hole_values(hand: &str) -> Result<(u8, u8, bool), Error> {
+    let hand: &str = hand.trim();
+    let (a, b) = Hand::parse(hand)?.hole;
+    if a.idx == b.idx {
+        return Err(Error::DuplicateCard(hand[..2].to_string()));
+    }
+    let (hi, lo) = (a.value.max(b.value), a.value.min(b.value));
+    Ok((hi as u8, lo as u8, a.suit == b.suit))
+}
 
-        let hits: u64x4 = u64x4::splat(*cards) & lanes;
-        // only the last 4 bits matter, rest are zero
-        let mask: u64 = hits.count_ones().simd_ge(u64x4::splat(5)).to_bitmask();
+// the card mask of a hand like "AhKd".
+pub(crate) fn hand_mask(hand: &str) -> Result<u64, Error> {
+    let hand: &str = hand.trim();
+    let h: Hand = Hand::parse(hand)?;
+    if h.hole.0.idx == h.hole.1.idx {
+        return Err(Error::DuplicateCard(hand[..2].to_string()));
+    }
+    Ok(h.hole_b)
+}
 
-        if mask == 0 {
-            // more likely
-            return false;
-        }
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SolveMode {
+    // walk every runout.
+    Exact,
+    // deal `samples` random runouts.
+    MonteCarlo { samples: usize },
+    // walk runouts until the equity is known to be above or below
+    // `threshold`, e.g. the equity needed to call given the pot odds.
+    Threshold { threshold: f32 },
+    // deal random runouts until the 95% confidence interval is within
+    // `half_width` either side of the equity, e.g. 0.001 for a tenth of a
+    // percent, or until `budget` is spent, see SolveStats::stop. solves
+    // that can't stop early, like range matrices, walk every runout.
+    UntilPrecision { half_width: f32, budget: Duration },
+}
 
-        // find the suit offset.
-        // d = 0 if clubs, 1 if hearts, 2 if spades, 3 if diamonds
-        let d: u64 = 63 - mask.leading_zeros() as u64;
-        // all the cards present that are of the flush suit.
-        let cmask: u64 = (suit_mask << d) & cards;
+// why a solve that stops on its own stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopReason {
+    // the confidence interval got as narrow as asked.
+    Precision,
+    // the time ran out first.
+    Budget,
+}
 
-        // less leading zeros, higher the flush
-        // so we invert the value to get a kicker val.
-        self.kicker = 64 - cmask.leading_zeros();
-        true
+impl SolveMode {
+    // deals random runouts rather than walking them.
+    pub fn is_sampled(&self) -> bool {
+        matches!(
+            self,
+            SolveMode::MonteCarlo { .. } | SolveMode::UntilPrecision { .. }
+        )
     }
+}
 
-    #[allow(dead_code)]
-    fn is_straight(&mut self, cards: &u64) -> bool {
-        let mut key_bin: u16 = 0;
-        // the following is all twos
-        let mut repr: u64 = 1 | 1 << 1 | 1 << 2 | 1 << 3;
+// how the hero did over a number of runouts. counts are exact, so
+// tallies of subtrees add up without rounding in any order. exact solves
+// count every order the cards can be dealt in, so there `runouts` is the
+// number of distinct runouts times the number of orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tally {
+    // runouts the hero won outright.
+    pub wins: u64,
+    // the hero's share of split pots, in 1/SHARE pots.
+    pub ties: u64,
+    pub runouts: u64,
+}
 
-        for i in 0..13 {
-            if *cards & repr != 0 {
-                key_bin |= 1 << (i + 1);
-                // if is ace
-                if i == 12 {
-                    key_bin |= 1;
-                }
-            }
-            repr <<= 4;
+impl Tally {
+    // divisible by every split from 2 to 10 ways.
+    pub const SHARE: u64 = 2520;
+
+    // a single runout the hero lost (None) or won, splitting the pot with
+    // `tied` opponents.
+    fn runout(tied: Option<u64>) -> Self {
+        match tied {
+            None => Tally {
+                runouts: 1,
+                ..Tally::default()
+            },
+            Some(0) => Tally {
+                wins: 1,
+                ties: 0,
+                runouts: 1,
+            },
+            Some(n) => Tally {
+                wins: 0,
+                ties: Self::SHARE / (n + 1),
+                runouts: 1,
+            },
         }
+    }
 
-        let mut mask: u16 = 1 << 14 | 1 << 13 | 1 << 12 | 1 << 11 | 1 << 10;
-
-        for i in 0..11 {
-            if mask & key_bin == mask {
-                self.kicker = 14 - i;
-                return true;
-            }
-            mask >>= 1;
-        }
-        false
-    }
-
-    fn is_straight_simd(&mut self, cards_vec: &u64x16) -> bool {
-        // 1: first convert to a bit map of the values present.
-        let lanes: u64x16 = u64x16::from_array([
-            0xF,
-            0xF << 4,
-            0xF << 8,
-            0xF << 12,
-            0xF << 16,
-            0xF << 20,
-            0xF << 24,
-            0xF << 28,
-            0xF << 32,
-            0xF << 36,
-            0xF << 40,
-            0xF << 44,
-            0xF << 48,
-            0,
-            0,
-            0,
-        ]);
-
-        let hits: u64x16 = *cards_vec & lanes;
-
-        // shift by one as cards assumes 2 is smallest bit.
-        // need to make room for ace.
-        let mut mask: u64 = hits.simd_ne(u64x16::splat(0)).to_bitmask() << 1;
-
-        // if ace exists, then set the smallest bit too.
-        mask |= ((1 << 13) & mask > 0) as u64;
-
-        // 2: then, find 5 bits in a row.
-        // the below is (1 << 14 | 1 << 13 | 1 << 12 | 1 << 11 | 1 << 10)
-        // shifted all the way down 10 times
-        let ms: u64x16 = u64x16::from_array([
-            0, 0, 0, 0, 0, 31, 62, 124, 248, 496, 992, 1984, 3968, 7936, 15872, 31744,
-        ]);
-
-        let h: u64x16 = u64x16::splat(mask) & ms;
-        let mut z: u64 = h.simd_eq(ms).to_bitmask();
-        // zero out the last 5 bits
-        z ^= 0b11111;
-
-        if z == 0 {
-            // more likely
-            return false;
-        }
-        self.kicker = 63 - z.leading_zeros() as u32;
-        true
-    }
-
-    #[allow(dead_code)]
-    fn is_three_of_a_kind(&mut self, cards: &u64) -> bool {
-        // this assumes its not a full house
-        let mut mask: u64 = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        let mut tmp: u32 = 0;
-        let mut count: usize = 0;
-
-        for i in 0..13 {
-            if (mask & *cards).count_ones() == 3 {
-                tmp = 14 - i;
-                count += 1;
-                break;
-            }
-            mask >>= 4;
+    pub fn equity(&self) -> f32 {
+        if self.runouts == 0 {
+            return 0.;
         }
+        let won: u64 = self.wins * Self::SHARE + self.ties;
+        (won as f64 / (self.runouts * Self::SHARE) as f64) as f32
+    }
+}
 
-        if count == 0 {
-            return false;
-        }
+impl std::ops::AddAssign for Tally {
+    fn add_assign(&mut self, other: Tally) {
+        self.wins += other.wins;
+        self.ties += other.ties;
+        self.runouts += other.runouts;
+    }
+}
 
-        mask = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        for i in 0..13 {
-            if mask & *cards != 0 {
-                tmp = tmp * 100 + 14 - i;
-                count += 1;
-            }
-            if count == 3 {
-                self.kicker = tmp;
-                return true;
-            }
-            mask >>= 4;
-        }
-        false
-    }
-
-    fn is_three_of_a_kind_simd(&mut self, cards_vec: &u64x16) -> bool {
-        let lanes: u64x16 = u64x16::from_array([
-            0xF,
-            0xF << 4,
-            0xF << 8,
-            0xF << 12,
-            0xF << 16,
-            0xF << 20,
-            0xF << 24,
-            0xF << 28,
-            0xF << 32,
-            0xF << 36,
-            0xF << 40,
-            0xF << 44,
-            0xF << 48,
-            0,
-            0,
-            0,
-        ]);
-
-        let hits_count_set: u64x16 = (*cards_vec & lanes).count_ones();
-        // in theory there should only be 1 set bit, if more then its a fullhouse.
-        // assumption: assume only at most 1 set bit in val3
-        let val3: u64 = hits_count_set.simd_eq(u64x16::splat(3)).to_bitmask();
-
-        if val3 == 0 {
-            return false;
-        }
-
-        let mut val1: u64 = hits_count_set.simd_eq(u64x16::splat(1)).to_bitmask();
-
-        // subtract from 64 instead of 63 as we do not want tmp to be 0.
-        let mut tmp: u32 = 64 - val3.leading_zeros(); // the val that 3peats
-        for _ in 0..2 {
-            let d: u32 = 64 - val1.leading_zeros();
-            tmp = tmp * 100 + d;
-            val1 ^= 1 << (d - 1); // unset this bit
-        }
-        self.kicker = tmp;
-        true
-    }
-
-    #[allow(dead_code)]
-    fn is_two_pair(&mut self, cards: &u64) -> bool {
-        let mut mask: u64 = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        let mut tmp: u32 = 0;
-        let mut count: usize = 0;
-
-        // find the two pair first
-        for i in 0..13 {
-            if (mask & *cards).count_ones() == 2 {
-                tmp = tmp * 100 + 14 - i;
-                count += 1;
-            }
-            mask >>= 4;
-        }
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    // with a control variate, the tally's equity corrected by the control.
+    pub equity: f32,
+    // the counts `equity` was worked out from.
+    pub tally: Tally,
+    // lower and upper bound on the exact equity in threshold mode. when the
+    // solve stopped early `equity` is the average over the runouts walked.
+    pub bounds: Option<(f32, f32)>,
+    pub stats: SolveStats,
+}
 
-        if count < 2 {
-            return false;
+impl Solution {
+    // the 95% confidence interval around a monte carlo equity, None for the
+    // other modes.
+    pub fn confidence(&self) -> Option<(f32, f32)> {
+        if !self.stats.mode.is_sampled() {
+            return None;
         }
+        let n: f32 = self.tally.runouts.max(1) as f32;
+        let std_err: f32 = self
+            .stats
+            .std_err
+            .unwrap_or_else(|| (self.equity * (1. - self.equity) / n).sqrt());
+        let margin: f32 = 1.96 * std_err;
+        Some((
+            (self.equity - margin).max(0.),
+            (self.equity + margin).min(1.),
+        ))
+    }
+}
 
-        // then find the kicker
-        mask = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        for i in 0..13 {
-            if mask & *cards != 0 {
-                self.kicker = tmp * 100 + 14 - i;
-                return true;
-            }
-            mask >>= 4;
-        }
-        false
-    }
-
-    fn is_two_pair_simd(&mut self, cards_vec: &u64x16) -> bool {
-        let lanes: u64x16 = u64x16::from_array([
-            0xF,
-            0xF << 4,
-            0xF << 8,
-            0xF << 12,
-            0xF << 16,
-            0xF << 20,
-            0xF << 24,
-            0xF << 28,
-            0xF << 32,
-            0xF << 36,
-            0xF << 40,
-            0xF << 44,
-            0xF << 48,
-            0,
-            0,
-            0,
-        ]);
-
-        let hits_count_set: u64x16 = (*cards_vec & lanes).count_ones();
-        let mut val2: u64 = hits_count_set.simd_eq(u64x16::splat(2)).to_bitmask();
-
-        if val2.count_ones() < 2 {
-            return false;
-        }
-
-        let val1: u64 = hits_count_set.simd_eq(u64x16::splat(1)).to_bitmask();
-
-        let mut tmp: u32 = 0;
-        for _ in 0..2 {
-            let d: u32 = 64 - val2.leading_zeros();
-            tmp = tmp * 100 + d;
-            val2 ^= 1 << (d - 1);
-        }
-
-        self.kicker = tmp * 100 + (64 - val1.leading_zeros());
-        true
-    }
-
-    #[allow(dead_code)]
-    fn is_pair(&mut self, cards: &u64) -> bool {
-        let mut mask: u64 = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        let mut tmp: u32 = 0;
-        let mut count: usize = 0;
-
-        for i in 0..13 {
-            if (mask & *cards).count_ones() == 2 {
-                tmp = tmp * 100 + 14 - i;
-                count += 1;
-                break;
-            }
-            mask >>= 4;
-        }
+// the distinct runouts an exact solve of `players` hands walks with
+// `board_cards` dealt, e.g. 1712304 heads up preflop.
+pub fn exact_runouts(players: usize, board_cards: usize) -> u64 {
+    let deck: u64 = 52u64.saturating_sub(2 * players as u64 + board_cards as u64);
+    let need: u64 = 5u64.saturating_sub(board_cards as u64);
+    (0..need).fold(1, |n, i| n * deck.saturating_sub(i) / (i + 1))
+}
 
-        if count == 0 {
-            return false;
-        }
+fn choose(n: u64, k: u64) -> u64 {
+    (0..k).fold(1, |c, i| c * n.saturating_sub(i) / (i + 1))
+}
 
-        mask = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        for i in 0..13 {
-            if mask & *cards != 0 {
-                tmp = tmp * 100 + 14 - i;
-                count += 1;
-            }
-            if count == 4 {
-                self.kicker = tmp;
-                return true;
-            }
-            mask >>= 4;
-        }
-        false
-    }
+// what an exact solve of a spot is in for, worked out before starting it
+// so frontends can warn about the big ones, e.g. multiway preflop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveSize {
+    // distinct runouts, see exact_runouts.
+    pub runouts: u64,
+    // showdowns the walk scores from an empty memo, SolveStats::leaves.
+    // with simd every board one card short is scored over all its rivers,
+    // so each runout is scored once per card it could have come last.
+    pub leaves: u64,
+    // subtrees the walk memoizes, one per set of cards dealt short of a
+    // full board.
+    pub nodes: u64,
+    // time per leaf, timed on a burst of the spot's own.
+    pub leaf_time: Duration,
+    pub threads: usize,
+    // the leaves at that pace, split over the threads.
+    pub eta: Duration,
+}
 
-    fn is_pair_simd(&mut self, cards_vec: &u64x16) -> bool {
-        let lanes: u64x16 = u64x16::from_array([
-            0xF,
-            0xF << 4,
-            0xF << 8,
-            0xF << 12,
-            0xF << 16,
-            0xF << 20,
-            0xF << 24,
-            0xF << 28,
-            0xF << 32,
-            0xF << 36,
-            0xF << 40,
-            0xF << 44,
-            0xF << 48,
-            0,
-            0,
-            0,
-        ]);
+// how long Solver::exact_size times leaves for.
+const CALIBRATION: Duration = Duration::from_millis(20);
+
+// how a solve went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveStats {
+    pub mode: SolveMode,
+    // showdowns evaluated, on the cpu or the gpu.
+    pub leaves: u64,
+    // memo lookups made by this solve.
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    // worker threads used, 0 when the answer came from the memo or the gpu.
+    pub threads: usize,
+    // wall time spent solving.
+    pub elapsed: Duration,
+    // time the worker threads spent working, added up over them.
+    pub busy: Duration,
+    // the whole answer was already in the memo.
+    pub cached: bool,
+    // what ended an UntilPrecision solve, None for the other modes.
+    pub stop: Option<StopReason>,
+    // the standard error of a monte carlo equity under the sampling that
+    // dealt it. None for the other solves, and on the gpu.
+    pub std_err: Option<f32>,
+    // how many times smaller the variance of a monte carlo equity is than
+    // plain sampling's with as many showdowns, see Sampling. below one when
+    // the sampling cost more than it saved. None for plain sampling and
+    // the other solves.
+    pub variance_reduction: Option<f32>,
+}
 
-        // in theory there should only be 1 set bit, otherwise its 2 pair.
-        let hits_count_set: u64x16 = (*cards_vec & lanes).count_ones();
-        let val2: u64 = hits_count_set.simd_eq(u64x16::splat(2)).to_bitmask();
+impl SolveStats {
+    // the share of memo lookups that found their subtree.
+    pub fn hit_rate(&self) -> f32 {
+        let lookups: u64 = self.cache_hits + self.cache_misses;
+        self.cache_hits as f32 / lookups.max(1) as f32
+    }
 
-        if val2 == 0 {
-            return false;
+    // how much of the solve the average thread was working for, 1 when
+    // none of them sat idle waiting for the others to finish.
+    pub fn utilization(&self) -> f32 {
+        let available: f64 = self.elapsed.as_secs_f64() * self.threads as f64;
+        if available == 0. {
+            return 0.;
         }
+        (self.busy.as_secs_f64() / available).min(1.) as f32
+    }
+}
 
-        let mut val1: u64 = hits_count_set.simd_eq(u64x16::splat(1)).to_bitmask();
+// every player's equity on one street of a hand being replayed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Street {
+    // "Preflop", "Flop", "Turn" or "River".
+    pub name: String,
+    // the board dealt so far.
+    pub board: String,
+    // by player, in the order the hands were given, the hero's first.
+    pub equities: Vec<f32>,
+}
 
-        let mut tmp: u32 = 64 - val2.leading_zeros(); // val that is a pair
-        for _ in 0..2 {
-            let d: u32 = 64 - val1.leading_zeros();
-            tmp = tmp * 100 + d;
-            val1 ^= 1 << (d - 1);
-        }
+// everything a solve found out, for frontends that show more than the
+// hero's equity.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetailedSolution {
+    // the hero's, as from try_solve.
+    pub equity: f32,
+    // by player, in the order the hands were given, the hero's first.
+    pub players: Vec<PlayerResult>,
+    pub stats: SolveStats,
+}
+
+// the hero's equity against a weighted range and what it was averaged
+// over. combos sharing a card with the hero or the board can't be dealt,
+// so the equity is normalized by the weight of the ones left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeEquity {
+    pub equity: f32,
+    // the combos that can still be dealt and their summed weight, what
+    // the equity is divided by.
+    pub combos: usize,
+    pub weight: f32,
+    // the weight of every combo of the range, blocked ones included.
+    pub range_weight: f32,
+}
 
-        self.kicker = tmp;
-        true
+impl RangeEquity {
+    // the share of the range's weight the hero's cards and the board
+    // leave, e.g. 0.75 when blockers take out a quarter of it.
+    pub fn live_share(&self) -> f32 {
+        self.weight / self.range_weight.max(f32::MIN_POSITIVE)
     }
+}
 
-    fn compute_kicker_for_high_card(&mut self, cards: &u64) {
-        let mut mask: u64 = 1 << 51 | 1 << 50 | 1 << 49 | 1 << 48;
-        let mut tmp: u32 = 0;
-        let mut count: usize = 0;
+// how one player did over the runouts solved.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerResult {
+    pub hand: String,
+    // ties split the pot between the players sharing it.
+    pub equity: f32,
+    // shares of the runouts won alone, tied for the best hand and lost.
+    pub win: f32,
+    pub tie: f32,
+    pub lose: f32,
+    // how often the player ends up with each hand category, from high card
+    // to straight flush.
+    pub categories: Vec<(String, f32)>,
+}
 
-        for i in 0..13 {
-            if (mask & *cards).count_ones() == 1 {
-                tmp = tmp * 100 + 14 - i;
-                count += 1;
-            }
+// a worker's counts for one player in solve_detailed.
+#[derive(Debug, Clone, Copy, Default)]
+struct PlayerCounts {
+    wins: u64,
+    ties: u64,
+    equity: f64,
+    categories: [u64; 9],
+}
 
-            if count == 5 {
-                self.kicker = tmp;
-                break;
-            }
-            mask >>= 4;
-        }
+// how the hero's equity is spread over the cards that can come next.
+// draws swing much more than made hands of the same equity.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EquityDistribution {
+    // the equity after each card, e.g. every turn on a flop.
+    pub by_card: Vec<(String, f32)>,
+    // the average over the cards, which is the equity now.
+    pub mean: f32,
+    pub std_dev: f32,
+    // how many cards leave the equity in each tenth of [0, 1].
+    pub histogram: [u32; 10],
+}
+
+impl EquityDistribution {
+    // the cards from best to worst for the hero, to see which ones to fear.
+    pub fn ranked(&self) -> Vec<(String, f32)> {
+        let mut ranked: Vec<(String, f32)> = self.by_card.clone();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
     }
 
-    fn from_string(s: String) -> Self {
-        let (h1, h2) = s.split_at(2);
-        Hand::new((
-            Card::from_string(h1.to_string()),
-            Card::from_string(h2.to_string()),
-        ))
+    // the `n` cards that help the hero most, with how much each one moves
+    // the equity from where it is now.
+    pub fn best(&self, n: usize) -> Vec<(String, f32)> {
+        self.ranked()
+            .into_iter()
+            .take(n)
+            .map(|(card, e)| (card, e - self.mean))
+            .collect()
     }
+
+    // the `n` cards that hurt the hero most, worst first.
+    pub fn worst(&self, n: usize) -> Vec<(String, f32)> {
+        self.ranked()
+            .into_iter()
+            .rev()
+            .take(n)
+            .map(|(card, e)| (card, e - self.mean))
+            .collect()
+    }
+}
+
+// a partial result reported while a solve is running.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EquityEstimate {
+    pub equity: f32,
+    // fraction of the work done, in [0, 1].
+    pub progress: f32,
+    // standard error of a monte carlo estimate.
+    pub std_err: Option<f32>,
 }
 
 #[derive(Debug, Clone)]
@@ -763,59 +595,92 @@ impl Game {
     pub fn new(hero_pos: usize, hands: Vec<Hand>) -> Self {
         Game { hero_pos, hands }
     }
-}
-
-#[derive(Debug, Clone)]
-struct BitSet {
-    s: u64,
-    length: usize,
-}
-
-impl BitSet {
-    fn new() -> Self {
-        BitSet { s: 0, length: 0 }
-    }
 
-    fn add(&mut self, idx: usize) {
-        if !self.contains(idx) {
-            self.s |= 1 << idx;
-            self.length += 1;
-        }
+    // the game and board with the suits relabeled so the matchup key is as
+    // small as it gets, see isomorphism.rs, and the relabeling.
+    fn canonical(self, board: u64) -> (Game, u64, [usize; 4]) {
+        let key: Vec<u64> = self.key();
+        let (perm, _, _) = canonical(&key, 1);
+        let hands: Vec<Hand> = self
+            .hands
+            .iter()
+            .map(|h| {
+                let mask: u64 = permute(h.hole_b, &perm);
+                let low: usize = mask.trailing_zeros() as usize;
+                let high: usize = 63 - mask.leading_zeros() as usize;
+                Hand::new((Card::from_idx(high), Card::from_idx(low)))
+            })
+            .collect();
+        (Game::new(self.hero_pos, hands), permute(board, &perm), perm)
     }
 
-    fn remove(&mut self, idx: usize) {
-        if self.contains(idx) {
-            self.s -= 1 << idx;
-            self.length -= 1;
-        }
-    }
+    // the hero's hole cards followed by the opponents' in sorted order.
+    // two games with the same key share every subtree value.
+    fn key(&self) -> Vec<u64> {
+        let mut opponents: Vec<u64> = self
+            .hands
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.hero_pos)
+            .map(|(_, h)| h.hole_b)
+            .collect();
+        opponents.sort_unstable();
 
-    fn contains(&self, idx: usize) -> bool {
-        (self.s >> idx) & 1 == 1
+        let mut key: Vec<u64> = vec![self.hands[self.hero_pos].hole_b];
+        key.extend(opponents);
+        key
     }
+}
 
-    fn len(&self) -> usize {
-        self.length
-    }
+// subtree tallies keyed by the set of drawn cards.
+type EquityMemo = Arc<Memo<u64, Tally>>;
 
-    fn add_board(&mut self, board: &u64) {
-        self.length += ((*board).count_ones() - (*board & self.s).count_ones()) as usize;
-        self.s |= *board;
-    }
+// what every worker of a solve reads and nobody writes.
+#[derive(Debug)]
+struct Spot {
+    hero_pos: usize,
+    board: u64,
+    memo: EquityMemo,
+    memo_mode: MemoMode,
+    // the solver's, summed over all its solves.
+    counters: Arc<Counters>,
+    // this solve's.
+    solve_counters: Counters,
+    #[cfg(feature = "verify")]
+    verifier: Arc<Verifier>,
 }
 
-#[derive(Debug, Clone)]
+// a worker's view of a solve: the shared spot plus its own scratch.
+#[derive(Debug)]
 struct Brancher {
-    game: Game,
-    hero: Hand,
+    spot: Arc<Spot>,
+    // every seat's hand, hero included. ranking one updates its kicker
+    // and rank memo, so each worker has its own.
+    hands: Vec<Hand>,
     drawn: BitSet,
-    board: u64,
-    memo: Arc<DashMap<u64, f32>>,
+    // opponents in the order they're checked at showdown, the ones that
+    // beat the hero most often first so losing leaves bail out early.
+    order: Vec<usize>,
+    // how often each seat beat the hero, by seat.
+    beats: Vec<u32>,
+    leaves: u32,
+    // a worker's own entries in MemoMode::ThreadLocal.
+    local: Option<AHashMap<u64, Tally>>,
+    // showdowns and memo lookups since the last flush_counters.
+    showdowns: u64,
+    hits: u64,
+    misses: u64,
 }
 
 impl Brancher {
-    fn new(game: Game, board: u64, memo: Arc<DashMap<u64, f32>>) -> Self {
-        let hero = game.hands[game.hero_pos].clone();
+    fn new(
+        game: Game,
+        board: u64,
+        memo: EquityMemo,
+        memo_mode: MemoMode,
+        counters: Arc<Counters>,
+        #[cfg(feature = "verify")] verifier: Arc<Verifier>,
+    ) -> Self {
         let mut drawn = BitSet::new();
 
         for hand in game.hands.iter() {
@@ -824,222 +689,2480 @@ impl Brancher {
         }
 
         drawn.add_board(&board);
+        let order: Vec<usize> = (0..game.hands.len())
+            .filter(|&i| i != game.hero_pos)
+            .collect();
+        let beats: Vec<u32> = vec![0; game.hands.len()];
 
         Brancher {
-            game,
-            hero,
+            spot: Arc::new(Spot {
+                hero_pos: game.hero_pos,
+                board,
+                memo,
+                memo_mode,
+                counters,
+                solve_counters: Counters::default(),
+                #[cfg(feature = "verify")]
+                verifier,
+            }),
+            hands: game.hands,
             drawn,
-            board,
-            memo,
+            order,
+            beats,
+            leaves: 0,
+            local: None,
+            showdowns: 0,
+            hits: 0,
+            misses: 0,
         }
     }
 
-    fn branch(&mut self, board: &mut u64) -> f32 {
-        if let Some(val) = self.memo.get(&self.drawn.s) {
-            return *val;
+    // deals `card` onto the board of a spot that's been solved. the memo
+    // and the seats' rank memos carry over, so the next street's solve
+    // starts with every subtree the last one finished, its own included.
+    fn extend_board(&mut self, card: usize) {
+        let spot: &Spot = &self.spot;
+        self.spot = Arc::new(Spot {
+            hero_pos: spot.hero_pos,
+            board: spot.board | 1 << card,
+            memo: spot.memo.clone(),
+            memo_mode: spot.memo_mode,
+            counters: spot.counters.clone(),
+            solve_counters: Counters::default(),
+            #[cfg(feature = "verify")]
+            verifier: spot.verifier.clone(),
+        });
+        self.drawn.add(card);
+    }
+
+    // a brancher for another worker. shares the spot and starts with
+    // fresh scratch.
+    fn fork(&self) -> Self {
+        Brancher {
+            spot: self.spot.clone(),
+            hands: self.hands.iter().map(Hand::fork).collect(),
+            drawn: self.drawn.clone(),
+            order: self.order.clone(),
+            beats: vec![0; self.beats.len()],
+            leaves: 0,
+            local: match self.spot.memo_mode {
+                MemoMode::Shared => None,
+                MemoMode::ThreadLocal => Some(AHashMap::new()),
+            },
+            showdowns: 0,
+            hits: 0,
+            misses: 0,
         }
+    }
 
-        if board.count_ones() == 5 {
-            let hero_rank = self.hero.rank(board);
-            let hero_kicker = self.hero.kicker;
+    fn lookup(&mut self) -> Option<Tally> {
+        let val: Option<Tally> = match &self.local {
+            Some(local) => local
+                .get(&self.drawn.s)
+                .copied()
+                .or_else(|| self.spot.memo.get(&self.drawn.s)),
+            None => self.spot.memo.get(&self.drawn.s),
+        };
+        if val.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        val
+    }
 
-            let beats_all = self
-                .game
-                .hands
-                .iter_mut()
-                .enumerate()
-                .filter(|&(i, _)| i != self.game.hero_pos)
-                .all(|(_, hand)| {
-                    let v = hand.rank(board);
-                    hero_rank > v || (hero_rank == v && hero_kicker >= hand.kicker)
-                });
-            let val: f32 = if beats_all { 1. } else { 0. };
-            self.memo.insert(self.drawn.s, val);
-            return val;
+    fn store(&mut self, val: Tally) {
+        match &mut self.local {
+            Some(local) => {
+                local.insert(self.drawn.s, val);
+            }
+            None => self.spot.memo.insert(self.drawn.s, val),
         }
+    }
 
-        let mut pb: f32 = 0.;
-        for i in 0..52 {
-            if !self.drawn.contains(i) {
-                self.add_to_end_of_board(i, board);
-                pb += self.branch(board);
-                self.remove_from_end_of_board(i, board);
+    // hands a worker's entries over to the shared memo once it's done.
+    fn merge_local(&mut self) {
+        if let Some(local) = self.local.take() {
+            for (k, v) in local {
+                self.spot.memo.insert(k, v);
             }
         }
+    }
 
-        pb /= (52 - self.drawn.len()) as f32;
-        self.memo.insert(self.drawn.s, pb);
-        pb
+    // counting locally and flushing once keeps the atomics off the hot path.
+    fn flush_counters(&mut self) {
+        let solve: &Counters = &self.spot.solve_counters;
+        solve.leaves.fetch_add(self.showdowns, Ordering::Relaxed);
+        for counters in [&*self.spot.counters, solve] {
+            counters.hits.fetch_add(self.hits, Ordering::Relaxed);
+            counters.misses.fetch_add(self.misses, Ordering::Relaxed);
+        }
+        self.showdowns = 0;
+        self.hits = 0;
+        self.misses = 0;
     }
 
-    fn branch_parallel(&self) -> f32 {
-        // use up all the cores we got
-        let nthreads: usize = num_cpus::get_physical();
-        println!("Running on {:} threads.", nthreads);
+    // stats for a solve that started at `start`, counting what's been
+    // flushed so far.
+    fn stats(&self, mode: SolveMode, threads: usize, start: Instant, cached: bool) -> SolveStats {
+        let counters: &Counters = &self.spot.solve_counters;
+        SolveStats {
+            mode,
+            leaves: counters.leaves.load(Ordering::Relaxed),
+            cache_hits: counters.hits.load(Ordering::Relaxed),
+            cache_misses: counters.misses.load(Ordering::Relaxed),
+            threads,
+            elapsed: start.elapsed(),
+            busy: Duration::from_nanos(counters.busy.load(Ordering::Relaxed)),
+            cached,
+            stop: None,
+            std_err: None,
+            variance_reduction: None,
+        }
+    }
 
-        let step: usize = 52 / nthreads;
-        let chunks: Vec<(usize, usize)> = (0..52)
-            .step_by(step)
-            .map(|s| (s, (s + step).min(52)))
-            .collect();
+    // counts the time since `start` as time a worker spent working.
+    fn clock(&self, start: Instant) {
+        let nanos: u64 = start.elapsed().as_nanos() as u64;
+        self.spot
+            .solve_counters
+            .busy
+            .fetch_add(nanos, Ordering::Relaxed);
+    }
 
-        let handles: Vec<_> = chunks
-            .into_iter()
-            .map(|(s, e)| {
-                let mut local_brancher = self.clone();
-                thread::spawn(move || {
-                    let mut pb: f32 = 0.;
-                    let mut board: u64 = local_brancher.board;
-                    for i in s..e {
-                        if !local_brancher.drawn.contains(i) {
-                            local_brancher.add_to_end_of_board(i, &mut board);
-                            pb += local_brancher.branch(&mut board);
-                            local_brancher.remove_from_end_of_board(i, &mut board);
-                        }
-                    }
+    fn showdown(&mut self, board: &u64) -> Tally {
+        #[cfg(feature = "verify")]
+        if Verifier::sampled(*board) {
+            for hand in self.hands.iter_mut() {
+                let got: u64 = hand.score(board);
+                self.spot.verifier.check("ladder", hand.hole_b | board, got);
+            }
+        }
 
-                    pb
-                })
-            })
-            .collect();
+        self.showdowns += 1;
+        // with the rank memo on, the hands look their ranks up one by one.
+        #[cfg(feature = "simd")]
+        if !self.hands[self.spot.hero_pos].memoize {
+            if let Some(val) = self.showdown_lanes(*board) {
+                return val;
+            }
+        }
+        self.showdown_seats(*board)
+    }
 
-        let mut sum_pb: f32 = 0.;
-        for h in handles {
-            sum_pb += h.join().unwrap();
+    // same dispatch as river, the table lookups leaning on popcnt.
+    #[cfg(feature = "simd")]
+    fn showdown_seats(&mut self, board: u64) -> Tally {
+        match SimdLevel::detect() {
+            // SAFETY: detect only reports features the cpu has.
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Avx512 => unsafe { self.showdown_seats_avx512(board) },
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Avx2 => unsafe { self.showdown_seats_avx2(board) },
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Sse42 => unsafe { self.showdown_seats_sse42(board) },
+            #[cfg(target_arch = "aarch64")]
+            SimdLevel::Neon => unsafe { self.showdown_seats_neon(board) },
+            SimdLevel::Baseline => self.showdown_seats_generic(board),
         }
+    }
 
-        sum_pb / (52 - self.drawn.len()) as f32
+    #[cfg(not(feature = "simd"))]
+    fn showdown_seats(&mut self, board: u64) -> Tally {
+        self.showdown_seats_generic(board)
     }
 
-    fn add_to_end_of_board(&mut self, card_idx: usize, board: &mut u64) {
-        self.drawn.add(card_idx);
-        *board |= 1 << card_idx;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "avx512f,avx512bw,avx512vl,avx512cd,popcnt,lzcnt,bmi1,bmi2")]
+    fn showdown_seats_avx512(&mut self, board: u64) -> Tally {
+        self.showdown_seats_generic(board)
     }
 
-    fn remove_from_end_of_board(&mut self, card_idx: usize, board: &mut u64) {
-        self.drawn.remove(card_idx);
-        *board -= 1 << card_idx;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "avx2,popcnt,lzcnt,bmi1,bmi2")]
+    fn showdown_seats_avx2(&mut self, board: u64) -> Tally {
+        self.showdown_seats_generic(board)
     }
 
-    fn compute_equity(&mut self) -> f32 {
-        /*
-        Run on one thread if 4 cards are
-        already on the board to avoid overhead
-        of copying and moving onto threads.
-        */
-        if let Some(val) = self.memo.get(&self.drawn.s) {
-            println!("[Cached] Equity is {:}.", *val);
-            return *val;
-        }
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "sse4.2,popcnt")]
+    fn showdown_seats_sse42(&mut self, board: u64) -> Tally {
+        self.showdown_seats_generic(board)
+    }
 
-        let p: f32;
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    #[target_feature(enable = "neon")]
+    fn showdown_seats_neon(&mut self, board: u64) -> Tally {
+        self.showdown_seats_generic(board)
+    }
 
-        if self.board.count_ones() >= 4 {
-            let mut board: u64 = self.board.clone();
-            p = self.branch(&mut board);
-        } else {
-            p = self.branch_parallel();
-            self.memo.insert(self.drawn.s, p);
+    // the hero against each opponent in turn, the ones that win most
+    // often first.
+    #[inline(always)]
+    fn showdown_seats_generic(&mut self, board: u64) -> Tally {
+        // leaves between reorderings of the opponents.
+        const REORDER_EVERY: u32 = 256;
+
+        let hero_pos: usize = self.spot.hero_pos;
+        let hero_rank = self.hands[hero_pos].rank(&board);
+        let hero_kicker = self.hands[hero_pos].kicker;
+
+        self.leaves += 1;
+        if self.leaves.is_multiple_of(REORDER_EVERY) && self.order.len() > 1 {
+            let beats: &Vec<u32> = &self.beats;
+            self.order.sort_by_key(|&i| Reverse(beats[i]));
+        }
+
+        let mut tied: u64 = 0;
+        for &i in self.order.iter() {
+            let hand: &mut Hand = &mut self.hands[i];
+            let v = hand.rank(&board);
+            if hero_rank < v || (hero_rank == v && hero_kicker < hand.kicker) {
+                self.beats[i] += 1;
+                return Tally::runout(None);
+            }
+            if hero_rank == v && hero_kicker == hand.kicker {
+                tied += 1;
+            }
         }
-        println!("Equity is {:}.", p);
-        p
+        Tally::runout(Some(tied))
     }
-}
 
-pub struct Solver {
-    memo: Arc<DashMap<u64, f32>>,
-}
+    fn branch(&mut self, board: &mut u64) -> Tally {
+        if let Some(val) = self.lookup() {
+            return val;
+        }
 
-impl Solver {
-    pub fn new() -> Self {
-        Solver {
-            memo: Arc::new(DashMap::with_shard_amount(64)),
+        // the rivers are scored together, so they never reach the memo.
+        #[cfg(feature = "simd")]
+        if board.count_ones() == 4 {
+            let val: Tally = self.river(*board);
+            self.showdowns += val.runouts;
+            self.store(val);
+            return val;
+        }
+
+        if board.count_ones() == 5 {
+            let val: Tally = self.showdown(board);
+            self.store(val);
+            return val;
+        }
+
+        let mut tally: Tally = Tally::default();
+        for i in 0..52 {
+            if !self.drawn.contains(i) {
+                self.add_to_end_of_board(i, board);
+                tally += self.branch(board);
+                self.remove_from_end_of_board(i, board);
+            }
         }
+
+        self.store(tally);
+        tally
     }
 
-    pub fn solve(&self, hands: &Vec<String>, bd: &String) -> f32 {
-        let mut hs: Vec<Hand> = Vec::new();
+    // only avx512 counts the leading zeros of all the lanes at once, which
+    // the scoring leans on; elsewhere lanes are scored one by one and the
+    // seats are quicker looked at in turn. None there.
+    #[cfg(feature = "simd")]
+    fn showdown_lanes(&self, board: u64) -> Option<Tally> {
+        match SimdLevel::detect() {
+            // SAFETY: detect only reports features the cpu has.
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Avx512 => Some(unsafe { self.showdown_lanes_avx512(board) }),
+            _ => None,
+        }
+    }
 
-        for hand in hands {
-            hs.push(Hand::from_string(hand.to_string()));
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "avx512f,avx512bw,avx512vl,avx512cd,popcnt,lzcnt,bmi1,bmi2")]
+    fn showdown_lanes_avx512(&self, board: u64) -> Tally {
+        self.showdown_lanes_generic(board)
+    }
+
+    // every seat scored at once on a complete board, the hero in the first
+    // lane and the opponents in the ones after. ten seats at most fit in
+    // a pass.
+    #[cfg(feature = "simd")]
+    #[inline(always)]
+    fn showdown_lanes_generic(&self, board: u64) -> Tally {
+        let mut holes: [u64; batch::LANES] = [0; batch::LANES];
+        holes[0] = self.hands[self.spot.hero_pos].hole_b;
+        for (lane, &i) in self.order.iter().enumerate() {
+            holes[lane + 1] = self.hands[i].hole_b;
+        }
+        let cards: u64x16 = u64x16::from_array(holes) | u64x16::splat(board);
+        let scores: u64x16 = batch::scores(cards);
+        let seats: usize = self.order.len() + 1;
+        #[cfg(feature = "verify")]
+        if Verifier::sampled(board) {
+            self.spot
+                .verifier
+                .check_lanes("batch", (1 << seats) - 1, cards, scores);
+        }
+        let hero: u64 = scores[0];
+        let mut tied: u64 = 0;
+        for &score in &scores.as_array()[1..seats] {
+            if score > hero {
+                return Tally::runout(None);
+            }
+            tied += (score == hero) as u64;
         }
+        Tally::runout(Some(tied))
+    }
 
-        let bd: Vec<char> = bd.chars().collect();
-        let mut board: u64 = 0;
-        for chunk in bd.chunks(2) {
-            let c: String = chunk.iter().collect();
-            let card: Card = Card::from_string(c);
-            board |= 1 << card.idx;
+    // picks the widest build of the batch evaluator the cpu we're running
+    // on supports.
+    #[cfg(feature = "simd")]
+    fn river(&self, board: u64) -> Tally {
+        match SimdLevel::detect() {
+            // SAFETY: detect only reports features the cpu has.
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Avx512 => unsafe { self.river_avx512(board) },
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Avx2 => unsafe { self.river_avx2(board) },
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Sse42 => unsafe { self.river_sse42(board) },
+            #[cfg(target_arch = "aarch64")]
+            SimdLevel::Neon => unsafe { self.river_neon(board) },
+            SimdLevel::Baseline => self.river_generic(board),
         }
+    }
 
-        let game = Game::new(0, hs);
-        let mut brancher = Brancher::new(game, board, self.memo.clone());
-        println!("START: {:?}", SystemTime::now());
-        let p: f32 = brancher.compute_equity();
-        println!("END: {:?}", SystemTime::now());
-        p
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "avx512f,avx512bw,avx512vl,avx512cd,popcnt,lzcnt,bmi1,bmi2")]
+    fn river_avx512(&self, board: u64) -> Tally {
+        self.river_generic(board)
     }
-}
 
-fn pop_extra_characters(s: &mut String) {
-    while matches!(s.chars().last(), Some('\n')) {
-        s.pop();
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "avx2,popcnt,lzcnt,bmi1,bmi2")]
+    fn river_avx2(&self, board: u64) -> Tally {
+        self.river_generic(board)
     }
-}
 
-#[allow(dead_code)]
-pub fn parse_input_and_solve() {
-    /*
-    By threading & sharing memo table across threads,
-    we get the following result on a board with 0 cards
-    running on 8 threads:
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "sse4.2,popcnt")]
+    fn river_sse42(&self, board: u64) -> Tally {
+        self.river_generic(board)
+    }
 
-        1 thread (Python): 60 seconds
-        1 thread (Rust): 60 seconds
-        8 threads - Without sharing memo: 60 seconds
-        8 threads - With sharing memo: 16 seconds.
-        8 threads with opt-level 3 + sharing memo: 5 seconds.
-        8 threads w/ opt l3 + sharing memo w/ rwlock: < 3 seconds
-        8 threads w/ opt l3 + memo as dashmap: < 1 seconds
-        The row above + all computations binary - remove heap allocation during Hand.rank call: < 400 ms
-    */
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    #[target_feature(enable = "neon")]
+    fn river_neon(&self, board: u64) -> Tally {
+        self.river_generic(board)
+    }
 
-    let solution: Solver = Solver::new();
+    // tally over every river card of a four card board. each hand is
+    // scored on sixteen rivers per pass instead of one board at a time.
+    #[cfg(feature = "simd")]
+    #[inline(always)]
+    fn river_generic(&self, board: u64) -> Tally {
+        // at least two hands are out, so at most 44 cards are left.
+        let mut rivers: [u64; 48] = [0; 48];
+        let mut n: usize = 0;
+        for i in 0..52 {
+            if !self.drawn.contains(i) {
+                rivers[n] = board | 1 << i;
+                n += 1;
+            }
+        }
 
-    loop {
-        println!("# active players [0 to exit]:");
-        let mut nplayers = String::new();
-        io::stdin()
-            .read_line(&mut nplayers)
-            .expect("Failed to get console input");
-        let nplayers = nplayers.trim().parse::<i32>().expect("Failed to parse int");
-        if nplayers == 0 {
-            break;
+        let mut tally: Tally = Tally {
+            runouts: n as u64,
+            ..Tally::default()
+        };
+        for chunk in rivers[..n].chunks(batch::LANES) {
+            let boards: u64x16 = u64x16::load_or_default(chunk);
+            let hero_hole: u64x16 = u64x16::splat(self.hands[self.spot.hero_pos].hole_b);
+            let hero: u64x16 = batch::scores(boards | hero_hole);
+            #[cfg(feature = "verify")]
+            let sampled: u64 = chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| Verifier::sampled(b))
+                .fold(0, |acc, (lane, _)| acc | 1 << lane);
+            #[cfg(feature = "verify")]
+            self.spot
+                .verifier
+                .check_lanes("batch", sampled, boards | hero_hole, hero);
+            let mut best: u64x16 = u64x16::splat(0);
+            // opponents with the same hand as the hero, in case it's the best.
+            let mut tied: u64x16 = u64x16::splat(0);
+            for &i in self.order.iter() {
+                let hole: u64x16 = u64x16::splat(self.hands[i].hole_b);
+                let score: u64x16 = batch::scores(boards | hole);
+                #[cfg(feature = "verify")]
+                self.spot
+                    .verifier
+                    .check_lanes("batch", sampled, boards | hole, score);
+                best = best.simd_max(score);
+                tied += score
+                    .simd_eq(hero)
+                    .select(u64x16::splat(1), u64x16::splat(0));
+            }
+            // the padding lanes don't count.
+            let valid: u64 = (1 << chunk.len()) - 1;
+            tally.wins += (hero.simd_gt(best).to_bitmask() & valid).count_ones() as u64;
+            let mut splits: u64 = hero.simd_eq(best).to_bitmask() & valid;
+            while splits != 0 {
+                let lane: usize = splits.trailing_zeros() as usize;
+                tally.ties += Tally::SHARE / (tied[lane] + 1);
+                splits &= splits - 1;
+            }
         }
+        tally
+    }
 
-        let mut hs: Vec<String> = Vec::new();
+    // returns the tally of the first cards that were walked and how many
+    // of them there were. with a threshold the workers stop as soon as the
+    // equity is known to be on one side of it, so where they stop depends
+    // on how the threads were scheduled, but which side it's on doesn't.
+    fn branch_parallel(
+        &self,
+        nthreads: usize,
+        threshold: Option<f32>,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> (Tally, usize) {
+        // more than one worker per card would leave some without any.
+        let step: usize = 52 / nthreads.clamp(1, 52);
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        // each worker reports the value of every first card it finishes.
+        let workers: Vec<_> = (0..52)
+            .step_by(step)
+            .map(|s| {
+                let e: usize = (s + step).min(52);
+                let mut local_brancher = self.fork();
+                let stop: Arc<AtomicBool> = stop.clone();
+                move |report: &mut dyn FnMut(Tally)| {
+                    let start: Instant = Instant::now();
+                    let mut board: u64 = local_brancher.spot.board;
+                    for i in s..e {
+                        if stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        if !local_brancher.drawn.contains(i) {
+                            local_brancher.add_to_end_of_board(i, &mut board);
+                            report(local_brancher.branch(&mut board));
+                            local_brancher.remove_from_end_of_board(i, &mut board);
+                        }
+                    }
+                    local_brancher.merge_local();
+                    local_brancher.flush_counters();
+                    local_brancher.clock(start);
+                }
+            })
+            .collect();
 
-        for i in 0..nplayers {
-            if i == 0 {
-                println!("Your starting hand: ");
-            } else {
-                println!("Opponent {} hand: ", i);
+        let ncards: usize = 52 - self.drawn.len();
+        let mut done: usize = 0;
+        let mut total: Tally = Tally::default();
+        run_workers(workers, |tally: Tally| {
+            total += tally;
+            done += 1;
+            progress(EquityEstimate {
+                equity: total.equity(),
+                progress: done as f32 / ncards as f32,
+                std_err: None,
+            });
+            if let Some(t) = threshold {
+                let (lower, upper) = bounds(&total, done, ncards);
+                if lower >= t || upper < t {
+                    stop.store(true, Ordering::Relaxed);
+                }
             }
-            let mut x = String::new();
-            io::stdin()
-                .read_line(&mut x)
-                .expect("Failed to get console input");
-
-            pop_extra_characters(&mut x);
-            hs.push(x);
-        }
-
-        println!("Board: ");
-        let mut bd: String = String::new();
-        io::stdin()
-            .read_line(&mut bd)
-            .expect("Failed to get console input");
-        pop_extra_characters(&mut bd);
-        solution.solve(&hs, &bd);
+        });
+
+        (total, done)
+    }
+
+    // the numbered `samples` of a solve: sample i plainly, or stratified
+    // by the next card dealt the i-th of `cards` next, going round them,
+    // and the rest of the runout at random. antithetic samples are a
+    // runout and its swapped twin, the control variate's a runout played
+    // out by the real hands and by the control's.
+    fn sample(
+        &mut self,
+        deck: &mut Deck,
+        cards: &[usize],
+        samples: std::ops::Range<usize>,
+        sampler: &Sampler,
+        moments: &mut Moments,
+    ) -> Tally {
+        let dealt: u64 = self.spot.board;
+        let need: usize = 5 - dealt.count_ones() as usize;
+        let mut tally: Tally = Tally::default();
+        for i in samples {
+            match sampler {
+                Sampler::Plain => {
+                    let board: u64 = deck.runout(dealt, need);
+                    let showdown: Tally = self.showdown(&board);
+                    moments.add(0, showdown.equity() as f64);
+                    tally += showdown;
+                }
+                Sampler::Stratified => {
+                    let stratum: usize = i % cards.len();
+                    let board: u64 = deck.runout_from(dealt, cards[stratum], need);
+                    let showdown: Tally = self.showdown(&board);
+                    moments.add(stratum, showdown.equity() as f64);
+                    tally += showdown;
+                }
+                Sampler::Antithetic { swap } => {
+                    let board: u64 = deck.runout(dealt, need);
+                    let a: Tally = self.showdown(&board);
+                    let b: Tally = self.showdown(&swapped(board, dealt, swap));
+                    moments.add_pair(a.equity() as f64, b.equity() as f64);
+                    tally += a;
+                    tally += b;
+                }
+                Sampler::ControlVariate { .. } => {
+                    let board: u64 = deck.runout(dealt, need);
+                    let showdown: Tally = self.showdown(&board);
+                    let control: f64 = sampler.control(board, deck.rng());
+                    moments.add_control(showdown.equity() as f64, control);
+                    tally += showdown;
+                }
+            }
+        }
+        tally
+    }
+
+    // the exact walk's last step on random boards one card short of full,
+    // or the full board, timed for at least `burst` or `leaves` leaves,
+    // whichever comes first, and the time per leaf scored.
+    fn leaf_time(&mut self, burst: Duration, leaves: u64, rng: &mut impl Rng) -> Duration {
+        let need: usize = (5 - self.spot.board.count_ones() as usize).saturating_sub(1);
+        let start: Instant = Instant::now();
+        let before: u64 = self.showdowns;
+        while self.showdowns == before
+            || (start.elapsed() < burst && self.showdowns - before < leaves)
+        {
+            let mut deck: Vec<usize> = self.live_deck();
+            let board: u64 = deal(self.spot.board, &mut deck, need, rng);
+            for &card in &deck[..need] {
+                self.drawn.add(card);
+            }
+            self.score_leaves(board);
+            for &card in &deck[..need] {
+                self.drawn.remove(card);
+            }
+        }
+        start.elapsed() / (self.showdowns - before) as u32
+    }
+
+    // every leaf under `board`, scored the way branch scores them but
+    // without the memo.
+    fn score_leaves(&mut self, board: u64) {
+        #[cfg(feature = "simd")]
+        if board.count_ones() == 4 {
+            self.showdowns += self.river(board).runouts;
+            return;
+        }
+        match board.count_ones() {
+            5 => {
+                self.showdown(&board);
+            }
+            _ => {
+                for card in self.live_deck() {
+                    self.showdown(&(board | 1 << card));
+                }
+            }
+        }
+    }
+
+    // the cards not dealt to anyone yet.
+    fn live_deck(&self) -> Vec<usize> {
+        (0..52).filter(|&i| !self.drawn.contains(i)).collect()
+    }
+
+    // how this spot samples with `sampling`, see Sampler::new.
+    fn sampler(&self, sampling: Sampling, table: Option<&PreflopTable>) -> Sampler {
+        let hero: u64 = self.hands[self.spot.hero_pos].hole_b;
+        let opponents: Vec<u64> = self.order.iter().map(|&i| self.hands[i].hole_b).collect();
+        Sampler::new(
+            sampling,
+            table,
+            hero,
+            &opponents,
+            self.spot.board,
+            &self.live_deck(),
+        )
+    }
+
+    // the samples are dealt in fixed batches, each from its own rng seeded
+    // by `seed` and the batch number, and handed out to the workers round
+    // robin. which runouts get dealt doesn't depend on the thread count.
+    // stratified, every card that can come next comes next in as many
+    // samples, rounded up to the same number each, and antithetic samples
+    // come in pairs, rounded up to an even number. until a precision, the
+    // batches go on until the estimate is precise enough or the budget is
+    // spent, so how many get dealt depends on the threads and the clock.
+    fn monte_carlo(
+        &self,
+        mode: SolveMode,
+        nthreads: usize,
+        seed: u64,
+        sampler: &Sampler,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> (Tally, Estimate, Option<StopReason>) {
+        let start: Instant = Instant::now();
+        let deck: Vec<usize> = self.live_deck();
+        let strata: usize = match sampler {
+            Sampler::Stratified => deck.len(),
+            _ => 1,
+        };
+        let (samples, target): (usize, Option<(f32, Duration)>) = match mode {
+            SolveMode::UntilPrecision { half_width, budget } => {
+                (usize::MAX, Some((half_width, budget)))
+            }
+            SolveMode::MonteCarlo { samples } => match sampler {
+                Sampler::Stratified => (samples.div_ceil(strata) * strata, None),
+                Sampler::Antithetic { .. } => (samples.div_ceil(2), None),
+                _ => (samples, None),
+            },
+            SolveMode::Exact | SolveMode::Threshold { .. } => (0, None),
+        };
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        // showdowns per sample.
+        let per: usize = match sampler {
+            Sampler::Antithetic { .. } => 2,
+            _ => 1,
+        };
+        let nbatches: usize = samples.div_ceil(SAMPLE_BATCH);
+        let workers: Vec<_> = (0..nthreads.min(nbatches))
+            .map(|t| {
+                let mut local_brancher = self.fork();
+                // random boards almost never repeat, so don't memoize them.
+                for hand in local_brancher.hands.iter_mut() {
+                    hand.memoize = false;
+                }
+                let deck: Vec<usize> = deck.clone();
+                let sampler: Sampler = sampler.clone();
+                let stop: Arc<AtomicBool> = stop.clone();
+                move |report: &mut dyn FnMut((usize, Tally, Moments))| {
+                    let start: Instant = Instant::now();
+                    for b in (t..nbatches).step_by(nthreads) {
+                        if stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let n: usize = (samples - b * SAMPLE_BATCH).min(SAMPLE_BATCH);
+                        // every batch shuffles the deck from the same order.
+                        let mut batch: Deck = Deck::batch(&deck, seed, b);
+                        let mut moments: Moments = Moments::new(strata);
+                        let from: usize = b * SAMPLE_BATCH;
+                        let tally: Tally = local_brancher.sample(
+                            &mut batch,
+                            &deck,
+                            from..from + n,
+                            &sampler,
+                            &mut moments,
+                        );
+                        report((b, tally, moments));
+                    }
+                    local_brancher.flush_counters();
+                    local_brancher.clock(start);
+                }
+            })
+            .collect();
+
+        let mut total: Tally = Tally::default();
+        let mut moments: Moments = Moments::new(strata);
+        // float sums depend on the order they're added in, so the answer
+        // adds the batches up in batch order, not as they come in. batches
+        // wait in `pending` until the ones before them are in.
+        let mut ordered: Moments = Moments::new(strata);
+        let mut pending: BTreeMap<usize, Moments> = BTreeMap::new();
+        let mut next: usize = 0;
+        let mut reason: Option<StopReason> = None;
+        run_workers(workers, |(b, tally, m): (usize, Tally, Moments)| {
+            total += tally;
+            moments.merge(&m);
+            pending.insert(b, m);
+            while let Some(m) = pending.remove(&next) {
+                ordered.merge(&m);
+                next += 1;
+            }
+            let estimate: Estimate = moments.estimate(sampler);
+            let half_width: f32 = 1.96 * estimate.std_err as f32;
+            let done: f32 = match target {
+                // the interval narrows with the square root of the samples.
+                Some((target, budget)) => {
+                    let elapsed: Duration = start.elapsed();
+                    if reason.is_none() && half_width <= target {
+                        reason = Some(StopReason::Precision);
+                    } else if reason.is_none() && elapsed >= budget {
+                        reason = Some(StopReason::Budget);
+                    }
+                    if reason.is_some() {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    let precision: f32 = match half_width > 0. {
+                        true => (target / half_width).powi(2),
+                        false => 1.,
+                    };
+                    precision
+                        .max(elapsed.as_secs_f32() / budget.as_secs_f32())
+                        .min(1.)
+                }
+                None => total.runouts as f32 / (samples * per) as f32,
+            };
+            progress(EquityEstimate {
+                equity: estimate.equity as f32,
+                progress: done,
+                std_err: Some(estimate.std_err as f32),
+            });
+        });
+        // stopped early, some batches never came.
+        for m in pending.values() {
+            ordered.merge(m);
+        }
+        (total, ordered.estimate(sampler), reason)
+    }
+
+    fn add_to_end_of_board(&mut self, card_idx: usize, board: &mut u64) {
+        self.drawn.add(card_idx);
+        *board |= 1 << card_idx;
+    }
+
+    fn remove_from_end_of_board(&mut self, card_idx: usize, board: &mut u64) {
+        self.drawn.remove(card_idx);
+        *board -= 1 << card_idx;
+    }
+
+    fn compute_equity(
+        &mut self,
+        mode: SolveMode,
+        nthreads: usize,
+        seed: u64,
+        sampler: &Sampler,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> Solution {
+        let start: Instant = Instant::now();
+        if mode.is_sampled() {
+            debug!("Sampling runouts ({:?}) on {} threads.", mode, nthreads);
+            // estimates never go in the memo, it only holds exact values.
+            let (tally, estimate, stop) = self.monte_carlo(mode, nthreads, seed, sampler, progress);
+            return Solution {
+                equity: estimate.equity as f32,
+                tally,
+                bounds: None,
+                stats: SolveStats {
+                    stop,
+                    std_err: Some(estimate.std_err as f32),
+                    variance_reduction: estimate.reduction,
+                    ..self.stats(mode, nthreads, start, false)
+                },
+            };
+        }
+        let threshold: Option<f32> = match mode {
+            SolveMode::Threshold { threshold } => Some(threshold),
+            _ => None,
+        };
+
+        /*
+        Run on one thread if 4 cards are
+        already on the board to avoid overhead
+        of copying and moving onto threads.
+        */
+        let cached: Option<Tally> = self.lookup();
+        self.flush_counters();
+        if let Some(tally) = cached {
+            let p: f32 = tally.equity();
+            debug!("Equity {} found in the memo.", p);
+            progress(EquityEstimate {
+                equity: p,
+                progress: 1.,
+                std_err: None,
+            });
+            return Solution {
+                equity: p,
+                tally,
+                bounds: threshold.map(|_| (p, p)),
+                stats: self.stats(mode, 0, start, true),
+            };
+        }
+
+        let tally: Tally;
+        let threads: usize;
+        let mut equity_bounds: Option<(f32, f32)> = threshold.map(|_| (0., 1.));
+
+        if self.spot.board.count_ones() >= 4 {
+            let mut board: u64 = self.spot.board;
+            tally = self.branch(&mut board);
+            self.flush_counters();
+            self.clock(start);
+            threads = 1;
+            let p: f32 = tally.equity();
+            equity_bounds = equity_bounds.map(|_| (p, p));
+            progress(EquityEstimate {
+                equity: p,
+                progress: 1.,
+                std_err: None,
+            });
+        } else {
+            debug!("Running on {} threads.", nthreads);
+            let done: usize;
+            (tally, done) = self.branch_parallel(nthreads, threshold, progress);
+            let ncards: usize = 52 - self.drawn.len();
+            threads = nthreads;
+            if done == ncards {
+                self.spot.memo.insert(self.drawn.s, tally);
+            } else {
+                debug!("Stopped after {} of {} cards.", done, ncards);
+            }
+            equity_bounds = equity_bounds.map(|_| bounds(&tally, done, ncards));
+        }
+        Solution {
+            equity: tally.equity(),
+            tally,
+            bounds: equity_bounds,
+            stats: self.stats(mode, threads, start, false),
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl Brancher {
+    // solves on the gpu when there are enough runouts for it to pay off.
+    // None means the cpu should take it from here.
+    fn compute_equity_gpu(
+        &mut self,
+        gpu: &GpuEvaluator,
+        mode: SolveMode,
+        seed: u64,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> Option<Solution> {
+        let start: Instant = Instant::now();
+        // past the flop the memoized tree is already quick, and cached
+        // results are reported by the cpu path.
+        let exact: bool = match mode {
+            SolveMode::Exact => true,
+            SolveMode::MonteCarlo { .. } => false,
+            // stopping early needs the cpu tree walk.
+            SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => return None,
+        };
+        if exact
+            && (self.spot.board.count_ones() >= 3 || self.spot.memo.get(&self.drawn.s).is_some())
+        {
+            return None;
+        }
+
+        let need: usize = 5 - self.spot.board.count_ones() as usize;
+        let deck: Vec<usize> = (0..52).filter(|&i| !self.drawn.contains(i)).collect();
+        let mut runouts: Vec<u64> = Vec::new();
+        match mode {
+            // the same runouts the cpu would deal with this seed.
+            SolveMode::MonteCarlo { samples } => {
+                runouts = sample_runouts(&deck, self.spot.board, need, samples, seed)
+            }
+            _ => all_runouts(&deck, need, self.spot.board, &mut runouts),
+        }
+        if runouts.is_empty() {
+            return None;
+        }
+
+        let hands: Vec<u64> = self.hands.iter().map(|h| h.hole_b).collect();
+        debug!("Evaluating {} runouts on the gpu.", runouts.len());
+        let Some((wins, ties)) = gpu.wins(&hands, self.spot.hero_pos, &runouts) else {
+            warn!("The gpu solve failed, falling back to the cpu.");
+            return None;
+        };
+
+        let n: usize = runouts.len();
+        let mut tally: Tally = Tally {
+            wins,
+            ties,
+            runouts: n as u64,
+        };
+        let p: f32 = tally.equity();
+        let std_err: Option<f32> = if exact {
+            tally = tally_permutations(tally, need);
+            self.spot.memo.insert(self.drawn.s, tally);
+            None
+        } else {
+            Some((p * (1. - p) / n as f32).sqrt())
+        };
+        progress(EquityEstimate {
+            equity: p,
+            progress: 1.,
+            std_err,
+        });
+        self.spot
+            .solve_counters
+            .leaves
+            .fetch_add(n as u64, Ordering::Relaxed);
+        Some(Solution {
+            equity: p,
+            tally,
+            bounds: None,
+            // no cpu threads were used.
+            stats: self.stats(mode, 0, start, false),
+        })
+    }
+}
+
+// runouts dealt from one rng in a monte carlo solve.
+pub(crate) const SAMPLE_BATCH: usize = 10_000;
+
+pub(crate) fn batch_rng(seed: u64, batch: usize) -> StdRng {
+    StdRng::seed_from_u64(seed.wrapping_add(batch as u64))
+}
+
+// finishes `board` with `need` random cards from `deck`. partial
+// fisher-yates: the first `need` cards of the deck are the runout.
+pub(crate) fn deal(board: u64, deck: &mut [usize], need: usize, rng: &mut impl Rng) -> u64 {
+    let mut board: u64 = board;
+    for j in 0..need {
+        let r: usize = rng.gen_range(j..deck.len());
+        deck.swap(j, r);
+        board |= 1 << deck[j];
+    }
+    board
+}
+
+// every way to finish `board` with `need` cards from `deck`, each once.
+pub(crate) fn all_runouts(deck: &[usize], need: usize, board: u64, out: &mut Vec<u64>) {
+    out.extend(Combinations::new(deck.to_vec(), need).map(|cards| board | cards));
+}
+
+// what the equity can still be after `done` of `ncards` equally likely
+// subtrees came to `tally`: the rest are worth between 0 and 1 each.
+fn bounds(tally: &Tally, done: usize, ncards: usize) -> (f32, f32) {
+    if done == 0 {
+        return (0., 1.);
+    }
+    // every subtree has as many runouts.
+    let total: f64 = tally.runouts as f64 / done as f64 * ncards as f64;
+    let won: f64 = tally.wins as f64 + tally.ties as f64 / Tally::SHARE as f64;
+    let lower: f64 = won / total;
+    let upper: f64 = (won + total - tally.runouts as f64) / total;
+    (lower as f32, upper as f32)
+}
+
+// the tree walk deals the runout one card at a time, so it sees each
+// unordered runout of `need` cards need! times.
+fn tally_permutations(tally: Tally, need: usize) -> Tally {
+    let orders: u64 = (1..=need as u64).product();
+    Tally {
+        wins: tally.wins * orders,
+        ties: tally.ties * orders,
+        runouts: tally.runouts * orders,
+    }
+}
+
+// use up all the cores we got
+pub(crate) fn default_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    return num_cpus::get_physical();
+    #[cfg(not(feature = "parallel"))]
+    return 1;
+}
+
+// runs the workers on the shared worker threads, passing what they
+// report to `on_report` on the calling thread as it comes in. a worker
+// that panics panics the caller once the others are done.
+#[cfg(feature = "parallel")]
+pub(crate) fn run_workers<T, W>(workers: Vec<W>, mut on_report: impl FnMut(T))
+where
+    T: Send + 'static,
+    W: FnOnce(&mut dyn FnMut(T)) + Send + 'static,
+{
+    // workers starting workers would wait on threads they hold.
+    if WorkerPool::on_worker() {
+        for work in workers {
+            work(&mut on_report);
+        }
+        return;
+    }
+
+    let pool: &WorkerPool = WorkerPool::global();
+    let (tx, rx) = mpsc::channel::<T>();
+    let (done_tx, done) = mpsc::channel::<thread::Result<()>>();
+    for work in workers {
+        let tx = tx.clone();
+        // the receiver outlives the workers.
+        pool.execute(
+            move || work(&mut |x: T| tx.send(x).unwrap()),
+            done_tx.clone(),
+        );
+    }
+    drop((tx, done_tx));
+
+    for x in rx {
+        on_report(x);
+    }
+    for result in done {
+        if let Err(e) = result {
+            panic::resume_unwind(e);
+        }
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn run_workers<T, W>(workers: Vec<W>, mut on_report: impl FnMut(T))
+where
+    W: FnOnce(&mut dyn FnMut(T)),
+{
+    for work in workers {
+        work(&mut on_report);
+    }
+}
+
+// how workers share memoized subtrees during a parallel solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoMode {
+    // every worker reads and writes the solver's memo as it goes, so
+    // permutations of a runout are only solved once across all workers.
+    #[default]
+    Shared,
+    // workers write to a private map that is merged into the memo when they
+    // finish. no lock traffic while solving, but a runout can be solved by
+    // each of the up to five workers owning one of its cards. ~10% faster on
+    // one core, which doesn't make up for that on many.
+    ThreadLocal,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    // only counted per solve.
+    leaves: AtomicU64,
+    // nanoseconds the workers spent working, per solve too.
+    busy: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    // rough size of the cached entries, not counting the maps' own overhead.
+    pub bytes: usize,
+}
+
+// locks per matchup memo unless the options say otherwise, plenty for the
+// core counts desktops have.
+const DEFAULT_SHARDS: usize = 64;
+
+// clones share the same cache.
+#[derive(Clone)]
+pub struct Solver {
+    // one memo table per matchup, since the memo is keyed by the drawn cards
+    // alone and can't tell which of them belong to the hero.
+    memos: Arc<Memo<Vec<u64>, EquityMemo>>,
+    // every player's tally of whole spots, keyed by the board and the hands
+    // as a set, so any of them can be the hero. see Solver::equities.
+    showdowns: Arc<Memo<Vec<u64>, Vec<Tally>>>,
+    counters: Arc<Counters>,
+    // entries to reserve up front in each matchup's table.
+    capacity: usize,
+    // locks each matchup's table is split over, a power of two.
+    shards: usize,
+    // keep results around for later solves.
+    persist: bool,
+    // memoize hand ranks per player on top of the equity memo.
+    hand_memo: bool,
+    memo_mode: MemoMode,
+    // worker threads, all physical cores when None.
+    threads: Option<usize>,
+    // monte carlo seed, a fresh one every solve when None.
+    seed: Option<u64>,
+    // how monte carlo solves deal their runouts.
+    sampling: Sampling,
+    // the control variate's means, see Sampling::ControlVariate.
+    preflop: Option<Arc<PreflopTable>>,
+    // how try_solve and solve solve.
+    mode: SolveMode,
+    variant: Variant,
+    // where solves asked for through try_solve and solve_cards are
+    // recorded, if anywhere.
+    store: Option<Arc<ResultStore>>,
+    #[cfg(feature = "gpu")]
+    gpu: Option<Arc<GpuEvaluator>>,
+    #[cfg(feature = "verify")]
+    verifier: Arc<Verifier>,
+}
+
+// the poker game the hands are dealt in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    // two hole cards and five on the board, the only game evaluated so far.
+    #[default]
+    Holdem,
+}
+
+// everything a solver can be set up with, e.g. Solver::builder()
+// .threads(12).mode(SolveMode::MonteCarlo { samples: 2_000_000 }).seed(42)
+// .build(). whatever isn't set is as in Solver::new.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverOptions {
+    pub threads: Option<usize>,
+    pub mode: SolveMode,
+    pub variant: Variant,
+    pub seed: Option<u64>,
+    // memo entries to reserve per matchup.
+    pub capacity: usize,
+    // locks each matchup's memo is split over. more let many threads write
+    // without waiting on each other, fewer keep small solves' maps small.
+    // rounded up to a power of two, at least 2.
+    pub shards: usize,
+    // keep results around for later solves.
+    pub cache: bool,
+    pub hand_memo: bool,
+    pub memo_mode: MemoMode,
+    // how monte carlo solves deal their runouts, see Sampling.
+    pub sampling: Sampling,
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        SolverOptions {
+            threads: None,
+            mode: SolveMode::Exact,
+            variant: Variant::default(),
+            seed: None,
+            capacity: 0,
+            shards: DEFAULT_SHARDS,
+            cache: true,
+            hand_memo: false,
+            memo_mode: MemoMode::default(),
+            sampling: Sampling::Plain,
+        }
+    }
+}
+
+impl SolverOptions {
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    // the mode try_solve and solve use, the other entry points take theirs.
+    pub fn mode(mut self, mode: SolveMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn shards(mut self, shards: usize) -> Self {
+        self.shards = shards;
+        self
+    }
+
+    pub fn cache(mut self, enabled: bool) -> Self {
+        self.cache = enabled;
+        self
+    }
+
+    pub fn hand_memo(mut self, enabled: bool) -> Self {
+        self.hand_memo = enabled;
+        self
+    }
+
+    pub fn memo_mode(mut self, mode: MemoMode) -> Self {
+        self.memo_mode = mode;
+        self
+    }
+
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn build(self) -> Solver {
+        Solver {
+            capacity: self.capacity,
+            shards: self.shards.max(2).next_power_of_two(),
+            persist: self.cache,
+            hand_memo: self.hand_memo,
+            memo_mode: self.memo_mode,
+            threads: self.threads,
+            seed: self.seed,
+            sampling: self.sampling,
+            mode: self.mode,
+            variant: self.variant,
+            ..Solver::new()
+        }
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Solver {
+            memos: Arc::new(Memo::new()),
+            showdowns: Arc::new(Memo::new()),
+            counters: Arc::new(Counters::default()),
+            capacity: 0,
+            shards: DEFAULT_SHARDS,
+            persist: true,
+            hand_memo: false,
+            memo_mode: MemoMode::default(),
+            threads: None,
+            seed: None,
+            sampling: Sampling::Plain,
+            preflop: None,
+            mode: SolveMode::Exact,
+            variant: Variant::default(),
+            store: None,
+            #[cfg(feature = "gpu")]
+            gpu: None,
+            #[cfg(feature = "verify")]
+            verifier: Arc::new(Verifier::default()),
+        }
+    }
+
+    pub fn builder() -> SolverOptions {
+        SolverOptions::default()
+    }
+
+    // the options this solver was set up with.
+    pub fn options(&self) -> SolverOptions {
+        SolverOptions {
+            threads: self.threads,
+            mode: self.mode,
+            variant: self.variant,
+            seed: self.seed,
+            capacity: self.capacity,
+            shards: self.shards,
+            cache: self.persist,
+            hand_memo: self.hand_memo,
+            memo_mode: self.memo_mode,
+            sampling: self.sampling,
+        }
+    }
+
+    // hands big solves to the gpu if there is one, otherwise this is the
+    // same as Solver::new.
+    #[cfg(feature = "gpu")]
+    pub fn with_gpu() -> Self {
+        let gpu: Option<GpuEvaluator> = GpuEvaluator::new();
+        if gpu.is_none() {
+            warn!("No usable gpu adapter, solving on the cpu.");
+        }
+        Solver {
+            gpu: gpu.map(Arc::new),
+            ..Self::new()
+        }
+    }
+
+    // reserves room for `capacity` entries per matchup, e.g. ~2.6m for a
+    // heads up preflop solve, so big solves don't pay for rehashing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Solver {
+            capacity,
+            ..Self::new()
+        }
+    }
+
+    // records every spot solved through try_solve (and solve, solve_cards
+    // and incremental solves) in `store`. the solves a range or a chart is built from
+    // aren't recorded, only the ones asked for.
+    pub fn with_store(self, store: Arc<ResultStore>) -> Self {
+        Solver {
+            store: Some(store),
+            ..self
+        }
+    }
+
+    // the table Sampling::ControlVariate takes its means from.
+    pub fn with_preflop_table(self, table: Arc<PreflopTable>) -> Self {
+        Solver {
+            preflop: Some(table),
+            ..self
+        }
+    }
+
+    // every solve starts from an empty memo that is dropped when it's done.
+    // the memo still dedupes runouts within a solve.
+    pub fn without_cache() -> Self {
+        Solver {
+            persist: false,
+            ..Self::new()
+        }
+    }
+
+    // also memoize each hand's rank by its seven cards. off by default: the
+    // equity memo already dedupes runouts, so each seven card set is rarely
+    // ranked twice and the lookups cost more than they save (~1.7s vs ~1.2s
+    // heads up preflop).
+    pub fn with_hand_memo(mut self, enabled: bool) -> Self {
+        self.hand_memo = enabled;
+        self
+    }
+
+    pub fn with_memo_mode(mut self, mode: MemoMode) -> Self {
+        self.memo_mode = mode;
+        self
+    }
+
+    // exact and monte carlo solves come out the same at any thread count,
+    // so this only changes how long they take.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    // makes monte carlo solves repeatable: the same seed deals the same
+    // runouts, on any number of threads or on the gpu.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn nthreads(&self) -> usize {
+        self.threads.unwrap_or_else(default_threads)
+    }
+
+    fn memo_for(&self, game: &Game) -> EquityMemo {
+        let new_memo = || {
+            Arc::new(Memo::with_capacity_and_shard_amount(
+                self.capacity,
+                self.shards,
+            ))
+        };
+        if !self.persist {
+            return new_memo();
+        }
+        self.memos.get_or_insert_with(game.key(), new_memo)
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        let entries: usize = self.memos.values().iter().map(|m| m.len()).sum();
+        let players: usize = self.showdowns.values().iter().map(Vec::len).sum();
+        CacheStats {
+            entries: entries + self.showdowns.len(),
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            bytes: entries * std::mem::size_of::<(u64, Tally)>()
+                + players * std::mem::size_of::<Tally>(),
+        }
+    }
+
+    // what the reference evaluator made of the runouts it checked in this
+    // solver's solves so far. gpu solves aren't checked, the shader only
+    // hands back totals.
+    #[cfg(feature = "verify")]
+    pub fn verify_report(&self) -> SelfTest {
+        self.verifier.report()
+    }
+
+    // drops every cached result and resets the hit/miss counters.
+    pub fn clear_cache(&self) {
+        self.memos.clear();
+        self.showdowns.clear();
+        self.counters.hits.store(0, Ordering::Relaxed);
+        self.counters.misses.store(0, Ordering::Relaxed);
+    }
+
+    // panics on hands or a board that can't be read, see try_solve.
+    pub fn solve(&self, hands: &[impl AsRef<str>], bd: &str) -> f32 {
+        match self.try_solve(hands, bd) {
+            Ok(solution) => solution.equity,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    // in the solver's mode, exact unless it was built with another.
+    pub fn try_solve(&self, hands: &[impl AsRef<str>], bd: &str) -> Result<Solution, Error> {
+        self.try_solve_with_progress(hands, bd, self.mode, |_| {})
+    }
+
+    // like try_solve, calling `progress` with refined estimates as the solve goes.
+    pub fn try_solve_with_progress(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+        mode: SolveMode,
+        mut progress: impl FnMut(EquityEstimate),
+    ) -> Result<Solution, Error> {
+        let (game, board) = parse_game(hands, bd)?;
+        let solution: Solution = self.solve_game(game, board, mode, &mut progress);
+        if let Some(store) = &self.store {
+            store.record(
+                hands,
+                bd,
+                &SolverOptions {
+                    mode,
+                    ..self.options()
+                },
+                solution.equity,
+            );
+        }
+        debug!(
+            "Solved {:?} on '{}' in {:?}: equity {}.",
+            hands.iter().map(AsRef::as_ref).collect::<Vec<&str>>(),
+            bd,
+            solution.stats.elapsed,
+            solution.equity
+        );
+        Ok(solution)
+    }
+
+    // like try_solve with cards that were already read, so only a card
+    // dealt twice or the number of players can fail.
+    pub fn solve_cards(
+        &self,
+        hands: &[HoleCards],
+        board: &Board,
+        mode: SolveMode,
+    ) -> Result<Solution, Error> {
+        if !(2..=10).contains(&hands.len()) {
+            return Err(Error::PlayerCount(hands.len()));
+        }
+        let mut seen: u64 = board.mask;
+        let mut hs: Vec<Hand> = Vec::with_capacity(hands.len());
+        for hand in hands {
+            check_free(seen, hand.mask)?;
+            seen |= hand.mask;
+            let low: usize = hand.mask.trailing_zeros() as usize;
+            let high: usize = 63 - hand.mask.leading_zeros() as usize;
+            hs.push(Hand::new((Card::from_idx(high), Card::from_idx(low))));
+        }
+        let solution: Solution = self.solve_game(Game::new(0, hs), board.mask, mode, &mut |_| {});
+        if let Some(store) = &self.store {
+            let hands: Vec<String> = hands.iter().map(HoleCards::to_string).collect();
+            store.record(
+                &hands,
+                &board.to_string(),
+                &SolverOptions {
+                    mode,
+                    ..self.options()
+                },
+                solution.equity,
+            );
+        }
+        Ok(solution)
+    }
+
+    // every scenario solved in the solver's mode, in the order given. the
+    // worker threads each take whole scenarios, the next one left as they
+    // finish, and solve them on one thread, which scales better than
+    // splitting every solve when there are many of them. the scenarios
+    // share the solver's cache, and monte carlo ones its seed.
+    pub fn solve_batch(&self, scenarios: &[Scenario]) -> Vec<Result<Solution, Error>> {
+        self.solve_batch_in(scenarios, self.mode)
+    }
+
+    pub(crate) fn solve_batch_in(
+        &self,
+        scenarios: &[Scenario],
+        mode: SolveMode,
+    ) -> Vec<Result<Solution, Error>> {
+        let nthreads: usize = self.nthreads().min(scenarios.len());
+        let scenarios: Arc<Vec<Scenario>> = Arc::new(scenarios.to_vec());
+        let next: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..nthreads)
+            .map(|_| {
+                let solver: Solver = self.clone().with_threads(1);
+                let (scenarios, next) = (scenarios.clone(), next.clone());
+                move |report: &mut dyn FnMut((usize, Result<Solution, Error>))| loop {
+                    let i: usize = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(s) = scenarios.get(i) else {
+                        break;
+                    };
+                    report((
+                        i,
+                        solver.try_solve_with_progress(&s.hands, &s.board, mode, |_| {}),
+                    ));
+                }
+            })
+            .collect();
+        let mut solutions: Vec<Option<Result<Solution, Error>>> = vec![None; scenarios.len()];
+        run_workers(
+            workers,
+            |(i, solution): (usize, Result<Solution, Error>)| solutions[i] = Some(solution),
+        );
+        solutions.into_iter().flatten().collect()
+    }
+
+    // the equity of each of `heroes` against the same `villains`, in one
+    // pass over the runouts in the solver's mode: every hero is scored at
+    // every runout it doesn't hold a card of, which is the same as solving
+    // it alone. much quicker than a solve per hero for building ranges. the
+    // heroes may share cards with each other, not with the villains or board.
+    pub fn solve_many(
+        &self,
+        heroes: &[HoleCards],
+        villains: &[HoleCards],
+        board: &Board,
+    ) -> Result<Vec<f32>, Error> {
+        if !(1..=9).contains(&villains.len()) {
+            return Err(Error::PlayerCount(villains.len() + 1));
+        }
+        let mut dead: u64 = board.mask;
+        for hand in villains {
+            check_free(dead, hand.mask)?;
+            dead |= hand.mask;
+        }
+        for hero in heroes {
+            check_free(dead, hero.mask)?;
+        }
+
+        let need: usize = 5 - board.mask.count_ones() as usize;
+        let deck: Vec<usize> = (0..52).filter(|&i| dead & 1 << i == 0).collect();
+        let mut runouts: Vec<u64> = Vec::new();
+        match self.mode {
+            // dealt like a monte carlo solve with the same seed.
+            SolveMode::MonteCarlo { samples } => {
+                let seed: u64 = self.seed.unwrap_or_else(rand::random);
+                runouts = sample_runouts(&deck, board.mask, need, samples, seed);
+            }
+            SolveMode::Exact | SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => {
+                all_runouts(&deck, need, board.mask, &mut runouts)
+            }
+        }
+
+        let nthreads: usize = self.nthreads();
+        let runouts: Arc<Vec<u64>> = Arc::new(runouts);
+        let heroes: Arc<Vec<u64>> = Arc::new(heroes.iter().map(|h| h.mask).collect());
+        let villains: Arc<Vec<u64>> = Arc::new(villains.iter().map(|h| h.mask).collect());
+        let workers: Vec<_> = (0..nthreads)
+            .map(|t| {
+                let (runouts, heroes, villains) =
+                    (runouts.clone(), heroes.clone(), villains.clone());
+                move |report: &mut dyn FnMut(Vec<Tally>)| {
+                    let mut tallies: Vec<Tally> = vec![Tally::default(); heroes.len()];
+                    let mut scores: Vec<u64> = vec![0; villains.len()];
+                    for &runout in runouts.iter().skip(t).step_by(nthreads) {
+                        // the villains are scored once for all the heroes.
+                        for (score, &hole) in scores.iter_mut().zip(villains.iter()) {
+                            *score = ladder_score(hole | runout);
+                        }
+                        let best: u64 = *scores.iter().max().unwrap();
+                        let tied: u64 = scores.iter().filter(|&&s| s == best).count() as u64;
+                        for (tally, &hero) in tallies.iter_mut().zip(heroes.iter()) {
+                            if hero & runout != 0 {
+                                continue;
+                            }
+                            tally.runouts += 1;
+                            let score: u64 = ladder_score(hero | runout);
+                            if score > best {
+                                tally.wins += 1;
+                            } else if score == best {
+                                tally.ties += Tally::SHARE / (tied + 1);
+                            }
+                        }
+                    }
+                    report(tallies);
+                }
+            })
+            .collect();
+        let mut tallies: Vec<Tally> = vec![Tally::default(); heroes.len()];
+        run_workers(workers, |worker: Vec<Tally>| {
+            for (total, tally) in tallies.iter_mut().zip(worker) {
+                *total += tally;
+            }
+        });
+        Ok(tallies.iter().map(Tally::equity).collect())
+    }
+
+    // the hero's equity against every hand of `villain` that can still be
+    // dealt, weighted by how often it's played. each combo is a solve of its
+    // own, so exact preflop solves of wide ranges take a while, monte carlo
+    // keeps them quick.
+    pub fn equity_vs_range(
+        &self,
+        hero: &str,
+        villain: &Range,
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<f32, Error> {
+        Ok(self
+            .equity_vs_range_detailed(hero, villain, bd, mode)?
+            .equity)
+    }
+
+    // equity_vs_range, along with the combos and weight the equity was
+    // averaged over once the hero's cards and the board took theirs out.
+    pub fn equity_vs_range_detailed(
+        &self,
+        hero: &str,
+        villain: &Range,
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<RangeEquity, Error> {
+        let dead: u64 = parse_board(bd)? | hand_mask(hero)?;
+        let mut total: f64 = 0.;
+        let mut weight: f64 = 0.;
+        let mut combos: usize = 0;
+        for combo in villain.live(dead).filter(|c| c.weight > 0.) {
+            let hands: Vec<String> = vec![hero.to_string(), combo.hand.clone()];
+            let solution: Solution = self.try_solve_with_progress(&hands, bd, mode, |_| {})?;
+            total += solution.equity as f64 * combo.weight as f64;
+            weight += combo.weight as f64;
+            combos += 1;
+        }
+        if weight == 0. {
+            return Err(Error::EmptyRange);
+        }
+        let range_weight: f64 = villain.combos().iter().map(|c| c.weight as f64).sum();
+        Ok(RangeEquity {
+            equity: (total / weight) as f32,
+            combos,
+            weight: weight as f32,
+            range_weight: range_weight as f32,
+        })
+    }
+
+    // the equity of every combo of the hero's range against the villain's
+    // range on `bd`, in one pass over the runouts in the solver's mode. each
+    // combo is weighed against the villain combos it shares no card with.
+    pub fn range_matrix(
+        &self,
+        hero: &Range,
+        villain: &Range,
+        bd: &str,
+    ) -> Result<RangeMatrix, Error> {
+        let board: u64 = parse_board(bd)?;
+        let weights = |range: &Range| -> Vec<(u64, f32)> {
+            let live = range.combos().iter().filter(|c| c.weight > 0.);
+            live.map(|c| (c.cards(), c.weight)).collect()
+        };
+        let (heroes, villains) = (weights(hero), weights(villain));
+        if villains.iter().all(|v| v.0 & board != 0) {
+            return Err(Error::EmptyRange);
+        }
+
+        let need: usize = 5 - board.count_ones() as usize;
+        let deck: Vec<usize> = (0..52).filter(|&i| board & 1 << i == 0).collect();
+        let mut runouts: Vec<u64> = Vec::new();
+        match self.mode {
+            // dealt like a monte carlo solve with the same seed.
+            SolveMode::MonteCarlo { samples } => {
+                let seed: u64 = self.seed.unwrap_or_else(rand::random);
+                runouts = sample_runouts(&deck, board, need, samples, seed);
+            }
+            SolveMode::Exact | SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => {
+                all_runouts(&deck, need, board, &mut runouts)
+            }
+        }
+        let scores: Vec<Score> = score_runouts(heroes, villains, runouts, self.nthreads());
+        Ok(RangeMatrix::new(hero, villain, bd, &scores))
+    }
+
+    // every hand's ehs and ehs2 on `options.boards` random boards, over all
+    // their runouts or the mode's sample of them, bucketed for a card
+    // abstraction. exact flops take a few tenths of a second each on one
+    // core, so a few hundred samples per board are the usual choice.
+    pub fn equity_buckets(&self, options: BucketOptions) -> Result<EquityBuckets, Error> {
+        EquityBuckets::new(options, self.mode, self.nthreads())
+    }
+
+    // every player's equity split by the texture of the board once it has
+    // `cards` cards, 3 for the flop to 5 for the river: monotone or not,
+    // paired, a flush or straight possible, and so on. in the solver's mode,
+    // so preflop the exact report goes over all 1.7 million runouts.
+    pub fn texture_report(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+        cards: usize,
+    ) -> Result<TextureReport, Error> {
+        let (_, board) = parse_game(hands, bd)?;
+        if !(3..=5).contains(&cards) || board.count_ones() as usize > cards {
+            return Err(Error::InvalidBoard(bd.to_string()));
+        }
+        let holes: Vec<u64> = hands
+            .iter()
+            .map(|h| hand_mask(h.as_ref().trim()))
+            .collect::<Result<_, _>>()?;
+        let dead: u64 = holes.iter().fold(board, |acc, h| acc | h);
+
+        let need: usize = 5 - board.count_ones() as usize;
+        let deck: Vec<usize> = (0..52).filter(|&i| dead & 1 << i == 0).collect();
+        let mut runouts: Vec<u64> = Vec::new();
+        match self.mode {
+            // dealt like a monte carlo solve with the same seed.
+            SolveMode::MonteCarlo { samples } => {
+                let seed: u64 = self.seed.unwrap_or_else(rand::random);
+                runouts = sample_runouts(&deck, board, need, samples, seed);
+            }
+            SolveMode::Exact | SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => {
+                all_runouts(&deck, need, board, &mut runouts)
+            }
+        }
+        let hands: Vec<String> = hands
+            .iter()
+            .map(|h| h.as_ref().trim().to_string())
+            .collect();
+        Ok(TextureReport::new(
+            hands,
+            bd,
+            board,
+            holes,
+            runouts,
+            cards,
+            self.nthreads(),
+        ))
+    }
+
+    // how often the villain's range continues against a bet of `bet` into
+    // `pot`, next to how often the bet has to work to break even. a combo
+    // continues when its equity against the hero's hand is at least
+    // `threshold`, by default the villain's pot odds.
+    pub fn bluff_check(
+        &self,
+        hero: &str,
+        villain: &Range,
+        bd: &str,
+        pot: f32,
+        bet: f32,
+        threshold: Option<f32>,
+    ) -> Result<BluffCheck, Error> {
+        let threshold: f32 = threshold.unwrap_or(bet / (pot + 2. * bet));
+        let dead: u64 = parse_board(bd)? | hand_mask(hero)?;
+        let mut continues: f64 = 0.;
+        let mut weight: f64 = 0.;
+        for combo in villain.live(dead).filter(|c| c.weight > 0.) {
+            // solved from the villain's side, stopping once it's clear which
+            // side of the threshold the combo is on.
+            let hands: Vec<String> = vec![combo.hand.clone(), hero.to_string()];
+            let mode: SolveMode = SolveMode::Threshold { threshold };
+            let solution: Solution = self.try_solve_with_progress(&hands, bd, mode, |_| {})?;
+            let (lower, _) = solution
+                .bounds
+                .unwrap_or((solution.equity, solution.equity));
+            if lower >= threshold {
+                continues += combo.weight as f64;
+            }
+            weight += combo.weight as f64;
+        }
+        if weight == 0. {
+            return Err(Error::EmptyRange);
+        }
+        Ok(BluffCheck {
+            break_even: break_even_fold(pot, bet),
+            threshold,
+            continues: (continues / weight) as f32,
+        })
+    }
+
+    // the hero's equity against `opponents` random hands, without making
+    // up their cards. heads up an exact solve is the expected hand strength,
+    // which takes minutes preflop. against more opponents there are too many
+    // hands to walk, so exact and threshold modes deal 100k samples.
+    pub fn equity_vs_random(
+        &self,
+        hero: &str,
+        opponents: usize,
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<f32, Error> {
+        if !(1..=9).contains(&opponents) {
+            return Err(Error::PlayerCount(opponents + 1));
+        }
+        let (hero, board) = hero_and_board(hero, bd)?;
+        let seed: u64 = self.seed.unwrap_or_else(rand::random);
+        let samples: usize = match mode {
+            SolveMode::MonteCarlo { samples } => samples,
+            _ if opponents == 1 => {
+                return Ok(expected_strength(hero, board, mode, self.nthreads(), seed).ehs)
+            }
+            _ => RANDOM_SAMPLES,
+        };
+        Ok(random_equity(
+            hero,
+            board,
+            opponents,
+            samples,
+            self.nthreads(),
+            seed,
+        ))
+    }
+
+    // how likely the hero is to end up with exactly `category` by the river,
+    // from `samples` runouts split up to find rare hands quickly, see
+    // rare.rs.
+    pub fn category_odds(
+        &self,
+        hero: &str,
+        bd: &str,
+        category: Category,
+        samples: usize,
+    ) -> Result<CategoryEstimate, Error> {
+        let (hero, board) = hero_and_board(hero, bd)?;
+        let seed: u64 = self.seed.unwrap_or_else(rand::random);
+        Ok(category_odds(hero, board, category, samples, seed))
+    }
+
+    // the hero's expected hand strength against a random hand and its
+    // square, over every runout or `samples` random ones. an exact solve
+    // preflop takes minutes.
+    pub fn hand_strength(
+        &self,
+        hero: &str,
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<HandStrength, Error> {
+        let (hero, board) = hero_and_board(hero, bd)?;
+        let seed: u64 = self.seed.unwrap_or_else(rand::random);
+        Ok(expected_strength(hero, board, mode, self.nthreads(), seed))
+    }
+
+    // the hero's equity after each card that can come next, to see how much
+    // it swings from card to card. solving the spot first makes this cheap,
+    // the next cards' answers are then already in the memo.
+    pub fn distribution(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+    ) -> Result<EquityDistribution, Error> {
+        let (game, board) = parse_game(hands, bd)?;
+        let drawn: u64 = game.hands.iter().fold(board, |acc, h| acc | h.hole_b);
+
+        let mut by_card: Vec<(String, f32)> = Vec::new();
+        if board.count_ones() < 5 {
+            for i in (0..52).filter(|&i| drawn & (1 << i) == 0) {
+                let next: Solution =
+                    self.solve_game(game.clone(), board | 1 << i, SolveMode::Exact, &mut |_| {});
+                by_card.push((Card::from_idx(i).to_string(), next.equity));
+            }
+        }
+        if by_card.is_empty() {
+            // nothing left to come.
+            let equity: f32 = self
+                .solve_game(game, board, SolveMode::Exact, &mut |_| {})
+                .equity;
+            return Ok(EquityDistribution {
+                by_card,
+                mean: equity,
+                std_dev: 0.,
+                histogram: [0; 10],
+            });
+        }
+
+        let n: f32 = by_card.len() as f32;
+        let mean: f32 = by_card.iter().map(|(_, e)| e).sum::<f32>() / n;
+        let var: f32 = by_card.iter().map(|(_, e)| (e - mean).powi(2)).sum::<f32>() / n;
+        let mut histogram: [u32; 10] = [0; 10];
+        for (_, e) in by_card.iter() {
+            histogram[((e * 10.) as usize).min(9)] += 1;
+        }
+        Ok(EquityDistribution {
+            by_card,
+            mean,
+            std_dev: var.sqrt(),
+            histogram,
+        })
+    }
+
+    // the hero's equity against each opponent alone, as if the others had
+    // folded, in the order the opponents were given. the reduced games go
+    // through this solver's cache like any other.
+    pub fn head_to_head(&self, hands: &[impl AsRef<str>], bd: &str) -> Result<Vec<f32>, Error> {
+        let [hero, opponents @ ..] = hands else {
+            return Err(Error::PlayerCount(0));
+        };
+        opponents
+            .iter()
+            .map(|opponent| {
+                let hands: [&str; 2] = [hero.as_ref(), opponent.as_ref()];
+                Ok(self.try_solve(&hands, bd)?.equity)
+            })
+            .collect()
+    }
+
+    // the hero's solve along with every player's win, tie and loss rates
+    // and made hands, in one call. the breakdown is a second pass over the
+    // same runouts, monte carlo ones included when the seed is fixed.
+    pub fn solve_detailed(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<DetailedSolution, Error> {
+        let (game, board) = parse_game(hands, bd)?;
+        let solution: Solution = self.solve_game(game.clone(), board, mode, &mut |_| {});
+
+        let holes: Arc<Vec<u64>> = Arc::new(game.hands.iter().map(|h| h.hole_b).collect());
+        let drawn: u64 = holes.iter().fold(board, |acc, h| acc | h);
+        let need: usize = 5 - board.count_ones() as usize;
+        let deck: Vec<usize> = (0..52).filter(|&i| drawn & 1 << i == 0).collect();
+        let mut runouts: Vec<u64> = Vec::new();
+        match mode {
+            // dealt like a monte carlo solve with the same seed.
+            SolveMode::MonteCarlo { samples } => {
+                let seed: u64 = self.seed.unwrap_or_else(rand::random);
+                runouts = sample_runouts(&deck, board, need, samples, seed);
+            }
+            SolveMode::Exact | SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => {
+                all_runouts(&deck, need, board, &mut runouts)
+            }
+        }
+
+        let nthreads: usize = self.nthreads();
+        let runouts: Arc<Vec<u64>> = Arc::new(runouts);
+        let workers: Vec<_> = (0..nthreads)
+            .map(|t| {
+                let (runouts, holes) = (runouts.clone(), holes.clone());
+                move |report: &mut dyn FnMut(Vec<PlayerCounts>)| {
+                    let mut counts: Vec<PlayerCounts> = vec![PlayerCounts::default(); holes.len()];
+                    let mut scores: Vec<u64> = vec![0; holes.len()];
+                    for &runout in runouts.iter().skip(t).step_by(nthreads) {
+                        for (score, hole) in scores.iter_mut().zip(holes.iter()) {
+                            *score = ladder_score(hole | runout);
+                        }
+                        let best: u64 = *scores.iter().max().unwrap();
+                        let winners: usize = scores.iter().filter(|&&s| s == best).count();
+                        for (c, &score) in counts.iter_mut().zip(scores.iter()) {
+                            c.categories[(score >> 26) as usize] += 1;
+                            if score < best {
+                                continue;
+                            }
+                            match winners {
+                                1 => c.wins += 1,
+                                _ => c.ties += 1,
+                            }
+                            c.equity += 1. / winners as f64;
+                        }
+                    }
+                    report(counts);
+                }
+            })
+            .collect();
+        let mut counts: Vec<PlayerCounts> = vec![PlayerCounts::default(); holes.len()];
+        run_workers(workers, |worker: Vec<PlayerCounts>| {
+            for (c, w) in counts.iter_mut().zip(worker) {
+                c.wins += w.wins;
+                c.ties += w.ties;
+                c.equity += w.equity;
+                for (a, b) in c.categories.iter_mut().zip(w.categories) {
+                    *a += b;
+                }
+            }
+        });
+
+        let n: f64 = runouts.len().max(1) as f64;
+        let players: Vec<PlayerResult> = hands
+            .iter()
+            .zip(counts)
+            .map(|(hand, c)| PlayerResult {
+                hand: hand.as_ref().trim().to_string(),
+                equity: (c.equity / n) as f32,
+                win: (c.wins as f64 / n) as f32,
+                tie: (c.ties as f64 / n) as f32,
+                lose: (1. - (c.wins + c.ties) as f64 / n) as f32,
+                categories: CATEGORIES
+                    .iter()
+                    .zip(c.categories)
+                    .map(|(name, k)| (name.to_string(), (k as f64 / n) as f32))
+                    .collect(),
+            })
+            .collect();
+        Ok(DetailedSolution {
+            equity: solution.equity,
+            players,
+            stats: solution.stats,
+        })
+    }
+
+    // a solve of `hands` on `bd` that can be dealt more cards, in the
+    // solver's mode. once the flop is solved, solving the turn after it
+    // reuses the flop's subtrees even when the solver keeps no memo.
+    pub fn incremental(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+    ) -> Result<IncrementalSolve<'_>, Error> {
+        let (game, board) = parse_game(hands, bd)?;
+        let (game, board, perm) = match self.mode {
+            SolveMode::MonteCarlo { .. } => (game, board, [0, 1, 2, 3]),
+            _ => game.canonical(board),
+        };
+        Ok(IncrementalSolve {
+            solver: self,
+            hands: hands
+                .iter()
+                .map(|h| h.as_ref().trim().to_string())
+                .collect(),
+            brancher: self.brancher(game, board),
+            board: bd.split_whitespace().collect(),
+            perm,
+        })
+    }
+
+    // every player's equity preflop and on every street of `bd` dealt so far,
+    // to step through a hand the way it played out.
+    pub fn replay(&self, hands: &[impl AsRef<str>], bd: &str) -> Result<Vec<Street>, Error> {
+        // a street is dealt whole, there's no board of one or two cards.
+        if (1..3).contains(&parse_board(bd)?.count_ones()) {
+            return Err(Error::InvalidBoard(bd.to_string()));
+        }
+        let bd: String = bd.split_whitespace().collect();
+        let streets = [("Preflop", 0), ("Flop", 3), ("Turn", 4), ("River", 5)];
+        streets
+            .into_iter()
+            .filter(|&(_, cards)| cards * 2 <= bd.len())
+            .map(|(name, cards)| {
+                let board: &str = &bd[..cards * 2];
+                let equities: Vec<f32> = match self.mode {
+                    SolveMode::Exact => self.equities(hands, board)?,
+                    // each player's equity is the hero's with their hand first.
+                    _ => (0..hands.len())
+                        .map(|i| {
+                            let mut hands: Vec<&str> = hands.iter().map(|h| h.as_ref()).collect();
+                            hands.swap(0, i);
+                            Ok(self.try_solve(&hands, board)?.equity)
+                        })
+                        .collect::<Result<_, Error>>()?,
+                };
+                Ok(Street {
+                    name: name.to_string(),
+                    board: board.to_string(),
+                    equities,
+                })
+            })
+            .collect()
+    }
+
+    fn solve_game(
+        &self,
+        game: Game,
+        board: u64,
+        mode: SolveMode,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> Solution {
+        // matchups that only differ in their suits share a memo. the board
+        // is relabeled along with the hands, so subtrees carry over between
+        // boards of the same matchup. monte carlo solves keep the cards they
+        // were given, their seed deals from the deck in that order.
+        let (game, board) = match mode {
+            SolveMode::MonteCarlo { .. } => (game, board),
+            _ => {
+                let (game, board, _) = game.canonical(board);
+                (game, board)
+            }
+        };
+        if let Some(solution) = self.cached_showdown(&game, board, mode) {
+            return solution;
+        }
+        let mut brancher: Brancher = self.brancher(game, board);
+        self.run(&mut brancher, mode, progress)
+    }
+
+    fn brancher(&self, mut game: Game, board: u64) -> Brancher {
+        for hand in game.hands.iter_mut() {
+            hand.memoize = self.hand_memo;
+        }
+        let memo = self.memo_for(&game);
+        Brancher::new(
+            game,
+            board,
+            memo,
+            self.memo_mode,
+            self.counters.clone(),
+            #[cfg(feature = "verify")]
+            self.verifier.clone(),
+        )
+    }
+
+    fn run(
+        &self,
+        brancher: &mut Brancher,
+        mode: SolveMode,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> Solution {
+        let seed: u64 = self.seed.unwrap_or_else(rand::random);
+        let sampler: Sampler = brancher.sampler(self.sampling, self.preflop.as_deref());
+        // the gpu only deals plain samples.
+        let plain: bool = sampler.is_plain() || !mode.is_sampled();
+        let gpu: Option<Solution> = match plain {
+            true => self.solve_on_gpu(brancher, mode, seed, progress),
+            false => None,
+        };
+        match gpu {
+            Some(solution) => solution,
+            None => brancher.compute_equity(mode, self.nthreads(), seed, &sampler, progress),
+        }
+    }
+
+    // the hero's answer from the showdown cache, when some other player's
+    // equities of the same spot put it there. exact answers only.
+    fn cached_showdown(&self, game: &Game, board: u64, mode: SolveMode) -> Option<Solution> {
+        let start: Instant = Instant::now();
+        if mode.is_sampled() {
+            return None;
+        }
+        let (key, seats) = showdown_key(game, board);
+        let tallies: Vec<Tally> = self.showdowns.get(&key)?;
+        let need: usize = 5 - board.count_ones() as usize;
+        let tally: Tally = tally_permutations(tallies[seats[game.hero_pos]], need);
+        let p: f32 = tally.equity();
+        Some(Solution {
+            equity: p,
+            tally,
+            bounds: matches!(mode, SolveMode::Threshold { .. }).then_some((p, p)),
+            stats: SolveStats {
+                mode,
+                leaves: 0,
+                cache_hits: 0,
+                cache_misses: 0,
+                threads: 0,
+                elapsed: start.elapsed(),
+                busy: Duration::ZERO,
+                cached: true,
+                stop: None,
+                std_err: None,
+                variance_reduction: None,
+            },
+        })
+    }
+
+    // how big an exact solve of `hands` on `bd` is and about how long it
+    // takes on the cpu from an empty memo, timing the spot's leaves for up
+    // to a few hundredths of a second. spots already in the memo go
+    // quicker.
+    pub fn exact_size(&self, hands: &[impl AsRef<str>], bd: &str) -> Result<SolveSize, Error> {
+        let (game, board) = parse_game(hands, bd)?;
+        let (players, dealt) = (game.hands.len(), board.count_ones() as u64);
+        let deck: u64 = 52 - 2 * players as u64 - dealt;
+        let need: u64 = 5 - dealt;
+        let runouts: u64 = exact_runouts(players, dealt as usize);
+        // every subtree from the empty board down to the leaves' parents.
+        let nodes: u64 = (0..need).map(|k| choose(deck, k)).sum();
+        let leaves: u64 = match cfg!(feature = "simd") && need > 0 {
+            true => need * runouts,
+            false => runouts,
+        };
+        let mut brancher: Brancher = Brancher::new(
+            game,
+            board,
+            Arc::new(Memo::new()),
+            MemoMode::default(),
+            Arc::new(Counters::default()),
+            #[cfg(feature = "verify")]
+            self.verifier.clone(),
+        );
+        brancher.hands.iter_mut().for_each(|h| h.memoize = false);
+        let mut rng: StdRng = StdRng::seed_from_u64(self.seed.unwrap_or_else(rand::random));
+        let leaf_time: Duration = brancher.leaf_time(CALIBRATION, leaves, &mut rng);
+        // past the turn the walk runs on one thread, before it on as many as
+        // there are first cards to split.
+        let threads: usize = match need {
+            0 | 1 => 1,
+            _ => self.nthreads().min(deck as usize),
+        };
+        Ok(SolveSize {
+            runouts,
+            leaves,
+            nodes,
+            leaf_time,
+            threads,
+            eta: leaf_time.mul_f64(leaves as f64 / threads as f64),
+        })
+    }
+
+    // every player's exact equity, in the order the hands were given. each
+    // runout is scored once for all of them, where solving with each
+    // player as the hero in turn scores it once per player, and the result
+    // is cached for the spot as a whole: later solves of it with any hero,
+    // or with the suits relabeled, are lookups.
+    pub fn equities(&self, hands: &[impl AsRef<str>], bd: &str) -> Result<Vec<f32>, Error> {
+        let (game, board) = parse_game(hands, bd)?;
+        let (key, seats) = showdown_key(&game, board);
+        let tallies: Vec<Tally> = match self.showdowns.get(&key) {
+            Some(tallies) => tallies,
+            None => {
+                // the players in the key's order.
+                let holes: Vec<u64> = key[1..].to_vec();
+                let tallies: Vec<Tally> = showdowns(holes, key[0], self.nthreads());
+                if self.persist {
+                    self.showdowns.insert(key, tallies.clone());
+                }
+                tallies
+            }
+        };
+        Ok(seats.iter().map(|&s| tallies[s].equity()).collect())
+    }
+
+    #[cfg(feature = "gpu")]
+    fn solve_on_gpu(
+        &self,
+        brancher: &mut Brancher,
+        mode: SolveMode,
+        seed: u64,
+        progress: &mut dyn FnMut(EquityEstimate),
+    ) -> Option<Solution> {
+        brancher.compute_equity_gpu(self.gpu.as_deref()?, mode, seed, progress)
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    fn solve_on_gpu(
+        &self,
+        _brancher: &mut Brancher,
+        _mode: SolveMode,
+        _seed: u64,
+        _progress: &mut dyn FnMut(EquityEstimate),
+    ) -> Option<Solution> {
+        None
+    }
+
+    // solves on a background thread, yielding better and better estimates
+    // as they come in. the last one is the final answer.
+    #[cfg(feature = "parallel")]
+    pub fn solve_streaming(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<impl Iterator<Item = EquityEstimate>, Error> {
+        // parse here so bad input is reported to the caller, not the thread.
+        parse_game(hands, bd)?;
+
+        let (tx, rx) = mpsc::channel::<EquityEstimate>();
+        let solver: Solver = self.clone();
+        let hands: Vec<String> = hands.iter().map(|h| h.as_ref().to_string()).collect();
+        let bd: String = bd.to_string();
+        thread::spawn(move || {
+            // the consumer may stop listening early.
+            let _ = solver.try_solve_with_progress(&hands, &bd, mode, |e| {
+                let _ = tx.send(e);
+            });
+        });
+        Ok(rx.into_iter())
+    }
+
+    // without threads the estimates can only be handed over once the solve is done.
+    #[cfg(not(feature = "parallel"))]
+    pub fn solve_streaming(
+        &self,
+        hands: &[impl AsRef<str>],
+        bd: &str,
+        mode: SolveMode,
+    ) -> Result<impl Iterator<Item = EquityEstimate>, Error> {
+        let mut estimates: Vec<EquityEstimate> = Vec::new();
+        self.try_solve_with_progress(hands, bd, mode, |e| estimates.push(e))?;
+        Ok(estimates.into_iter())
+    }
+}
+
+// the spot as the showdown cache keys it, the board and then the hands in
+// sorted order with the suits relabeled, and each player's place among them.
+fn showdown_key(game: &Game, board: u64) -> (Vec<u64>, Vec<usize>) {
+    let groups: Vec<u64> = std::iter::once(board)
+        .chain(game.hands.iter().map(|h| h.hole_b))
+        .collect();
+    let (perm, key, _) = canonical(&groups, 1);
+    let seats: Vec<usize> = game
+        .hands
+        .iter()
+        .map(|h| {
+            let hole: u64 = permute(h.hole_b, &perm);
+            key[1..].iter().position(|&k| k == hole).unwrap()
+        })
+        .collect();
+    (key, seats)
+}
+
+// every player's tally over each runout of `board`, counted once.
+fn showdowns(holes: Vec<u64>, board: u64, nthreads: usize) -> Vec<Tally> {
+    let dead: u64 = holes.iter().fold(board, |acc, h| acc | h);
+    let need: usize = 5 - board.count_ones() as usize;
+    let deck: Vec<usize> = (0..52).filter(|&i| dead & 1 << i == 0).collect();
+    let mut runouts: Vec<u64> = Vec::new();
+    all_runouts(&deck, need, board, &mut runouts);
+
+    let runouts: Arc<Vec<u64>> = Arc::new(runouts);
+    let holes: Arc<Vec<u64>> = Arc::new(holes);
+    let workers: Vec<_> = (0..nthreads)
+        .map(|t| {
+            let (runouts, holes) = (runouts.clone(), holes.clone());
+            move |report: &mut dyn FnMut(Vec<Tally>)| {
+                let mut tallies: Vec<Tally> = vec![Tally::default(); holes.len()];
+                let mut scores: Vec<u64> = vec![0; holes.len()];
+                for &runout in runouts.iter().skip(t).step_by(nthreads) {
+                    for (score, hole) in scores.iter_mut().zip(holes.iter()) {
+                        *score = ladder_score(hole | runout);
+                    }
+                    let best: u64 = *scores.iter().max().unwrap();
+                    let winners: u64 = scores.iter().filter(|&&s| s == best).count() as u64;
+                    for (tally, &score) in tallies.iter_mut().zip(scores.iter()) {
+                        tally.runouts += 1;
+                        match (score == best, winners) {
+                            (true, 1) => tally.wins += 1,
+                            (true, _) => tally.ties += Tally::SHARE / winners,
+                            _ => {}
+                        }
+                    }
+                }
+                report(tallies);
+            }
+        })
+        .collect();
+    let mut tallies: Vec<Tally> = vec![Tally::default(); holes.len()];
+    run_workers(workers, |worker: Vec<Tally>| {
+        for (total, tally) in tallies.iter_mut().zip(worker) {
+            *total += tally;
+        }
+    });
+    tallies
+}
+
+// fails with the first card of `hand` that is already in `dead`.
+pub(crate) fn check_free(dead: u64, hand: u64) -> Result<(), Error> {
+    match dead & hand {
+        0 => Ok(()),
+        taken => Err(Error::DuplicateCard(
+            Card::from_idx(taken.trailing_zeros() as usize).to_string(),
+        )),
+    }
+}
+
+// a spot being solved street by street, see Solver::incremental.
+pub struct IncrementalSolve<'a> {
+    solver: &'a Solver,
+    hands: Vec<String>,
+    brancher: Brancher,
+    // the board dealt so far, as given.
+    board: String,
+    // the suit relabeling the brancher's cards are in.
+    perm: [usize; 4],
+}
+
+impl IncrementalSolve<'_> {
+    pub fn board(&self) -> &str {
+        &self.board
+    }
+
+    // the hero's equity on the board dealt so far.
+    pub fn solve(&mut self) -> Solution {
+        let solver: &Solver = self.solver;
+        let solution: Solution = solver.run(&mut self.brancher, solver.mode, &mut |_| {});
+        if let Some(store) = &solver.store {
+            store.record(&self.hands, &self.board, &solver.options(), solution.equity);
+        }
+        solution
+    }
+
+    // deals one more card, e.g. the turn after the flop.
+    pub fn extend_board(&mut self, card: &str) -> Result<(), Error> {
+        let c: Card = parse_card(card.trim())?;
+        if self.brancher.spot.board.count_ones() == 5 {
+            return Err(Error::InvalidBoard(self.board.clone() + card.trim()));
+        }
+        let idx: usize = permute(1 << c.idx, &self.perm).trailing_zeros() as usize;
+        if self.brancher.drawn.contains(idx) {
+            return Err(Error::DuplicateCard(card.trim().to_string()));
+        }
+        self.brancher.extend_board(idx);
+        self.board.push_str(card.trim());
+        Ok(())
+    }
+}
+
+fn parse_card(s: &str) -> Result<Card, Error> {
+    Card::parse(s).ok_or_else(|| Error::InvalidCard(s.to_string()))
+}
+
+pub(crate) fn parse_board(bd: &str) -> Result<u64, Error> {
+    let bd: Vec<char> = bd.chars().filter(|c| !c.is_whitespace()).collect();
+    if !bd.len().is_multiple_of(2) || bd.len() > 10 {
+        return Err(Error::InvalidBoard(bd.iter().collect()));
+    }
+
+    let mut board: u64 = 0;
+    for chunk in bd.chunks(2) {
+        let c: String = chunk.iter().collect();
+        let card: Card = parse_card(&c)?;
+        if board & (1 << card.idx) != 0 {
+            return Err(Error::DuplicateCard(c));
+        }
+        board |= 1 << card.idx;
+    }
+    Ok(board)
+}
+
+fn parse_game(hands: &[impl AsRef<str>], bd: &str) -> Result<(Game, u64), Error> {
+    if !(2..=10).contains(&hands.len()) {
+        return Err(Error::PlayerCount(hands.len()));
+    }
+
+    let board: u64 = parse_board(bd)?;
+    // every card seen so far, to catch the same card being dealt twice.
+    let mut seen: u64 = board;
+    let mut hs: Vec<Hand> = Vec::new();
+
+    for hand in hands {
+        let hand: &str = hand.as_ref().trim();
+        let h: Hand = Hand::parse(hand)?;
+        for (card, c) in [(h.hole.0, &hand[..2]), (h.hole.1, &hand[2..])] {
+            if seen & (1 << card.idx) != 0 {
+                return Err(Error::DuplicateCard(c.to_string()));
+            }
+            seen |= 1 << card.idx;
+        }
+        hs.push(h);
+    }
+
+    Ok((Game::new(0, hs), board))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the equity and tally of `hands` on `bd` on each thread count.
+    fn solve_on(hands: &[&str], bd: &str, mode: SolveMode, seed: u64) -> Vec<(u32, Tally)> {
+        [1, 2, 8]
+            .into_iter()
+            .map(|threads| {
+                let solver: Solver = Solver::new().with_threads(threads).with_seed(seed);
+                let solution: Solution = solver
+                    .try_solve_with_progress(hands, bd, mode, |_| {})
+                    .unwrap();
+                (solution.equity.to_bits(), solution.tally)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replay_turns_away_a_partial_flop() {
+        let solver: Solver = Solver::new();
+        for bd in ["Th", "Th7h"] {
+            assert!(matches!(
+                solver.replay(&["AhKh", "QsQd"], bd),
+                Err(Error::InvalidBoard(_))
+            ));
+        }
+        let streets: Vec<Street> = solver.replay(&["AhKh", "QsQd"], "Th7h2c").unwrap();
+        assert_eq!(streets.len(), 2);
+    }
+
+    #[test]
+    fn exact_is_the_same_on_any_thread_count() {
+        let results = solve_on(&["AhKh", "QsQd", "7c6c"], "Jd8s2c", SolveMode::Exact, 0);
+        assert!(results.windows(2).all(|w| w[0] == w[1]), "{:?}", results);
+    }
+
+    #[test]
+    fn seeded_monte_carlo_is_the_same_on_any_thread_count() {
+        let mode = SolveMode::MonteCarlo { samples: 50_000 };
+        let results = solve_on(&["AhKh", "QsQd", "7c6c"], "", mode, 42);
+        assert!(results.windows(2).all(|w| w[0] == w[1]), "{:?}", results);
     }
 }
@@ -0,0 +1,280 @@
+use crate::isomorphism::canonical_scenario;
+use crate::sampling::Sampling;
+use crate::solver::{default_threads, SolveMode, SolverOptions};
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, Connection};
+#[cfg(not(feature = "sqlite"))]
+use std::fs::{File, OpenOptions};
+use std::io;
+#[cfg(not(feature = "sqlite"))]
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// a solve as it was recorded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoredResult {
+    // seconds since the unix epoch.
+    pub timestamp: u64,
+    // the hands, hero first, and board with the suits relabeled (see
+    // canonical_scenario), so spots that only differ in suits look alike.
+    pub hands: Vec<String>,
+    pub board: String,
+    // the options the solve ran with.
+    pub mode: SolveMode,
+    pub sampling: Sampling,
+    pub seed: Option<u64>,
+    pub threads: usize,
+    pub equity: f32,
+}
+
+// every solve a solver is asked for, so past spots can be looked up later.
+// see Solver::with_store. appended to a file one a line as "timestamp <tab>
+// hands <tab> board <tab> mode <tab> sampling <tab> seed <tab> threads <tab>
+// equity", or with the sqlite feature kept in a `results` table of a sqlite
+// database.
+#[derive(Debug)]
+pub struct ResultStore {
+    path: PathBuf,
+    #[cfg(not(feature = "sqlite"))]
+    file: Mutex<File>,
+    #[cfg(feature = "sqlite")]
+    db: Mutex<Connection>,
+}
+
+impl ResultStore {
+    // the store in `path`, made if it isn't there.
+    #[cfg(not(feature = "sqlite"))]
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let file: File = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(ResultStore {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let db: Connection = Connection::open(&path).map_err(io::Error::other)?;
+        db.execute_batch(
+            "CREATE TABLE IF NOT EXISTS results (
+                timestamp INTEGER NOT NULL,
+                hands TEXT NOT NULL,
+                board TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                sampling TEXT NOT NULL,
+                seed INTEGER,
+                threads INTEGER NOT NULL,
+                equity REAL NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS results_spot ON results (hands, board);",
+        )
+        .map_err(io::Error::other)?;
+        Ok(ResultStore {
+            path,
+            db: Mutex::new(db),
+        })
+    }
+
+    // `options` as the solve ran, threads left unset count as the default.
+    pub fn record(
+        &self,
+        hands: &[impl AsRef<str>],
+        board: &str,
+        options: &SolverOptions,
+        equity: f32,
+    ) {
+        // the solve already read these, they can't fail.
+        let Ok((hands, board)) = canonical_scenario(hands, board) else {
+            return;
+        };
+        let timestamp: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_secs());
+        let result: StoredResult = StoredResult {
+            timestamp,
+            hands,
+            board,
+            mode: options.mode,
+            sampling: options.sampling,
+            seed: options.seed,
+            threads: options.threads.unwrap_or_else(default_threads),
+            equity,
+        };
+        // a store that can't be written to shouldn't fail the solve.
+        if let Err(e) = self.write(&result) {
+            log::warn!("Couldn't record a solve in {}: {}.", self.path.display(), e);
+        }
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    fn write(&self, r: &StoredResult) -> io::Result<()> {
+        let line: String = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            r.timestamp,
+            r.hands.join(" "),
+            r.board,
+            mode_name(r.mode),
+            sampling_name(r.sampling),
+            r.seed.map_or("-".to_string(), |s| s.to_string()),
+            r.threads,
+            r.equity
+        );
+        self.file.lock().unwrap().write_all(line.as_bytes())
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn write(&self, r: &StoredResult) -> io::Result<()> {
+        self.db
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO results VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    r.timestamp as i64,
+                    r.hands.join(" "),
+                    r.board,
+                    mode_name(r.mode),
+                    sampling_name(r.sampling),
+                    // sqlite only has signed integers, the bits go in as they are.
+                    r.seed.map(|s| s as i64),
+                    r.threads as i64,
+                    r.equity,
+                ],
+            )
+            .map(|_| ())
+            .map_err(io::Error::other)
+    }
+
+    // everything recorded so far, oldest first. lines that can't be read
+    // are skipped.
+    #[cfg(not(feature = "sqlite"))]
+    pub fn records(&self) -> io::Result<Vec<StoredResult>> {
+        let text: String = std::fs::read_to_string(&self.path)?;
+        Ok(text.lines().filter_map(parse_line).collect())
+    }
+
+    // everything recorded so far, oldest first. rows that can't be read
+    // are skipped.
+    #[cfg(feature = "sqlite")]
+    pub fn records(&self) -> io::Result<Vec<StoredResult>> {
+        let db = self.db.lock().unwrap();
+        let mut rows = db
+            .prepare(
+                "SELECT timestamp, hands, board, mode, sampling, seed, threads, equity
+                FROM results ORDER BY rowid",
+            )
+            .map_err(io::Error::other)?;
+        let rows = rows
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<i64>>(5)?,
+                    row.get::<_, i64>(6)?,
+                    row.get::<_, f32>(7)?,
+                ))
+            })
+            .map_err(io::Error::other)?;
+        let mut records: Vec<StoredResult> = Vec::new();
+        for row in rows {
+            let (timestamp, hands, board, mode, sampling, seed, threads, equity) =
+                row.map_err(io::Error::other)?;
+            let (Some(mode), Some(sampling)) = (parse_mode(&mode), parse_sampling(&sampling))
+            else {
+                continue;
+            };
+            records.push(StoredResult {
+                timestamp: timestamp as u64,
+                hands: hands.split_whitespace().map(str::to_string).collect(),
+                board,
+                mode,
+                sampling,
+                seed: seed.map(|s| s as u64),
+                threads: threads as usize,
+                equity,
+            });
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn parse_line(line: &str) -> Option<StoredResult> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [timestamp, hands, board, mode, sampling, seed, threads, equity] = fields[..] else {
+        return None;
+    };
+    Some(StoredResult {
+        timestamp: timestamp.parse().ok()?,
+        hands: hands.split_whitespace().map(str::to_string).collect(),
+        board: board.to_string(),
+        mode: parse_mode(mode)?,
+        sampling: parse_sampling(sampling)?,
+        seed: match seed {
+            "-" => None,
+            s => Some(s.parse().ok()?),
+        },
+        threads: threads.parse().ok()?,
+        equity: equity.parse().ok()?,
+    })
+}
+
+// "exact", "montecarlo:100000", "threshold:0.5" or "precision:0.001:10",
+// the budget in seconds.
+fn mode_name(mode: SolveMode) -> String {
+    match mode {
+        SolveMode::Exact => "exact".to_string(),
+        SolveMode::MonteCarlo { samples } => format!("montecarlo:{}", samples),
+        SolveMode::Threshold { threshold } => format!("threshold:{}", threshold),
+        SolveMode::UntilPrecision { half_width, budget } => {
+            format!("precision:{}:{}", half_width, budget.as_secs_f64())
+        }
+    }
+}
+
+fn parse_mode(s: &str) -> Option<SolveMode> {
+    match s.split_once(':') {
+        None if s == "exact" => Some(SolveMode::Exact),
+        Some(("montecarlo", n)) => Some(SolveMode::MonteCarlo {
+            samples: n.parse().ok()?,
+        }),
+        Some(("threshold", t)) => Some(SolveMode::Threshold {
+            threshold: t.parse().ok()?,
+        }),
+        Some(("precision", rest)) => {
+            let (half_width, budget) = rest.split_once(':')?;
+            Some(SolveMode::UntilPrecision {
+                half_width: half_width.parse().ok()?,
+                budget: Duration::try_from_secs_f64(budget.parse().ok()?).ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn sampling_name(sampling: Sampling) -> &'static str {
+    match sampling {
+        Sampling::Plain => "plain",
+        Sampling::Stratified => "stratified",
+        Sampling::Antithetic => "antithetic",
+        Sampling::ControlVariate => "control",
+    }
+}
+
+fn parse_sampling(s: &str) -> Option<Sampling> {
+    match s {
+        "plain" => Some(Sampling::Plain),
+        "stratified" => Some(Sampling::Stratified),
+        "antithetic" => Some(Sampling::Antithetic),
+        "control" => Some(Sampling::ControlVariate),
+        _ => None,
+    }
+}
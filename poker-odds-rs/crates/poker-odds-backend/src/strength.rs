@@ -0,0 +1,297 @@
+use crate::deck::{sample_runouts, Deck};
+use crate::error::Error;
+use crate::solver::{
+    all_runouts, hand_mask, ladder_score, parse_board, run_workers, SolveMode, SAMPLE_BATCH,
+};
+use poker_odds_core::{card_names, describe};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+// how a hand does against a single random opponent, the usual features for
+// poker bots and card abstractions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandStrength {
+    // expected hand strength: the share of opponent hands beaten on the
+    // final board, ties counting half, averaged over the runouts. the same
+    // as the equity against a random hand.
+    pub ehs: f32,
+    // the average of the squared strength, which ranks draws that end up
+    // either very strong or very weak above made hands of the same ehs.
+    pub ehs2: f32,
+    // the runouts averaged over.
+    pub runouts: u64,
+}
+
+// where the hero's hand stands on the board as it is, among every two
+// cards an opponent can hold. unlike equity nothing more is dealt, so
+// this says how good the hand is now, not how it will do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Percentile {
+    // the hero's best five cards, e.g. "two pair K 9, 4".
+    pub hand: String,
+    // opponent holdings the hero's hand beats, ties with and loses to.
+    pub beats: u32,
+    pub ties: u32,
+    pub loses: u32,
+}
+
+impl Percentile {
+    // the share of holdings beaten, ties counting half.
+    pub fn share(&self) -> f32 {
+        let total: u32 = self.beats + self.ties + self.loses;
+        (self.beats as f32 + self.ties as f32 / 2.) / total as f32
+    }
+}
+
+// ranks the hero's hand on a board of three to five cards, e.g. "your
+// two pair beats 87% of hands".
+pub fn percentile(hero: &str, bd: &str) -> Result<Percentile, Error> {
+    let (hero, board) = hero_and_board(hero, bd)?;
+    if board.count_ones() < 3 {
+        return Err(Error::InvalidBoard(bd.to_string()));
+    }
+    let (beats, ties, loses) = matchups(hero, board);
+    Ok(Percentile {
+        hand: describe(ladder_score(hero | board)),
+        beats,
+        ties,
+        loses,
+    })
+}
+
+// the best hand anyone can hold on a board and how far the hero is from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nuts {
+    // e.g. "straight J high".
+    pub hand: String,
+    // every two cards that make it, e.g. "QhJh". counts the hero's cards
+    // too, the nuts don't depend on who holds them.
+    pub combos: Vec<String>,
+    pub hero_has_nuts: bool,
+    // opponent holdings that beat the hero right now.
+    pub stronger: u32,
+}
+
+// the nuts on a board of three to five cards, and whether the hero has them.
+pub fn nuts(hero: &str, bd: &str) -> Result<Nuts, Error> {
+    let (hero, board) = hero_and_board(hero, bd)?;
+    if board.count_ones() < 3 {
+        return Err(Error::InvalidBoard(bd.to_string()));
+    }
+    let deck: Vec<usize> = (0..52).filter(|&i| board & 1 << i == 0).collect();
+    let mut best: u64 = 0;
+    let mut combos: Vec<String> = Vec::new();
+    for (k, &a) in deck.iter().enumerate() {
+        for &b in &deck[k + 1..] {
+            let score: u64 = ladder_score(1 << a | 1 << b | board);
+            if score > best {
+                best = score;
+                combos.clear();
+            }
+            if score == best {
+                // higher card first.
+                combos.push(card_names(1 << b) + &card_names(1 << a));
+            }
+        }
+    }
+    let (_, _, stronger) = matchups(hero, board);
+    Ok(Nuts {
+        hand: describe(best),
+        combos,
+        hero_has_nuts: ladder_score(hero | board) == best,
+        stronger,
+    })
+}
+
+// the hero's and the board's card masks, which mustn't share a card.
+pub(crate) fn hero_and_board(hero: &str, bd: &str) -> Result<(u64, u64), Error> {
+    let hero: u64 = hand_mask(hero)?;
+    let board: u64 = parse_board(bd)?;
+    if hero & board != 0 {
+        let card: u64 = 1 << (hero & board).trailing_zeros();
+        return Err(Error::DuplicateCard(card_names(card)));
+    }
+    Ok((hero, board))
+}
+
+// how many of the two card hands left in the deck `hero` beats, ties with
+// and loses to on `board`.
+fn matchups(hero: u64, board: u64) -> (u32, u32, u32) {
+    let score: u64 = ladder_score(hero | board);
+    let deck: Vec<usize> = (0..52).filter(|&i| (hero | board) & 1 << i == 0).collect();
+    let (mut beats, mut ties, mut loses) = (0, 0, 0);
+    for (k, &a) in deck.iter().enumerate() {
+        for &b in &deck[k + 1..] {
+            match score.cmp(&ladder_score(1 << a | 1 << b | board)) {
+                Ordering::Greater => beats += 1,
+                Ordering::Equal => ties += 1,
+                Ordering::Less => loses += 1,
+            }
+        }
+    }
+    (beats, ties, loses)
+}
+
+// the share of the two card hands left in the deck that `hero` beats on
+// a complete `board`, ties counting half.
+fn strength(hero: u64, board: u64) -> f32 {
+    let (beats, ties, loses) = matchups(hero, board);
+    (beats as f32 + ties as f32 / 2.) / (beats + ties + loses) as f32
+}
+
+// averages `hero`'s strength over every runout of `board`, or over random
+// ones in monte carlo mode. threshold mode walks every runout too.
+pub(crate) fn expected_strength(
+    hero: u64,
+    board: u64,
+    mode: SolveMode,
+    nthreads: usize,
+    seed: u64,
+) -> HandStrength {
+    let need: usize = 5 - board.count_ones() as usize;
+    let deck: Vec<usize> = (0..52).filter(|&i| (hero | board) & 1 << i == 0).collect();
+    let mut runouts: Vec<u64> = Vec::new();
+    match mode {
+        // dealt like a monte carlo solve with the same seed.
+        SolveMode::MonteCarlo { samples } => {
+            runouts = sample_runouts(&deck, board, need, samples, seed)
+        }
+        SolveMode::Exact | SolveMode::Threshold { .. } | SolveMode::UntilPrecision { .. } => {
+            all_runouts(&deck, need, board, &mut runouts)
+        }
+    }
+
+    let runouts: Arc<Vec<u64>> = Arc::new(runouts);
+    let workers: Vec<_> = (0..nthreads)
+        .map(|t| {
+            let runouts = runouts.clone();
+            move |report: &mut dyn FnMut((f64, f64))| {
+                let (mut sum, mut squares) = (0., 0.);
+                for &runout in runouts.iter().skip(t).step_by(nthreads) {
+                    let s: f64 = strength(hero, runout) as f64;
+                    sum += s;
+                    squares += s * s;
+                }
+                report((sum, squares));
+            }
+        })
+        .collect();
+    let (mut sum, mut squares) = (0., 0.);
+    run_workers(workers, |(s, sq): (f64, f64)| {
+        sum += s;
+        squares += sq;
+    });
+
+    let n: f64 = runouts.len().max(1) as f64;
+    HandStrength {
+        ehs: (sum / n) as f32,
+        ehs2: (squares / n) as f32,
+        runouts: runouts.len() as u64,
+    }
+}
+
+// samples dealt against several random opponents when no count was given,
+// every opponent's cards being too many to walk.
+pub(crate) const RANDOM_SAMPLES: usize = 100_000;
+
+// `hero`'s equity against `opponents` random hands, each sample dealing the
+// opponents and the rest of `board` from the cards left, in batches like a
+// monte carlo solve.
+pub(crate) fn random_equity(
+    hero: u64,
+    board: u64,
+    opponents: usize,
+    samples: usize,
+    nthreads: usize,
+    seed: u64,
+) -> f32 {
+    let need: usize = 5 - board.count_ones() as usize;
+    let deck: Vec<usize> = (0..52).filter(|&i| (hero | board) & 1 << i == 0).collect();
+    let nbatches: usize = samples.div_ceil(SAMPLE_BATCH);
+    let workers: Vec<_> = (0..nthreads.min(nbatches))
+        .map(|t| {
+            let deck: Vec<usize> = deck.clone();
+            move |report: &mut dyn FnMut(f64)| {
+                for b in (t..nbatches).step_by(nthreads) {
+                    let n: usize = (samples - b * SAMPLE_BATCH).min(SAMPLE_BATCH);
+                    let mut deck: Deck = Deck::batch(&deck, seed, b);
+                    let mut won: f64 = 0.;
+                    for _ in 0..n {
+                        // the opponents' cards first, then the runout.
+                        let cards: &[usize] = deck.draw(opponents * 2 + need);
+                        let runout: u64 = cards[opponents * 2..]
+                            .iter()
+                            .fold(board, |acc, c| acc | 1 << c);
+                        let score: u64 = ladder_score(hero | runout);
+                        let mut tied: usize = 0;
+                        let mut beaten: bool = false;
+                        for pair in cards[..opponents * 2].chunks(2) {
+                            let other: u64 = ladder_score(1 << pair[0] | 1 << pair[1] | runout);
+                            match other.cmp(&score) {
+                                Ordering::Greater => {
+                                    beaten = true;
+                                    break;
+                                }
+                                Ordering::Equal => tied += 1,
+                                Ordering::Less => {}
+                            }
+                        }
+                        if !beaten {
+                            won += 1. / (tied + 1) as f64;
+                        }
+                    }
+                    report(won);
+                }
+            }
+        })
+        .collect();
+    let mut won: f64 = 0.;
+    run_workers(workers, |w: f64| won += w);
+    (won / samples.max(1) as f64) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_set_on_a_flop_loses_to_nothing() {
+        let p: Percentile = percentile("9h9d", "2c7d9s").unwrap();
+        assert_eq!(p.hand, "trips 9, 7 2");
+        assert_eq!(p.loses, 0);
+        assert_eq!(p.beats + p.ties, 1081);
+    }
+
+    #[test]
+    fn a_pair_of_aces_is_behind_on_a_flop() {
+        // the sets and two pairs beat it.
+        let p: Percentile = percentile("AhAd", "2c7d9s").unwrap();
+        assert_eq!(p.hand, "pair A, 9 7 2");
+        assert_eq!((p.loses, p.ties), (36, 1));
+    }
+
+    #[test]
+    fn nuts_on_a_flop() {
+        let n: Nuts = nuts("9h9d", "2c7d9s").unwrap();
+        assert_eq!(n.hand, "trips 9, 7 2");
+        assert_eq!(n.combos, ["9h9c", "9d9c", "9d9h"]);
+        assert!(n.hero_has_nuts);
+        assert_eq!(n.stronger, 0);
+        assert!(!nuts("3h4h", "2c7d9s").unwrap().hero_has_nuts);
+    }
+
+    #[test]
+    fn nuts_on_a_turn() {
+        let n: Nuts = nuts("Jh8h", "2c7d9sTc").unwrap();
+        assert_eq!(n.hand, "straight J high");
+        assert_eq!(n.combos.len(), 16);
+        assert!(n.hero_has_nuts);
+        assert_eq!(n.stronger, 0);
+        let n: Nuts = nuts("9h9d", "2c7d9sTc").unwrap();
+        assert!(!n.hero_has_nuts);
+        assert!(n.stronger > 0);
+    }
+}
@@ -0,0 +1,148 @@
+// the evaluator's lookup tables and their file format. build.rs works them
+// out into the asset the crate embeds, and lookup reads it back, so both
+// take this module as it is.
+use poker_odds_core::reference;
+
+// the start of a tables asset, and its version.
+pub const MAGIC: &[u8; 5] = b"EVTB2";
+
+// flush scores, one by each 13 bit value mask.
+pub const FLUSHES: usize = 1 << 13;
+
+// value count scores, one by the index of every five, six or seven values
+// with four of each at most, the fives first.
+pub const COUNTS: usize = 6175 + 18395 + 49205;
+
+// where each number of cards' value counts start.
+const BASES: [u32; 8] = [0, 0, 0, 0, 0, 0, 6175, 6175 + 18395];
+
+// every clubs card, the other suits are this shifted up.
+pub const CLUBS: u64 = 0x1_1111_1111_1111;
+
+// index offsets by value, then cards left before it, then its count.
+pub type Offsets = [[[u32; 5]; 8]; 13];
+
+// the hands with fewer of a value come first.
+pub fn offsets() -> Offsets {
+    // ways[v][r]: how many ways `r` cards go into the values from `v`
+    // up, four of each at most.
+    let mut ways: [[u32; 8]; 14] = [[0; 8]; 14];
+    ways[13][0] = 1;
+    for v in (0..13).rev() {
+        for r in 0..8 {
+            ways[v][r] = (0..=r.min(4)).map(|c| ways[v + 1][r - c]).sum();
+        }
+    }
+    let mut offsets: Offsets = [[[0; 5]; 8]; 13];
+    for (v, by_left) in offsets.iter_mut().enumerate() {
+        for (r, by_count) in by_left.iter_mut().enumerate() {
+            for c in 1..5 {
+                by_count[c] = by_count[c - 1] + if c <= r { ways[v + 1][r - c + 1] } else { 0 };
+            }
+        }
+    }
+    offsets
+}
+
+// where five to seven cards' value counts rank among all of them with as
+// many cards, from the count of each value, added up nibble by nibble.
+#[inline(always)]
+pub fn index(offsets: &Offsets, cards: u64) -> u32 {
+    let mut x: u64 = cards - (cards >> 1 & 0x5555_5555_5555_5555);
+    x = (x & 0x3333_3333_3333_3333) + (x >> 2 & 0x3333_3333_3333_3333);
+    let mut left: usize = cards.count_ones() as usize;
+    let mut index: u32 = BASES[left];
+    for (v, by_left) in offsets.iter().enumerate() {
+        let count: usize = (x >> (4 * v) & 0xF) as usize;
+        index += by_left[left][count];
+        left -= count;
+    }
+    index
+}
+
+// every score, by the reference evaluator: the flushes, then the value
+// counts.
+pub fn generate() -> Vec<u32> {
+    let offsets: Offsets = offsets();
+    let mut scores: Vec<u32> = vec![0; FLUSHES + COUNTS];
+    // five to seven cards of one suit, filled out with low cards of
+    // another.
+    for values in 0..FLUSHES as u64 {
+        let n: u32 = values.count_ones();
+        if !(5..=7).contains(&n) {
+            continue;
+        }
+        let flush: u64 = (0..13)
+            .filter(|v| values >> v & 1 == 1)
+            .fold(0, |acc, v| acc | 1 << (4 * v));
+        let filler: u64 = (0..7 - n as u64).fold(0, |acc, v| acc | 1 << (4 * v + 1));
+        scores[values as usize] = reference(flush | filler) as u32;
+    }
+    // every way to have five, six or seven values, each card of a value in
+    // the suit that has the fewest so far, so none gets five.
+    let mut counts: [u32; 13] = [0; 13];
+    for n in 5..=7 {
+        fill_counts(&offsets, &mut scores[FLUSHES..], &mut counts, 0, n);
+    }
+    scores
+}
+
+fn fill_counts(offsets: &Offsets, scores: &mut [u32], counts: &mut [u32; 13], v: usize, left: u32) {
+    if v == 13 {
+        if left > 0 {
+            return;
+        }
+        let mut suits: [u32; 4] = [0; 4];
+        let mut cards: u64 = 0;
+        for (value, &count) in counts.iter().enumerate() {
+            let mut order: [usize; 4] = [0, 1, 2, 3];
+            order.sort_by_key(|&s| suits[s]);
+            for &s in &order[..count as usize] {
+                suits[s] += 1;
+                cards |= 1 << (4 * value + s);
+            }
+        }
+        scores[index(offsets, cards) as usize] = reference(cards) as u32;
+        return;
+    }
+    for c in 0..=left.min(4) {
+        counts[v] = c;
+        fill_counts(offsets, scores, counts, v + 1, left - c);
+    }
+}
+
+// the header, how many scores, their checksum and then the scores.
+pub fn to_bytes(scores: &[u32]) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::with_capacity(scores.len() * 4);
+    for score in scores {
+        body.extend(score.to_le_bytes());
+    }
+    let mut bytes: Vec<u8> = MAGIC.to_vec();
+    bytes.extend((scores.len() as u64).to_le_bytes());
+    bytes.extend(checksum(&body).to_le_bytes());
+    bytes.extend(body);
+    bytes
+}
+
+// the scores of an asset, None unless it has the header, every score and
+// the checksum they add up to.
+pub fn scores_of(bytes: &[u8]) -> Option<&[u8]> {
+    let rest: &[u8] = bytes.strip_prefix(MAGIC)?;
+    let (len, rest) = rest.split_first_chunk::<8>()?;
+    let (sum, body) = rest.split_first_chunk::<8>()?;
+    let len: usize = u64::from_le_bytes(*len) as usize;
+    if len != FLUSHES + COUNTS
+        || body.len() != len * 4
+        || checksum(body) != u64::from_le_bytes(*sum)
+    {
+        return None;
+    }
+    Some(body)
+}
+
+// fnv-1a.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100_0000_01b3)
+    })
+}
@@ -0,0 +1,295 @@
+use crate::error::Error;
+use crate::solver::{ladder_score, parse_board, run_workers, Tally};
+use poker_odds_core::card_names;
+use std::fmt;
+use std::sync::Arc;
+
+// what a board makes possible, whoever holds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Texture {
+    pub paired: bool,
+    // three cards of a suit.
+    pub flush_possible: bool,
+    // three ranks within a straight's five.
+    pub straight_possible: bool,
+}
+
+impl Texture {
+    pub fn of(bd: &str) -> Result<Self, Error> {
+        Ok(Texture::of_mask(parse_board(bd)?))
+    }
+
+    pub(crate) fn of_mask(board: u64) -> Self {
+        let ranks: Vec<u32> = (0..13)
+            .map(|r| (board >> (4 * r) & 0xf).count_ones())
+            .collect();
+        // the ace also plays low, in the wheel.
+        let mut present: u32 = (0..13).fold(0, |acc, r| acc | ((ranks[r] > 0) as u32) << (r + 1));
+        present |= present >> 13 & 1;
+        Texture {
+            paired: ranks.iter().any(|&n| n > 1),
+            flush_possible: (0..4)
+                .any(|s| (0..13).filter(|r| board >> (4 * r + s) & 1 == 1).count() >= 3),
+            straight_possible: (0..10).any(|low| (present >> low & 0x1f).count_ones() >= 3),
+        }
+    }
+}
+
+// how a turn or river card changes the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shift {
+    // the best hand anyone can hold is a different one, e.g. a set of aces
+    // instead of kings, or a flush instead of a set.
+    Nuts,
+    // the first flush or straight the board allows.
+    Flush,
+    Straight,
+}
+
+// every card that can come next on a flop or turn that changes the nuts or
+// completes a draw, with what it does.
+pub fn runout_shifts(bd: &str) -> Result<Vec<(String, Vec<Shift>)>, Error> {
+    let board: u64 = parse_board(bd)?;
+    if !(3..=4).contains(&board.count_ones()) {
+        return Err(Error::InvalidBoard(bd.to_string()));
+    }
+    let before: Texture = Texture::of_mask(board);
+    // the nut hand, without its kickers.
+    let nuts: u64 = nut_score(board) >> 13;
+    let mut shifts: Vec<(String, Vec<Shift>)> = Vec::new();
+    for card in (0..52).filter(|&i| board & 1 << i == 0) {
+        let next: u64 = board | 1 << card;
+        let after: Texture = Texture::of_mask(next);
+        let mut shift: Vec<Shift> = Vec::new();
+        if nut_score(next) >> 13 != nuts {
+            shift.push(Shift::Nuts);
+        }
+        if after.flush_possible && !before.flush_possible {
+            shift.push(Shift::Flush);
+        }
+        if after.straight_possible && !before.straight_possible {
+            shift.push(Shift::Straight);
+        }
+        if !shift.is_empty() {
+            shifts.push((card_names(1 << card), shift));
+        }
+    }
+    Ok(shifts)
+}
+
+// the score of the best two cards to hold on `board`.
+fn nut_score(board: u64) -> u64 {
+    let deck: Vec<usize> = (0..52).filter(|&i| board & 1 << i == 0).collect();
+    let mut best: u64 = 0;
+    for (k, &a) in deck.iter().enumerate() {
+        for &b in &deck[k + 1..] {
+            best = best.max(ladder_score(1 << a | 1 << b | board));
+        }
+    }
+    best
+}
+
+// the groups of boards a texture report splits runouts into. rainbow is
+// three suits or more, so turns and rivers with all four count too.
+const GROUPS: [&str; 9] = [
+    "monotone",
+    "two-tone",
+    "rainbow",
+    "paired",
+    "unpaired",
+    "flush possible",
+    "no flush",
+    "straight possible",
+    "no straight",
+];
+
+// the groups `board` falls in, one bit each.
+fn group_bits(board: u64) -> u16 {
+    let texture: Texture = Texture::of_mask(board);
+    let suits: usize = (0..4)
+        .filter(|s| (0..13).any(|v| board >> (4 * v + s) & 1 == 1))
+        .count();
+    let bit = |yes: bool, first: usize| -> u16 { 1 << (first + !yes as usize) };
+    1 << (suits.min(3) - 1)
+        | bit(texture.paired, 3)
+        | bit(texture.flush_possible, 5)
+        | bit(texture.straight_possible, 7)
+}
+
+// every player's equity over the runouts whose board falls in a group.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureGroup {
+    // e.g. "monotone".
+    pub name: String,
+    // of the boards seen.
+    pub share: f32,
+    // in the order the hands were given.
+    pub equities: Vec<f32>,
+}
+
+// a matchup's equities split by the texture of the board on one street: on
+// the flop a runout is counted once under each flop its cards could have
+// come in, on the turn each turn, on the river once.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureReport {
+    pub hands: Vec<String>,
+    pub board: String,
+    // the cards on the board the textures are of, 3 to 5.
+    pub cards: usize,
+    // the groups any board fell in, in the order monotone, two-tone,
+    // rainbow, paired, unpaired, flush possible or not, straight possible
+    // or not.
+    pub groups: Vec<TextureGroup>,
+}
+
+impl TextureReport {
+    // scores `holes` on each of `runouts`, which finish `board`.
+    pub(crate) fn new(
+        hands: Vec<String>,
+        bd: &str,
+        board: u64,
+        holes: Vec<u64>,
+        runouts: Vec<u64>,
+        cards: usize,
+        nthreads: usize,
+    ) -> Self {
+        let players: usize = holes.len();
+        let (holes, runouts) = (Arc::new(holes), Arc::new(runouts));
+        let workers: Vec<_> = (0..nthreads)
+            .map(|t| {
+                let (holes, runouts) = (holes.clone(), runouts.clone());
+                move |report: &mut dyn FnMut(Vec<Vec<Tally>>)| {
+                    let mut tallies: Vec<Vec<Tally>> = vec![vec![Tally::default(); players]; 9];
+                    let mut scores: Vec<u64> = vec![0; players];
+                    for &runout in runouts.iter().skip(t).step_by(nthreads) {
+                        for (score, hole) in scores.iter_mut().zip(holes.iter()) {
+                            *score = ladder_score(hole | runout);
+                        }
+                        let best: u64 = *scores.iter().max().unwrap();
+                        let winners: u64 = scores.iter().filter(|&&s| s == best).count() as u64;
+                        let dealt: Vec<u64> = (0..52)
+                            .filter(|&i| (runout & !board) >> i & 1 == 1)
+                            .map(|i| 1 << i)
+                            .collect();
+                        let need: usize = cards - board.count_ones() as usize;
+                        for pick in
+                            (0..1u32 << dealt.len()).filter(|p| p.count_ones() as usize == need)
+                        {
+                            let seen: u64 = (0..dealt.len())
+                                .filter(|i| pick >> i & 1 == 1)
+                                .fold(board, |acc, i| acc | dealt[i]);
+                            let bits: u16 = group_bits(seen);
+                            for (g, group) in tallies.iter_mut().enumerate() {
+                                if bits >> g & 1 == 0 {
+                                    continue;
+                                }
+                                for (tally, &score) in group.iter_mut().zip(scores.iter()) {
+                                    tally.runouts += 1;
+                                    match (score == best, winners) {
+                                        (true, 1) => tally.wins += 1,
+                                        (true, _) => tally.ties += Tally::SHARE / winners,
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    report(tallies);
+                }
+            })
+            .collect();
+        let mut tallies: Vec<Vec<Tally>> = vec![vec![Tally::default(); players]; 9];
+        run_workers(workers, |worker: Vec<Vec<Tally>>| {
+            for (total, group) in tallies.iter_mut().zip(worker) {
+                for (total, tally) in total.iter_mut().zip(group) {
+                    *total += tally;
+                }
+            }
+        });
+
+        // every board is in one of the three suit groups.
+        let seen: u64 = tallies[..3].iter().map(|g| g[0].runouts).sum();
+        let groups: Vec<TextureGroup> = GROUPS
+            .iter()
+            .zip(tallies)
+            .filter(|(_, group)| group[0].runouts > 0)
+            .map(|(name, group)| TextureGroup {
+                name: name.to_string(),
+                share: group[0].runouts as f32 / seen as f32,
+                equities: group.iter().map(Tally::equity).collect(),
+            })
+            .collect();
+        TextureReport {
+            hands,
+            board: bd.to_string(),
+            cards,
+            groups,
+        }
+    }
+
+    // a line per group: its name, share and each player's equity.
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = format!("group,share,{}\n", self.hands.join(","));
+        for g in &self.groups {
+            let equities: Vec<String> = g.equities.iter().map(|e| format!("{:.4}", e)).collect();
+            csv += &format!("{},{:.4},{}\n", g.name, g.share, equities.join(","));
+        }
+        csv
+    }
+}
+
+impl fmt::Display for TextureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let street: &str = ["flop", "turn", "river"][self.cards - 3];
+        match self.board.is_empty() {
+            true => writeln!(f, "Equity (%) by {} texture", street)?,
+            false => writeln!(f, "Equity (%) by {} texture from {}", street, self.board)?,
+        }
+        write!(f, "{:<18}{:>7}", "", "share")?;
+        for hand in &self.hands {
+            write!(f, "{:>7}", hand)?;
+        }
+        writeln!(f)?;
+        for g in &self.groups {
+            write!(f, "{:<18}{:>6.1}%", g.name, g.share * 100.)?;
+            for e in &g.equities {
+                write!(f, "{:>7.1}", e * 100.)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shift(bd: &str, card: &str) -> Vec<Shift> {
+        runout_shifts(bd)
+            .unwrap()
+            .into_iter()
+            .find(|(c, _)| c == card)
+            .map_or(Vec::new(), |(_, shift)| shift)
+    }
+
+    #[test]
+    fn pairing_the_top_card_changes_the_nuts() {
+        // a set of nines becomes quads.
+        assert!(shift("2c7d9s", "9h").contains(&Shift::Nuts));
+    }
+
+    #[test]
+    fn a_blank_keeps_the_nuts() {
+        assert!(!shift("2c7d9s", "3h").contains(&Shift::Nuts));
+    }
+
+    #[test]
+    fn a_turn_card_can_bring_a_flush() {
+        assert!(shift("2c7dKc", "9c").contains(&Shift::Flush));
+    }
+}
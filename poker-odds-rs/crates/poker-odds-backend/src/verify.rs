@@ -0,0 +1,47 @@
+use crate::selftest::SelfTest;
+use log::warn;
+use poker_odds_core::reference;
+#[cfg(feature = "simd")]
+use std::simd::u64x16;
+use std::sync::Mutex;
+
+// checks the scores of the fast evaluators against the reference on some
+// of the runouts a solve walks, and keeps what it finds.
+#[derive(Debug, Default)]
+pub struct Verifier(Mutex<SelfTest>);
+
+impl Verifier {
+    // roughly one runout in 1024 is checked. picked by the cards, so the
+    // same runouts get checked however the solve is split up.
+    pub fn sampled(board: u64) -> bool {
+        board.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 54 == 0
+    }
+
+    pub fn check(&self, evaluator: &'static str, cards: u64, got: u64) {
+        let expected: u64 = reference(cards);
+        let mut report = self.0.lock().unwrap();
+        if expected != got {
+            warn!(
+                "The {} evaluator scored {:#x} as {:#x} instead of {:#x}.",
+                evaluator, cards, got, expected
+            );
+        }
+        report.hands += 1;
+        report.check(evaluator, cards, expected, got);
+    }
+
+    // checks the lanes set in `lanes`.
+    #[cfg(feature = "simd")]
+    pub fn check_lanes(&self, evaluator: &'static str, lanes: u64, cards: u64x16, got: u64x16) {
+        let mut m: u64 = lanes;
+        while m != 0 {
+            let lane: usize = m.trailing_zeros() as usize;
+            self.check(evaluator, cards[lane], got[lane]);
+            m &= m - 1;
+        }
+    }
+
+    pub fn report(&self) -> SelfTest {
+        self.0.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,56 @@
+use poker_odds_backend::{answer_query, Solver};
+use serenity::async_trait;
+use serenity::model::channel::Message;
+use serenity::prelude::{Client, Context, EventHandler, GatewayIntents};
+use std::env;
+use std::sync::Arc;
+
+const PREFIX: &str = "!equity ";
+
+struct Handler {
+    // shared across messages so repeated spots hit the memo.
+    solver: Arc<Solver>,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    // answers "!equity AhKh vs QsQd on 7c8c9c" in the channel it was asked in.
+    async fn message(&self, ctx: Context, msg: Message) {
+        let Some(query) = msg.content.strip_prefix(PREFIX) else {
+            return;
+        };
+        let solver = self.solver.clone();
+        let query: String = query.to_string();
+
+        // solving is cpu bound and spawns its own threads, keep it off the runtime.
+        let reply: String = match tokio::task::spawn_blocking(move || {
+            answer_query(&solver, &query).unwrap_or_else(|e| e.to_string())
+        })
+        .await
+        {
+            Ok(reply) => reply,
+            Err(e) => e.to_string(),
+        };
+        if let Err(e) = msg.reply(&ctx, reply).await {
+            eprintln!("couldn't reply to {}: {}", msg.author.name, e);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let token: String = env::var("DISCORD_TOKEN")?;
+    let intents: GatewayIntents = GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT;
+
+    let handler = Handler {
+        solver: Arc::new(Solver::new()),
+    };
+    let mut client: Client = Client::builder(&token, intents)
+        .event_handler(handler)
+        .await?;
+    println!("Answering {}...", PREFIX.trim());
+    client.start().await?;
+    Ok(())
+}
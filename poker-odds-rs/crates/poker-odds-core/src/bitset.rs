@@ -0,0 +1,43 @@
+// a set of cards by index, with its size kept alongside.
+#[derive(Debug, Clone, Default)]
+pub struct BitSet {
+    pub s: u64,
+    length: usize,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { s: 0, length: 0 }
+    }
+
+    pub fn add(&mut self, idx: usize) {
+        if !self.contains(idx) {
+            self.s |= 1 << idx;
+            self.length += 1;
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if self.contains(idx) {
+            self.s -= 1 << idx;
+            self.length -= 1;
+        }
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        (self.s >> idx) & 1 == 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn add_board(&mut self, board: &u64) {
+        self.length += ((*board).count_ones() - (*board & self.s).count_ones()) as usize;
+        self.s |= *board;
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use strum_macros::EnumIter;
+
+pub const VALUES: &[u8; 13] = b"23456789TJQKA";
+pub const SUITS: &[u8; 4] = b"chsd";
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy, EnumIter)]
+pub enum Suits {
+    Clubs,
+    Hearts,
+    Spades,
+    Diamonds,
+}
+
+impl Suits {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'c' => Some(Suits::Clubs),
+            'h' => Some(Suits::Hearts),
+            's' => Some(Suits::Spades),
+            'd' => Some(Suits::Diamonds),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, EnumIter)]
+pub enum Value {
+    Two = 2,
+    Three = 3,
+    Four = 4,
+    Five = 5,
+    Six = 6,
+    Seven = 7,
+    Eight = 8,
+    Nine = 9,
+    Ten = 10,
+    Jack = 11,
+    Queen = 12,
+    King = 13,
+    Ace = 14,
+}
+
+impl From<u8> for Value {
+    fn from(value: u8) -> Self {
+        match value {
+            2 => Value::Two,
+            3 => Value::Three,
+            4 => Value::Four,
+            5 => Value::Five,
+            6 => Value::Six,
+            7 => Value::Seven,
+            8 => Value::Eight,
+            9 => Value::Nine,
+            10 => Value::Ten,
+            11 => Value::Jack,
+            12 => Value::Queen,
+            13 => Value::King,
+            14 => Value::Ace,
+            _ => panic!("Invalid card value"),
+        }
+    }
+}
+
+// a card and its index in card masks, (value - 2) * 4 plus the suit in
+// the order of SUITS.
+#[derive(Debug, Clone, Copy)]
+pub struct Card {
+    pub value: Value,
+    pub suit: Suits,
+    pub idx: usize,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value: char = VALUES[self.value as usize - 2] as char;
+        let suit: char = match self.suit {
+            Suits::Clubs => 'c',
+            Suits::Hearts => 'h',
+            Suits::Spades => 's',
+            Suits::Diamonds => 'd',
+        };
+        write!(f, "{}{}", value, suit)
+    }
+}
+
+impl Card {
+    pub fn from_idx(idx: usize) -> Self {
+        let suit: Suits = [Suits::Clubs, Suits::Hearts, Suits::Spades, Suits::Diamonds][idx % 4];
+        Self::new(Value::from(idx as u8 / 4 + 2), suit)
+    }
+
+    pub fn new(value: Value, suit: Suits) -> Self {
+        let mut _idx = value as usize * 4 - 8;
+        for (i, s) in [Suits::Clubs, Suits::Hearts, Suits::Spades, Suits::Diamonds]
+            .iter()
+            .enumerate()
+        {
+            if suit == *s {
+                _idx += i;
+                break;
+            }
+        }
+
+        Card {
+            value,
+            suit,
+            idx: _idx,
+        }
+    }
+
+    // e.g. "Ah", None for anything else.
+    pub fn parse(s: &str) -> Option<Self> {
+        let b: &[u8] = s.as_bytes();
+        if b.len() != 2 {
+            return None;
+        }
+        let value: u8 = match b[0] {
+            65 => 14,
+            75 => 13,
+            81 => 12,
+            74 => 11,
+            84 => 10,
+            50..=57 => b[0] - 48,
+            _ => return None,
+        };
+        let suit: Suits = Suits::from_char(b[1] as char)?;
+        Some(Self::new(Value::from(value), suit))
+    }
+}
+
+// the cards in a mask, lowest first, e.g. "2c7hAs".
+pub fn card_names(cards: u64) -> String {
+    (0..52)
+        .filter(|&i| cards >> i & 1 == 1)
+        .flat_map(|i| [VALUES[i / 4] as char, SUITS[i % 4] as char])
+        .collect()
+}
+
+// any of the 52 cards.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Card {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Card::from_idx(u.int_in_range(0..=51)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        u8::size_hint(depth)
+    }
+}
@@ -0,0 +1,151 @@
+use crate::card::VALUES;
+
+// the category names, indexed by a score's category.
+pub const CATEGORIES: [&str; 9] = [
+    "high card",
+    "pair",
+    "two pair",
+    "trips",
+    "straight",
+    "flush",
+    "full house",
+    "quads",
+    "straight flush",
+];
+
+// the best of the five card hands in five to seven cards, e.g. the 21 of
+// seven.
+pub fn reference(cards: u64) -> u64 {
+    let n: usize = cards.count_ones() as usize;
+    let mut idx: [usize; 7] = [0; 7];
+    let mut m: u64 = cards;
+    for i in idx.iter_mut().take(n) {
+        *i = m.trailing_zeros() as usize;
+        m &= m - 1;
+    }
+
+    let mut best: u64 = 0;
+    for skip_a in 0..7 {
+        for skip_b in skip_a + 1..7 {
+            // with fewer than seven cards the missing ones are always left out.
+            if (n..7).any(|i| i != skip_a && i != skip_b) {
+                continue;
+            }
+            let mut five: [usize; 5] = [0; 5];
+            let mut n: usize = 0;
+            for (i, &c) in idx.iter().enumerate() {
+                if i != skip_a && i != skip_b {
+                    five[n] = c;
+                    n += 1;
+                }
+            }
+            best = best.max(score_five(five));
+        }
+    }
+    best
+}
+
+// scores five cards straight from the rules: category, then the ranks that
+// decide between two hands of it, as rank masks (or the top card of a
+// straight).
+fn score_five(cards: [usize; 5]) -> u64 {
+    let mut counts: [u32; 13] = [0; 13];
+    let mut ranks: u64 = 0;
+    for c in cards {
+        counts[c / 4] += 1;
+        ranks |= 1 << (c / 4);
+    }
+    let with = |n: u32| -> u64 {
+        (0..13)
+            .filter(|&r| counts[r] == n)
+            .fold(0, |acc, r| acc | 1 << r)
+    };
+    let (quads, trips, pairs, singles) = (with(4), with(3), with(2), with(1));
+
+    let flush: bool = cards.iter().all(|c| c % 4 == cards[0] % 4);
+    // the ace plays low in the wheel.
+    let straight: u64 = if ranks == 0b1_0000_0000_1111 {
+        5
+    } else if ranks.count_ones() == 5 && ranks >> ranks.trailing_zeros() == 0b1_1111 {
+        ranks.trailing_zeros() as u64 + 6
+    } else {
+        0
+    };
+
+    let (category, a, b): (u64, u64, u64) = if flush && straight != 0 {
+        (8, straight, 0)
+    } else if quads != 0 {
+        (7, quads, singles)
+    } else if trips != 0 && pairs != 0 {
+        (6, trips, pairs)
+    } else if flush {
+        (5, ranks, 0)
+    } else if straight != 0 {
+        (4, straight, 0)
+    } else if trips != 0 {
+        (3, trips, singles)
+    } else if pairs.count_ones() == 2 {
+        (2, pairs, singles)
+    } else if pairs != 0 {
+        (1, pairs, singles)
+    } else {
+        (0, ranks, 0)
+    };
+    category << 26 | a << 13 | b
+}
+
+// e.g. "two pair K 9, 4".
+pub fn describe(score: u64) -> String {
+    let category: usize = (score >> 26) as usize;
+    let a: u64 = (score >> 13) & 0x1fff;
+    let b: u64 = score & 0x1fff;
+    let Some(name) = CATEGORIES.get(category) else {
+        return format!("{:#x}", score);
+    };
+    // straights are scored by their top card.
+    if category == 4 || category == 8 {
+        return match a {
+            2..=14 => format!("{} {} high", name, VALUES[a as usize - 2] as char),
+            _ => format!("{} {:#x}", name, score),
+        };
+    }
+    if b == 0 {
+        format!("{} {}", name, rank_names(a))
+    } else {
+        format!("{} {}, {}", name, rank_names(a), rank_names(b))
+    }
+}
+
+// highest first.
+fn rank_names(ranks: u64) -> String {
+    (0..13)
+        .rev()
+        .filter(|&r| ranks >> r & 1 == 1)
+        .map(|r| (VALUES[r] as char).to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// the ranks in a card mask, one bit per rank with the deuce at bit 0.
+#[inline(always)]
+pub fn ranks_of(cards: u64) -> u32 {
+    (0..4).fold(0, |acc, suit| acc | suit_ranks(cards, suit))
+}
+
+// the ranks of one suit's cards.
+#[inline(always)]
+pub fn suit_ranks(cards: u64, suit: u64) -> u32 {
+    (0..13).fold(0, |acc, r| {
+        acc | (((cards >> (4 * r + suit)) & 1) as u32) << r
+    })
+}
+
+// keeps the n highest ranks of a rank mask.
+#[inline(always)]
+pub fn top_ranks(ranks: u32, n: u32) -> u32 {
+    let mut m: u32 = ranks;
+    for _ in n..m.count_ones() {
+        m &= m - 1;
+    }
+    m
+}
@@ -0,0 +1,7 @@
+mod bitset;
+mod card;
+mod eval;
+
+pub use bitset::BitSet;
+pub use card::{card_names, Card, Suits, Value, SUITS, VALUES};
+pub use eval::{describe, ranks_of, reference, suit_ranks, top_ranks, CATEGORIES};
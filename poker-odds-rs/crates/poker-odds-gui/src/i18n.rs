@@ -0,0 +1,134 @@
+use poker_odds_backend::Error;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+// the languages shipped with the app. more can be added without building
+// it, by putting files like these in LOCALES_DIR.
+const BUILT_IN: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.txt")),
+    ("es", include_str!("../locales/es.txt")),
+];
+
+// read at startup from the working directory, one file a language named
+// by its code, e.g. "locales/fr.txt". a file for a built in language
+// replaces it.
+pub const LOCALES_DIR: &str = "locales";
+
+// the texts of one language by key, read from "key = text" lines. "{0}",
+// "{1}" and so on in a text are filled in by `Locale::format`.
+pub struct Locale {
+    pub code: String,
+    texts: HashMap<String, String>,
+}
+
+impl Locale {
+    fn parse(code: &str, file: &str) -> Self {
+        let texts: HashMap<String, String> = file
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, text)| (key.trim().to_string(), text.trim().to_string()))
+            .collect();
+        Locale {
+            code: code.to_string(),
+            texts,
+        }
+    }
+
+    // the language's own name for itself, e.g. "Español".
+    pub fn name(&self) -> &str {
+        self.texts
+            .get("language")
+            .map_or(&self.code, String::as_str)
+    }
+}
+
+pub struct Locales {
+    locales: Vec<Locale>,
+    // the locale in use.
+    pub current: usize,
+}
+
+// the built in languages, in english.
+impl Default for Locales {
+    fn default() -> Self {
+        Locales {
+            locales: BUILT_IN
+                .iter()
+                .map(|(code, file)| Locale::parse(code, file))
+                .collect(),
+            current: 0,
+        }
+    }
+}
+
+impl Locales {
+    // the built in languages and any in LOCALES_DIR, starting in the one
+    // the system's LANG asks for when there is one.
+    pub fn load() -> Self {
+        let mut locales: Vec<Locale> = Locales::default().locales;
+        let files = std::fs::read_dir(Path::new(LOCALES_DIR))
+            .into_iter()
+            .flatten();
+        for path in files.flatten().map(|entry| entry.path()) {
+            let Some(code) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Ok(file) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let locale: Locale = Locale::parse(code, &file);
+            match locales.iter_mut().find(|l| l.code == locale.code) {
+                Some(l) => *l = locale,
+                None => locales.push(locale),
+            }
+        }
+
+        // e.g. "es_ES.UTF-8".
+        let lang: String = std::env::var("LANG").unwrap_or_default();
+        let current: usize = locales
+            .iter()
+            .position(|l| lang.starts_with(&l.code))
+            .unwrap_or(0);
+        Locales { locales, current }
+    }
+
+    pub fn all(&self) -> &[Locale] {
+        &self.locales
+    }
+
+    // the text for `key` in the current language, in english when it
+    // hasn't been translated, or the key itself.
+    pub fn text(&self, key: &str) -> String {
+        [self.current, 0]
+            .iter()
+            .find_map(|&i| self.locales.get(i)?.texts.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    // the text for `key` with `args` in place of "{0}", "{1}" and so on.
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut text: String = self.text(key);
+        for (i, arg) in args.iter().enumerate() {
+            text = text.replace(&format!("{{{}}}", i), &arg.to_string());
+        }
+        text
+    }
+
+    pub fn error(&self, e: &Error) -> String {
+        match e {
+            Error::InvalidCard(s) => self.format("error-invalid-card", &[s]),
+            Error::InvalidHand(s) => self.format("error-invalid-hand", &[s]),
+            Error::InvalidBoard(s) => self.format("error-invalid-board", &[s]),
+            Error::DuplicateCard(s) => self.format("error-duplicate-card", &[s]),
+            Error::PlayerCount(n) => self.format("error-player-count", &[n]),
+            Error::InvalidRange(s) => self.format("error-invalid-range", &[s]),
+            Error::EmptyRange => self.text("error-empty-range"),
+            Error::InvalidHistory(s) => self.format("error-invalid-history", &[s]),
+            Error::InvalidQuery(s) => self.format("error-invalid-query", &[s]),
+        }
+    }
+}
@@ -1,75 +1,1282 @@
+mod i18n;
+mod session;
+
 use eframe::egui;
-use poker_odds_backend::solve;
+use i18n::Locales;
+use poker_odds_backend::{
+    exact_runouts, parse_scenario, runout_shifts, starting_hands, BluffCheck, Deck,
+    EquityDistribution, Error, PreflopChart, Range, RangeMatrix, RangePresets, Shift, Solution,
+    SolveMode, SolveSize, Solver, Street, Tally,
+};
+use poker_odds_core::{Card, Suits};
+use session::{Session, SESSION_FILE};
 
 fn main() -> eframe::Result {
     env_logger::init();
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([720.0, 480.0]),
+        // transparent so the overlay can drop its background.
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size(WINDOW_SIZE)
+            .with_transparent(true),
         ..Default::default()
     };
     eframe::run_native(
         "NLH Poker Equity Calculator",
         options,
-        Box::new(|_| Ok(Box::<MyApp>::default())),
+        Box::new(|_| {
+            // saved by `poker-odds-backend ranges`, none if there's no file.
+            let presets: RangePresets = std::fs::read_to_string(PRESETS_FILE)
+                .ok()
+                .and_then(|text| RangePresets::parse(&text).ok())
+                .unwrap_or_default();
+            Ok(Box::new(MyApp {
+                presets,
+                locales: Locales::load(),
+                ..MyApp::default()
+            }))
+        }),
     )
 }
 
+// how many of the best and worst next cards to show.
+const SWING_CARDS: usize = 5;
+
+// the named ranges offered for the opponent, in the working directory.
+const PRESETS_FILE: &str = "ranges.txt";
+
+// monte carlo runouts per hand of the preflop chart.
+const CHART_SAMPLES: usize = 10_000;
+// monte carlo runouts of a range against range heatmap preflop.
+const MATRIX_SAMPLES: usize = 20_000;
+
+const WINDOW_SIZE: [f32; 2] = [720.0, 480.0];
+// just big enough for the matchup and its equity, for capturing in a stream.
+const OVERLAY_SIZE: [f32; 2] = [360.0, 90.0];
+
+// the player table scrolls past this height, about six seats.
+const TABLE_HEIGHT: f32 = 180.0;
+
+// a line a player in the equity chart, the hero's first.
+const PLAYER_COLORS: [egui::Color32; 10] = [
+    egui::Color32::from_rgb(66, 135, 245),
+    egui::Color32::from_rgb(230, 70, 60),
+    egui::Color32::from_rgb(60, 180, 90),
+    egui::Color32::from_rgb(240, 170, 30),
+    egui::Color32::from_rgb(160, 90, 210),
+    egui::Color32::from_rgb(40, 190, 200),
+    egui::Color32::from_rgb(230, 110, 180),
+    egui::Color32::from_rgb(140, 110, 70),
+    egui::Color32::from_rgb(150, 150, 150),
+    egui::Color32::from_rgb(190, 200, 50),
+];
+
+const CHART_HEIGHT: f32 = 160.0;
+
+// exact solves with more runouts than this, e.g. preflop with four players
+// or fewer, suggest monte carlo.
+const SLOW_RUNOUTS: u64 = 1_000_000;
+
+// where Export image saves the scenario, in the working directory.
+const EXPORT_FILE: &str = "scenario.png";
+
+// the text boxes up and down move between, by hand and then the board.
+fn card_field(i: usize) -> egui::Id {
+    egui::Id::new(("card field", i))
+}
+
 struct MyApp {
+    locales: Locales,
+    // kept between solves so solving a spot again comes from its cache.
+    solver: Solver,
     nplayers: usize,
     board: String,
     equity: Option<f32>,
+    // monte carlo solves deal `samples` runouts, and their equity comes with
+    // a 95% confidence interval.
+    monte_carlo: bool,
+    samples: usize,
+    // the size of an exact solve of the hands and board it was worked out
+    // for, timed once per spot rather than every frame.
+    exact_size: Option<(Vec<String>, String, SolveSize)>,
+    // what the equity was worked out from, shown on hovering it.
+    solution: Option<Solution>,
+    solve_error: Option<Error>,
+    // multiway, the hero's equity against each opponent alone.
+    head_to_head: Vec<(String, f32)>,
+    // the equity after each card that can come next, on the flop and turn.
+    next_cards: Option<EquityDistribution>,
+    // the next cards that change the nuts or complete a draw.
+    shifts: Vec<(String, Vec<Shift>)>,
     hands: Vec<String>,
+    // by seat, players unchecked in the table are left out of the solve.
+    // the hero is always in.
+    enabled: Vec<bool>,
+    villain_range: String,
+    presets: RangePresets,
+    pot: f32,
+    bet: f32,
+    bluff: Option<Result<BluffCheck, Error>>,
+    // a borderless, always on top window showing only the matchup and its
+    // equity over a transparent background.
+    overlay: bool,
+    // a hand history or "AhKh vs QsQd on 7c8c9c" to fill the hands and board
+    // from, and why it couldn't be read.
+    paste: String,
+    paste_error: Option<Error>,
+    // the preflop chart window, against random hands or the opponent range.
+    chart_open: bool,
+    chart_opponents: usize,
+    chart_vs_range: bool,
+    chart: Option<Result<PreflopChart, Error>>,
+    // the hero range's heatmap against the opponent range on the board.
+    matrix_hero: String,
+    matrix: Option<Result<RangeMatrix, Error>>,
+    // the hand replayed street by street, and the street being shown.
+    replay: Option<Result<Vec<Street>, Error>>,
+    replay_hands: Vec<String>,
+    replay_step: usize,
+    // the file sessions are saved to and loaded from, and why that failed.
+    session_path: String,
+    session_error: Option<String>,
+    // clubs in green and diamonds in blue, so every suit has its own color.
+    four_color: bool,
+    // the scenario being drawn to be captured as an image, and how the last
+    // export went.
+    export: Option<Export>,
+    export_status: Option<Result<(), String>>,
+}
+
+struct Export {
+    hands: Vec<String>,
+    // by player, on the board as it is.
+    equities: Vec<f32>,
+    // where the scenario was drawn, known after its first frame.
+    rect: Option<egui::Rect>,
+    requested: bool,
 }
 
 impl Default for MyApp {
     fn default() -> Self {
         Self {
+            locales: Locales::default(),
+            solver: Solver::new(),
             nplayers: 2,
             board: "".to_string(),
             equity: None,
+            monte_carlo: false,
+            samples: 100_000,
+            exact_size: None,
+            solution: None,
+            solve_error: None,
+            head_to_head: Vec::new(),
+            next_cards: None,
+            shifts: Vec::new(),
             hands: Vec::from(["".to_string(), "".to_string()]),
+            enabled: vec![true; 2],
+            villain_range: "".to_string(),
+            presets: RangePresets::default(),
+            pot: 100.,
+            bet: 75.,
+            bluff: None,
+            overlay: false,
+            paste: "".to_string(),
+            paste_error: None,
+            chart_open: false,
+            chart_opponents: 1,
+            chart_vs_range: false,
+            chart: None,
+            matrix_hero: "".to_string(),
+            matrix: None,
+            replay: None,
+            replay_hands: Vec::new(),
+            replay_step: 0,
+            session_path: SESSION_FILE.to_string(),
+            session_error: None,
+            four_color: false,
+            export: None,
+            export_status: None,
         }
     }
 }
 
-impl eframe::App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("NLH Poker Equity Calculator");
-            ui.add(egui::Slider::new(&mut self.nplayers, 2..=10).text("# players"));
+impl MyApp {
+    fn import_paste(&mut self) {
+        match parse_scenario(&self.paste) {
+            Ok((mut hands, board)) => {
+                hands.truncate(10);
+                self.nplayers = hands.len().max(2);
+                self.enabled = vec![true; hands.len()];
+                self.hands = hands;
+                self.board = board;
+                self.equity = None;
+                self.solution = None;
+                self.head_to_head.clear();
+                self.next_cards = None;
+                self.shifts.clear();
+                self.paste_error = None;
+            }
+            Err(e) => self.paste_error = Some(e),
+        }
+    }
 
-            while self.hands.len() < self.nplayers {
-                self.hands.push("".to_string());
+    // the hands of the players in the table, the hero's first.
+    fn active_hands(&self) -> Vec<String> {
+        self.hands
+            .iter()
+            .zip(self.enabled.iter())
+            .filter(|(_, &enabled)| enabled)
+            .map(|(hand, _)| hand.clone())
+            .collect()
+    }
+
+    fn solve_hands(&mut self) {
+        let hands: Vec<String> = self.active_hands();
+        let mode: SolveMode = match self.monte_carlo {
+            true => SolveMode::MonteCarlo {
+                samples: self.samples,
+            },
+            false => SolveMode::Exact,
+        };
+        let solution = self
+            .solver
+            .try_solve_with_progress(&hands, &self.board, mode, |_| {});
+        self.equity = solution.as_ref().ok().map(|s| s.equity);
+        (self.solution, self.solve_error) = match solution {
+            Ok(solution) => (Some(solution), None),
+            Err(e) => (None, Some(e)),
+        };
+        self.head_to_head = match hands.len() > 2 && self.solve_error.is_none() {
+            true => self
+                .solver
+                .head_to_head(&hands, &self.board)
+                .map(|equities| hands[1..].iter().cloned().zip(equities).collect())
+                .unwrap_or_default(),
+            false => Vec::new(),
+        };
+        // preflop that would be another full solve.
+        let cards: usize = self.board.chars().filter(|c| !c.is_whitespace()).count() / 2;
+        self.next_cards = match cards {
+            3 | 4 => self.solver.distribution(&hands, &self.board).ok(),
+            _ => None,
+        };
+        // cards in the hands can't come.
+        self.shifts = match &self.next_cards {
+            Some(next_cards) => runout_shifts(&self.board)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(card, _)| next_cards.by_card.iter().any(|(c, _)| c == card))
+                .collect(),
+            None => Vec::new(),
+        };
+    }
+
+    // a deck without the cards already in the hands and on the board.
+    fn deck(&self) -> Result<Deck, Error> {
+        let mut deck: Deck = Deck::new(None);
+        for cards in self.hands.iter().chain(std::iter::once(&self.board)) {
+            deck.remove(cards)?;
+        }
+        Ok(deck)
+    }
+
+    // fills every empty hand with random cards.
+    fn deal_hands(&mut self) {
+        match self.deck() {
+            Ok(mut deck) => {
+                for hand in self.hands.iter_mut().filter(|h| h.trim().is_empty()) {
+                    *hand = deck.deal(2).unwrap_or_default();
+                }
+                self.solve_error = None;
+            }
+            Err(e) => self.solve_error = Some(e),
+        }
+    }
+
+    // deals the flop, the turn or the river, whichever comes next.
+    fn deal_street(&mut self) {
+        let cards: usize = self.board.chars().filter(|c| !c.is_whitespace()).count() / 2;
+        let need: usize = match cards {
+            0 => 3,
+            1..=4 => 1,
+            _ => return,
+        };
+        match self.deck() {
+            Ok(mut deck) => {
+                self.board = self.board.split_whitespace().collect();
+                self.board += &deck.deal(need).unwrap_or_default();
+                self.solve_error = None;
             }
-            while self.hands.len() > self.nplayers {
-                self.hands.pop();
+            Err(e) => self.solve_error = Some(e),
+        }
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            hands: self
+                .hands
+                .iter()
+                .cloned()
+                .zip(self.enabled.iter().copied())
+                .collect(),
+            board: self.board.clone(),
+            villain_range: self.villain_range.clone(),
+            pot: self.pot,
+            bet: self.bet,
+            samples: self.monte_carlo.then_some(self.samples),
+            solved: self.equity.is_some(),
+        }
+    }
+
+    fn save_session(&mut self) {
+        let saved = std::fs::write(&self.session_path, self.session().to_string());
+        self.session_error = saved.err().map(|e| e.to_string());
+    }
+
+    fn load_session(&mut self) {
+        let loaded: Result<Session, String> = std::fs::read_to_string(&self.session_path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Session::parse(&text));
+        let session: Session = match loaded {
+            Ok(session) => session,
+            Err(e) => {
+                self.session_error = Some(e);
+                return;
+            }
+        };
+        self.clear();
+        self.session_error = None;
+        let (mut hands, mut enabled): (Vec<String>, Vec<bool>) =
+            session.hands.into_iter().take(10).unzip();
+        let seats: usize = hands.len().max(2);
+        hands.resize(seats, "".to_string());
+        enabled.resize(seats, true);
+        // the hero is always in.
+        enabled[0] = true;
+        self.nplayers = hands.len();
+        self.hands = hands;
+        self.enabled = enabled;
+        self.board = session.board;
+        self.villain_range = session.villain_range;
+        self.pot = session.pot;
+        self.bet = session.bet;
+        self.monte_carlo = session.samples.is_some();
+        self.samples = session.samples.unwrap_or(self.samples);
+        if session.solved {
+            self.solve_hands();
+        }
+    }
+
+    fn start_export(&mut self) {
+        let hands: Vec<String> = self.active_hands();
+        match self.solver.replay(&hands, &self.board) {
+            Ok(mut streets) => {
+                let equities: Vec<f32> = streets.pop().map(|s| s.equities).unwrap_or_default();
+                self.export = Some(Export {
+                    hands,
+                    equities,
+                    rect: None,
+                    requested: false,
+                });
+                self.export_status = None;
             }
+            Err(e) => self.solve_error = Some(e),
+        }
+    }
+
+    // draws the scenario over the window and, once it's on screen, asks for
+    // a screenshot to cut it out of.
+    fn show_export(&mut self, ctx: &egui::Context) {
+        let shot: Option<std::sync::Arc<egui::ColorImage>> = ctx.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        let Some(export) = &mut self.export else {
+            return;
+        };
+        if let (Some(image), Some(rect)) = (shot, export.rect) {
+            let image: egui::ColorImage = image.region(&rect, Some(ctx.pixels_per_point()));
+            self.export_status = Some(save_png(EXPORT_FILE, &image).map_err(|e| e.to_string()));
+            self.export = None;
+            return;
+        }
+
+        let (board, locales, four_color) = (self.board.as_str(), &self.locales, self.four_color);
+        let response = egui::Area::new(egui::Id::new("export"))
+            .fixed_pos(egui::pos2(16., 16.))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    scenario_card(
+                        ui,
+                        &export.hands,
+                        board,
+                        &export.equities,
+                        locales,
+                        four_color,
+                    )
+                });
+            })
+            .response;
+        if export.rect.is_some() && !export.requested {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+            export.requested = true;
+        }
+        export.rect = Some(response.rect);
+        ctx.request_repaint();
+    }
+
+    fn clear(&mut self) {
+        self.hands.iter_mut().for_each(String::clear);
+        self.board.clear();
+        self.equity = None;
+        self.solution = None;
+        self.solve_error = None;
+        self.head_to_head.clear();
+        self.next_cards = None;
+        self.shifts.clear();
+        self.replay = None;
+        self.paste_error = None;
+    }
+
+    // enter solves, escape clears, ctrl + and ctrl - add and remove a
+    // player, and up and down move between the hands and the board. enter
+    // still starts a new line in the paste box.
+    fn shortcuts(&mut self, ctx: &egui::Context) {
+        let key = |modifiers: egui::Modifiers, key: egui::Key| {
+            ctx.input_mut(|i| i.consume_key(modifiers, key))
+        };
+        let focused: Option<egui::Id> = ctx.memory(|m| m.focused());
+        let fields: usize = self.nplayers + 1;
+        let field: Option<usize> = (0..fields).find(|&i| focused == Some(card_field(i)));
+
+        if (focused.is_none() || field.is_some()) && key(egui::Modifiers::NONE, egui::Key::Enter) {
+            self.solve_hands();
+        }
+        if key(egui::Modifiers::NONE, egui::Key::Escape) {
+            self.clear();
+        }
+        if key(egui::Modifiers::COMMAND, egui::Key::Plus)
+            || key(egui::Modifiers::COMMAND, egui::Key::Equals)
+        {
+            self.nplayers = (self.nplayers + 1).min(10);
+        }
+        if key(egui::Modifiers::COMMAND, egui::Key::Minus) {
+            self.nplayers = (self.nplayers - 1).max(2);
+        }
+
+        let step: Option<usize> = if key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+            Some(field.map_or(0, |f| (f + 1) % fields))
+        } else if key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+            Some(field.map_or(fields - 1, |f| (f + fields - 1) % fields))
+        } else {
+            None
+        };
+        if let Some(next) = step.filter(|_| focused.is_none() || field.is_some()) {
+            ctx.memory_mut(|m| m.request_focus(card_field(next)));
+        }
+    }
+
+    fn set_overlay(&mut self, ctx: &egui::Context, overlay: bool) {
+        self.overlay = overlay;
+        let (level, size) = match overlay {
+            true => (egui::WindowLevel::AlwaysOnTop, OVERLAY_SIZE),
+            false => (egui::WindowLevel::Normal, WINDOW_SIZE),
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(!overlay));
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size.into()));
+    }
+
+    fn show_chart(&mut self, ctx: &egui::Context) {
+        let mut open: bool = self.chart_open;
+        let tr = |key: &str| self.locales.text(key);
+        egui::Window::new(tr("preflop-chart"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.chart_opponents, 1..=9).text(tr("chart-opponents")),
+                );
+                ui.checkbox(&mut self.chart_vs_range, tr("chart-vs-range"));
+                if ui.button(tr("compute")).clicked() {
+                    let villain: Option<Result<Range, _>> = self
+                        .chart_vs_range
+                        .then(|| self.presets.resolve(&self.villain_range));
+                    self.chart = Some(villain.transpose().and_then(|villain| {
+                        PreflopChart::new(self.chart_opponents, villain.as_ref(), CHART_SAMPLES, 0)
+                    }));
+                }
+                match &self.chart {
+                    Some(Ok(chart)) => chart_grid(ui, chart, &self.locales),
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::RED, self.locales.error(e));
+                    }
+                    None => {}
+                }
 
-            for i in 0..self.nplayers {
+                ui.separator();
                 ui.horizontal(|ui| {
-                    let label = if i == 0 {
-                        "Your Hand: "
-                    } else {
-                        "Opponent Hand: "
+                    ui.label(tr("matrix-hero"));
+                    ui.text_edit_singleline(&mut self.matrix_hero);
+                });
+                if ui.button(tr("matrix-compute")).clicked() {
+                    // every runout preflop is too many to walk for a range.
+                    let mode: SolveMode = match self.board.trim().is_empty() {
+                        true => SolveMode::MonteCarlo {
+                            samples: MATRIX_SAMPLES,
+                        },
+                        false => SolveMode::Exact,
                     };
-                    let name_label = ui.label(label);
-                    ui.text_edit_singleline(&mut self.hands[i])
-                        .labelled_by(name_label.id);
+                    let solver: Solver = Solver::builder().mode(mode).seed(0).build();
+                    self.matrix = Some(self.presets.resolve(&self.matrix_hero).and_then(|hero| {
+                        let villain: Range = self.presets.resolve(&self.villain_range)?;
+                        solver.range_matrix(&hero, &villain, &self.board)
+                    }));
+                }
+                match &self.matrix {
+                    Some(Ok(matrix)) => matrix_grid(ui, matrix, &self.locales),
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::RED, self.locales.error(e));
+                    }
+                    None => {}
+                }
+            });
+        self.chart_open = open;
+    }
+
+    // the overlay is dragged around by any part of it, and a double click
+    // brings the full window back.
+    fn show_overlay(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                let white = |text: String| egui::RichText::new(text).color(egui::Color32::WHITE);
+                ui.horizontal(|ui| {
+                    for (i, hand) in self.active_hands().iter().enumerate() {
+                        if i > 0 {
+                            ui.label(white("vs".to_string()));
+                        }
+                        card_chips(ui, hand, &self.locales, self.four_color);
+                    }
+                    if !self.board.trim().is_empty() {
+                        ui.label(white(self.locales.text("overlay-on")));
+                        card_chips(ui, &self.board, &self.locales, self.four_color);
+                    }
                 });
+                let equity: String = match self.equity {
+                    Some(equity) => format!("{:.1}%", equity * 100.),
+                    None => "-".to_string(),
+                };
+                ui.label(
+                    egui::RichText::new(equity)
+                        .color(egui::Color32::WHITE)
+                        .size(36.)
+                        .strong(),
+                );
+
+                let response = ui.interact(
+                    ui.max_rect(),
+                    egui::Id::new("overlay"),
+                    egui::Sense::click_and_drag(),
+                );
+                if response.double_clicked() {
+                    self.set_overlay(ctx, false);
+                } else if response.drag_started() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+            });
+    }
+}
+
+impl eframe::App for MyApp {
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        match self.overlay {
+            true => egui::Rgba::TRANSPARENT.to_array(),
+            false => visuals.panel_fill.to_normalized_gamma_f32(),
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.overlay {
+            self.show_overlay(ctx);
+            return;
+        }
+        if self.chart_open {
+            self.show_chart(ctx);
+        }
+        self.shortcuts(ctx);
+        self.show_export(ctx);
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(self.locales.text("title"));
+                let current: String = self.locales.all()[self.locales.current].name().to_string();
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(current)
+                    .show_ui(ui, |ui| {
+                        for i in 0..self.locales.all().len() {
+                            let name: String = self.locales.all()[i].name().to_string();
+                            ui.selectable_value(&mut self.locales.current, i, name);
+                        }
+                    })
+                    .response
+                    .on_hover_text(self.locales.text("language-label"))
+                    .widget_info(|| {
+                        let label: String = self.locales.text("language-label");
+                        egui::WidgetInfo::labeled(egui::WidgetType::ComboBox, true, label)
+                    });
+            });
+            ui.horizontal(|ui| {
+                let name_label = ui.label(self.locales.text("session"));
+                ui.add(egui::TextEdit::singleline(&mut self.session_path).desired_width(160.))
+                    .labelled_by(name_label.id);
+                if ui.button(self.locales.text("session-save")).clicked() {
+                    self.save_session();
+                }
+                if ui.button(self.locales.text("session-load")).clicked() {
+                    self.load_session();
+                }
+            });
+            if let Some(e) = &self.session_error {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    self.locales
+                        .format("session-error", &[&self.session_path, e]),
+                );
             }
 
+            // ctrl+v outside a text box pastes a hand straight in.
+            let pasted: Option<String> = ctx.input(|i| {
+                i.events.iter().find_map(|e| match e {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            });
+            if let Some(text) = pasted.filter(|_| ctx.memory(|m| m.focused().is_none())) {
+                self.paste = text;
+                self.import_paste();
+            }
             ui.horizontal(|ui| {
-                let name_label = ui.label("Board: ");
-                ui.text_edit_singleline(&mut self.board)
+                let name_label = ui.label(self.locales.text("paste"));
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.paste)
+                        .desired_rows(1)
+                        .hint_text(self.locales.text("paste-hint")),
+                )
+                .labelled_by(name_label.id);
+                if ui.button(self.locales.text("paste-hand")).clicked() {
+                    self.import_paste();
+                }
+            });
+            if let Some(e) = &self.paste_error {
+                ui.colored_label(egui::Color32::RED, self.locales.error(e));
+            }
+            ui.add(
+                egui::Slider::new(&mut self.nplayers, 2..=10).text(self.locales.text("players")),
+            );
+
+            self.hands.resize(self.nplayers, "".to_string());
+            self.enabled.resize(self.nplayers, true);
+
+            // a row a seat: its toggle, its hand and the hand's cards.
+            egui::ScrollArea::vertical()
+                .max_height(TABLE_HEIGHT)
+                .show(ui, |ui| {
+                    egui::Grid::new("players").striped(true).show(ui, |ui| {
+                        for i in 0..self.nplayers {
+                            let label: String = match i {
+                                0 => self.locales.text("your-hand"),
+                                _ => self.locales.format("opponent-hand", &[&i]),
+                            };
+                            let name_label = ui.label(label);
+                            ui.add_enabled(
+                                i > 0,
+                                egui::Checkbox::without_text(&mut self.enabled[i]),
+                            )
+                            .on_hover_text(self.locales.text("player-enabled"))
+                            .labelled_by(name_label.id);
+                            ui.add_enabled(
+                                self.enabled[i],
+                                egui::TextEdit::singleline(&mut self.hands[i])
+                                    .id(card_field(i))
+                                    .desired_width(80.),
+                            )
+                            .labelled_by(name_label.id);
+                            ui.horizontal(|ui| {
+                                card_chips(ui, &self.hands[i], &self.locales, self.four_color)
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+
+            ui.horizontal(|ui| {
+                let name_label = ui.label(self.locales.text("board"));
+                ui.add(egui::TextEdit::singleline(&mut self.board).id(card_field(self.nplayers)))
                     .labelled_by(name_label.id);
+                card_chips(ui, &self.board, &self.locales, self.four_color);
+                ui.checkbox(&mut self.four_color, self.locales.text("four-color"));
+            });
+
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut self.monte_carlo,
+                    false,
+                    self.locales.text("mode-exact"),
+                )
+                .on_hover_text(self.locales.text("mode-hint"));
+                ui.radio_value(
+                    &mut self.monte_carlo,
+                    true,
+                    self.locales.text("mode-monte-carlo"),
+                )
+                .on_hover_text(self.locales.text("mode-hint"));
+                if self.monte_carlo {
+                    let name_label = ui.label(self.locales.text("samples"));
+                    ui.add(egui::DragValue::new(&mut self.samples).range(1_000..=10_000_000))
+                        .labelled_by(name_label.id);
+                }
             });
+            let cards: usize = self.board.chars().filter(|c| !c.is_whitespace()).count() / 2;
+            let runouts: u64 = exact_runouts(self.active_hands().len(), cards);
+            if !self.monte_carlo && runouts > SLOW_RUNOUTS {
+                let (hands, board) = (self.active_hands(), self.board.clone());
+                let stale: bool = match &self.exact_size {
+                    Some((h, b, _)) => *h != hands || *b != board,
+                    None => true,
+                };
+                if stale {
+                    self.exact_size = self
+                        .solver
+                        .exact_size(&hands, &board)
+                        .ok()
+                        .map(|size| (hands, board, size));
+                }
+                let eta: String = match &self.exact_size {
+                    Some((_, _, size)) => format!("{:.1?}", size.eta),
+                    None => "?".to_string(),
+                };
+                ui.horizontal(|ui| {
+                    ui.label(self.locales.format("mode-suggest", &[&runouts, &eta]));
+                    if ui.button(self.locales.text("mode-monte-carlo")).clicked() {
+                        self.monte_carlo = true;
+                    }
+                });
+            }
 
-            if ui.button("Solve").clicked() {
-                self.equity = Some(solve(&self.hands, &self.board));
+            let (mut solve_clicked, mut overlay, mut replay): (bool, bool, bool) =
+                (false, false, false);
+            ui.horizontal(|ui| {
+                if ui
+                    .button(self.locales.text("deal-hands"))
+                    .on_hover_text(self.locales.text("deal-hands-hint"))
+                    .clicked()
+                {
+                    self.deal_hands();
+                }
+                if ui
+                    .button(self.locales.text("deal-street"))
+                    .on_hover_text(self.locales.text("deal-street-hint"))
+                    .clicked()
+                {
+                    self.deal_street();
+                }
+                solve_clicked = ui
+                    .button(self.locales.text("solve"))
+                    .on_hover_text(self.locales.text("shortcuts"))
+                    .clicked();
+                replay = ui
+                    .button(self.locales.text("replay"))
+                    .on_hover_text(self.locales.text("replay-hint"))
+                    .clicked();
+                overlay = ui
+                    .add_enabled(
+                        self.equity.is_some(),
+                        egui::Button::new(self.locales.text("overlay")),
+                    )
+                    .on_hover_text(self.locales.text("overlay-hint"))
+                    .clicked();
+                if ui
+                    .add_enabled(
+                        self.equity.is_some() && self.export.is_none(),
+                        egui::Button::new(self.locales.text("export")),
+                    )
+                    .on_hover_text(self.locales.format("export-hint", &[&EXPORT_FILE]))
+                    .clicked()
+                {
+                    self.start_export();
+                }
+            });
+            match &self.export_status {
+                Some(Ok(())) => {
+                    ui.label(self.locales.format("export-saved", &[&EXPORT_FILE]));
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        self.locales.format("export-error", &[&EXPORT_FILE, e]),
+                    );
+                }
+                None => {}
+            }
+            if overlay {
+                self.set_overlay(ctx, true);
+            }
+            if solve_clicked {
+                self.solve_hands();
+            }
+            if replay {
+                self.replay_hands = self.active_hands();
+                self.replay = Some(self.solver.replay(&self.replay_hands, &self.board));
+                self.replay_step = 0;
             }
             if let Some(equity) = self.equity {
-                ui.label(format!("Your hand's equity is: {:?}", equity));
+                let label = ui.label(self.locales.format("equity", &[&format!("{:?}", equity)]));
+                if let Some(solution) = &self.solution {
+                    label.on_hover_text(solution_details(solution, &self.locales));
+                }
+            }
+            if let Some((low, high)) = self.solution.as_ref().and_then(Solution::confidence) {
+                ui.label(self.locales.format(
+                    "confidence",
+                    &[
+                        &format!("{:.1}", low * 100.),
+                        &format!("{:.1}", high * 100.),
+                    ],
+                ));
+            }
+            for (hand, equity) in self.head_to_head.iter() {
+                ui.horizontal(|ui| {
+                    ui.label(self.locales.text("head-to-head"));
+                    card_chips(ui, hand, &self.locales, self.four_color);
+                    ui.label(
+                        self.locales
+                            .format("head-to-head-equity", &[&format!("{:.1}", equity * 100.)]),
+                    );
+                });
+            }
+            if let Some(e) = &self.solve_error {
+                ui.colored_label(egui::Color32::RED, self.locales.error(e));
+            }
+            match &self.replay {
+                Some(Ok(streets)) => {
+                    let last: usize = streets.len() - 1;
+                    let street: &Street = &streets[self.replay_step.min(last)];
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(self.replay_step > 0, egui::Button::new("<"))
+                            .clicked()
+                        {
+                            self.replay_step -= 1;
+                        }
+                        ui.label(
+                            self.locales
+                                .text(&format!("street-{}", street.name.to_lowercase())),
+                        );
+                        card_chips(ui, &street.board, &self.locales, self.four_color);
+                        ui.label(format!("{:.1}%", street.equities[0] * 100.));
+                        if ui
+                            .add_enabled(self.replay_step < last, egui::Button::new(">"))
+                            .clicked()
+                        {
+                            self.replay_step += 1;
+                        }
+                    });
+                    equity_chart(
+                        ui,
+                        streets,
+                        &self.replay_hands,
+                        self.replay_step,
+                        &self.locales,
+                        self.four_color,
+                    );
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(egui::Color32::RED, self.locales.error(e));
+                }
+                None => {}
+            }
+            if let Some(next_cards) = &self.next_cards {
+                // the cards that change the nuts or complete a draw are starred.
+                for (key, color, cards) in [
+                    (
+                        "best-cards",
+                        egui::Color32::GREEN,
+                        next_cards.best(SWING_CARDS),
+                    ),
+                    (
+                        "worst-cards",
+                        egui::Color32::RED,
+                        next_cards.worst(SWING_CARDS),
+                    ),
+                ] {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(color, self.locales.text(key));
+                        for (card, swing) in cards.iter() {
+                            card_chips(ui, card, &self.locales, self.four_color);
+                            let star: &str = match self.shifts.iter().any(|(c, _)| c == card) {
+                                true => "*",
+                                false => "",
+                            };
+                            ui.colored_label(color, format!("{}{:+.1}%", star, swing * 100.));
+                        }
+                    });
+                }
+                for (shift, key) in [
+                    (Shift::Nuts, "shift-nuts"),
+                    (Shift::Flush, "shift-flush"),
+                    (Shift::Straight, "shift-straight"),
+                ] {
+                    let cards: Vec<&str> = self
+                        .shifts
+                        .iter()
+                        .filter(|(_, shifts)| shifts.contains(&shift))
+                        .map(|(card, _)| card.as_str())
+                        .collect();
+                    if !cards.is_empty() {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.colored_label(egui::Color32::YELLOW, self.locales.text(key));
+                            for card in cards {
+                                card_chips(ui, card, &self.locales, self.four_color);
+                            }
+                        });
+                    }
+                }
+            }
+
+            if ui.button(self.locales.text("preflop-chart")).clicked() {
+                self.chart_open = true;
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let name_label = ui.label(self.locales.text("opponent-range"));
+                ui.text_edit_singleline(&mut self.villain_range)
+                    .labelled_by(name_label.id);
+                if self.presets.names().next().is_some() {
+                    egui::ComboBox::from_id_salt("presets")
+                        .selected_text(self.locales.text("presets"))
+                        .show_ui(ui, |ui| {
+                            for name in self.presets.names() {
+                                if ui.selectable_label(false, name).clicked() {
+                                    if let Some(range) = self.presets.get(name) {
+                                        self.villain_range = range.to_string();
+                                    }
+                                }
+                            }
+                        })
+                        .response
+                        .widget_info(|| {
+                            let label: String = self.locales.text("presets");
+                            egui::WidgetInfo::labeled(egui::WidgetType::ComboBox, true, label)
+                        });
+                }
+            });
+            ui.horizontal(|ui| {
+                let pot_label = ui.label(self.locales.text("pot"));
+                ui.add(egui::DragValue::new(&mut self.pot).range(0.0..=f32::MAX))
+                    .labelled_by(pot_label.id);
+                let bet_label = ui.label(self.locales.text("bet"));
+                ui.add(egui::DragValue::new(&mut self.bet).range(0.0..=f32::MAX))
+                    .labelled_by(bet_label.id);
+            });
+            if ui.button(self.locales.text("check-bluff")).clicked() {
+                self.bluff = Some(Range::parse(&self.villain_range).and_then(|range| {
+                    Solver::new().bluff_check(
+                        &self.hands[0],
+                        &range,
+                        &self.board,
+                        self.pot,
+                        self.bet,
+                        None,
+                    )
+                }));
+            }
+            match &self.bluff {
+                Some(Ok(check)) => {
+                    ui.label(self.locales.format(
+                        "bluff",
+                        &[
+                            &format!("{:.1}", check.break_even * 100.),
+                            &format!("{:.1}", check.folds() * 100.),
+                        ],
+                    ));
+                }
+                Some(Err(e)) => {
+                    ui.label(self.locales.error(e));
+                }
+                None => {}
             }
         });
     }
 }
+
+// the hands as cards with a bar of their equity each, and the board, for
+// Export image.
+fn scenario_card(
+    ui: &mut egui::Ui,
+    hands: &[String],
+    board: &str,
+    equities: &[f32],
+    locales: &Locales,
+    four_color: bool,
+) {
+    egui::Grid::new("scenario")
+        .spacing([8., 6.])
+        .show(ui, |ui| {
+            for ((hand, equity), color) in
+                hands.iter().zip(equities).zip(PLAYER_COLORS.iter().cycle())
+            {
+                ui.horizontal(|ui| card_chips(ui, hand, locales, four_color));
+                ui.add(
+                    egui::ProgressBar::new(*equity)
+                        .desired_width(160.)
+                        .fill(*color)
+                        .text(format!("{:.1}%", equity * 100.)),
+                );
+                ui.end_row();
+            }
+        });
+    if !board.trim().is_empty() {
+        ui.horizontal(|ui| card_chips(ui, board, locales, four_color));
+    }
+}
+
+fn save_png(path: &str, image: &egui::ColorImage) -> Result<(), png::EncodingError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(image.as_raw())
+}
+
+// the cards typed in `cards` as small white chips, hearts and diamonds in
+// red, or each suit in its own color with `four_color`. screen readers read
+// them out as e.g. "ace of hearts". anything that isn't a card is left out.
+fn card_chips(ui: &mut egui::Ui, cards: &str, locales: &Locales, four_color: bool) {
+    let cards: Vec<char> = cards.chars().filter(|c| !c.is_whitespace()).collect();
+    for card in cards.chunks_exact(2) {
+        let (rank, letter) = (card[0].to_ascii_uppercase(), card[1].to_ascii_lowercase());
+        let Some(card) = Card::parse(&format!("{}{}", rank, letter)) else {
+            continue;
+        };
+        let (suit, color) = match card.suit {
+            Suits::Clubs if four_color => ('♣', egui::Color32::from_rgb(0, 140, 60)),
+            Suits::Clubs => ('♣', egui::Color32::BLACK),
+            Suits::Hearts => ('♥', egui::Color32::RED),
+            Suits::Spades => ('♠', egui::Color32::BLACK),
+            Suits::Diamonds if four_color => ('♦', egui::Color32::from_rgb(20, 90, 220)),
+            Suits::Diamonds => ('♦', egui::Color32::RED),
+        };
+        egui::Frame::new()
+            .fill(egui::Color32::WHITE)
+            .corner_radius(3.)
+            .inner_margin(egui::Margin::symmetric(3, 1))
+            .show(ui, |ui| {
+                let name: String = locales.format(
+                    "card",
+                    &[
+                        &locales.text(&format!("rank-{}", rank)),
+                        &locales.text(&format!("suit-{}", letter)),
+                    ],
+                );
+                ui.label(
+                    egui::RichText::new(format!("{}{}", rank, suit))
+                        .color(color)
+                        .monospace(),
+                )
+                .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, &name));
+            });
+    }
+}
+
+// how the equity of `solution` breaks down into pots won and split, the
+// work that went into it, and how sure a monte carlo one is.
+fn solution_details(solution: &Solution, locales: &Locales) -> String {
+    let tally: &Tally = &solution.tally;
+    let runouts: f64 = tally.runouts.max(1) as f64;
+    let percent = |share: f64| format!("{:.2}", share * 100.);
+    let mut lines: Vec<String> = vec![
+        locales.format("details-wins", &[&percent(tally.wins as f64 / runouts)]),
+        locales.format(
+            "details-ties",
+            &[&percent(
+                tally.ties as f64 / (runouts * Tally::SHARE as f64),
+            )],
+        ),
+        locales.format("details-leaves", &[&solution.stats.leaves]),
+        locales.format(
+            "details-time",
+            &[&format!("{:.0?}", solution.stats.elapsed)],
+        ),
+    ];
+    if solution.stats.cached {
+        lines.push(locales.text("details-cached"));
+    }
+    // exact tallies count every order the cards can come in, only the
+    // runouts dealt by monte carlo are worth showing.
+    if let Some((low, high)) = solution.confidence() {
+        lines.push(locales.format("details-runouts", &[&tally.runouts]));
+        lines.push(locales.format("confidence", &[&percent(low as f64), &percent(high as f64)]));
+    }
+    lines.join("\n")
+}
+
+// every player's equity as a line from preflop to the last street dealt,
+// the street being replayed marked, and the hands in their colors below.
+fn equity_chart(
+    ui: &mut egui::Ui,
+    streets: &[Street],
+    hands: &[String],
+    step: usize,
+    locales: &Locales,
+    four_color: bool,
+) {
+    let size = egui::vec2(ui.available_width().min(480.), CHART_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    // what the chart shows, for screen readers.
+    let described: Vec<String> = streets
+        .iter()
+        .map(|street| {
+            let name: String = locales.text(&format!("street-{}", street.name.to_lowercase()));
+            let equities: Vec<String> = hands
+                .iter()
+                .zip(street.equities.iter())
+                .map(|(hand, e)| format!("{} {:.1}%", hand, e * 100.))
+                .collect();
+            format!("{}: {}", name, equities.join(", "))
+        })
+        .collect();
+    response.widget_info(|| {
+        egui::WidgetInfo::labeled(egui::WidgetType::Other, true, described.join("; "))
+    });
+    let painter = ui.painter_at(rect);
+    let text = ui.visuals().text_color();
+    let grid = ui.visuals().weak_text_color();
+    let font = egui::FontId::proportional(11.);
+    // room for the percentages on the left and the streets at the bottom.
+    let plot = egui::Rect::from_min_max(
+        rect.min + egui::vec2(34., 6.),
+        rect.max - egui::vec2(10., 18.),
+    );
+    let x = |i: usize| match streets.len() {
+        1 => plot.center().x,
+        n => plot.left() + plot.width() * i as f32 / (n - 1) as f32,
+    };
+    let y = |equity: f32| plot.bottom() - plot.height() * equity;
+
+    for equity in [0., 0.5, 1.] {
+        painter.hline(plot.x_range(), y(equity), (1., grid));
+        painter.text(
+            egui::pos2(plot.left() - 4., y(equity)),
+            egui::Align2::RIGHT_CENTER,
+            format!("{:.0}%", equity * 100.),
+            font.clone(),
+            text,
+        );
+    }
+    for (i, street) in streets.iter().enumerate() {
+        let name: String = locales.text(&format!("street-{}", street.name.to_lowercase()));
+        if i == step.min(streets.len() - 1) {
+            painter.vline(x(i), plot.y_range(), (1., text));
+        }
+        painter.text(
+            egui::pos2(x(i), plot.bottom() + 3.),
+            egui::Align2::CENTER_TOP,
+            name,
+            font.clone(),
+            text,
+        );
+    }
+    for (player, color) in (0..hands.len()).zip(PLAYER_COLORS.iter().cycle()) {
+        let points: Vec<egui::Pos2> = streets
+            .iter()
+            .enumerate()
+            .map(|(i, street)| egui::pos2(x(i), y(street.equities[player])))
+            .collect();
+        for point in points.iter() {
+            painter.circle_filled(*point, 3., *color);
+        }
+        painter.add(egui::Shape::line(points, (2., *color)));
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        for (hand, color) in hands.iter().zip(PLAYER_COLORS.iter().cycle()) {
+            ui.colored_label(*color, "■");
+            card_chips(ui, hand, locales, four_color);
+        }
+    });
+}
+
+fn chart_grid(ui: &mut egui::Ui, chart: &PreflopChart, locales: &Locales) {
+    let against: String = match &chart.villain {
+        Some(range) => locales.format("chart-range", &[&chart.opponents, range]),
+        None => locales.format("chart-random", &[&chart.opponents]),
+    };
+    heat_grid(ui, "chart", &chart.hands, &chart.equity, &against, locales);
+}
+
+fn matrix_grid(ui: &mut egui::Ui, matrix: &RangeMatrix, locales: &Locales) {
+    let board: String = match matrix.board.trim().is_empty() {
+        true => locales.text("street-preflop"),
+        false => matrix.board.clone(),
+    };
+    let against: String = locales.format("matrix-against", &[&matrix.villain, &board]);
+    let hands: Vec<String> = starting_hands();
+    heat_grid(ui, "matrix", &hands, &matrix.cells(), &against, locales);
+}
+
+// a 13x13 grid of hands, each colored from red at the grid's lowest
+// equity to green at its highest, with the number on hover.
+fn heat_grid(
+    ui: &mut egui::Ui,
+    id: &str,
+    hands: &[String],
+    equities: &[Option<f32>],
+    against: &str,
+    locales: &Locales,
+) {
+    let known = || equities.iter().flatten().copied();
+    let low: f32 = known().fold(1., f32::min);
+    let high: f32 = known().fold(0., f32::max);
+
+    egui::Grid::new(id).spacing([2., 2.]).show(ui, |ui| {
+        for (i, (hand, equity)) in hands.iter().zip(equities.iter()).enumerate() {
+            let (rect, response) =
+                ui.allocate_exact_size(egui::vec2(36., 22.), egui::Sense::hover());
+            let t: f32 = match equity {
+                Some(e) if high > low => (e - low) / (high - low),
+                Some(_) => 1.,
+                None => 0.,
+            };
+            let color: egui::Color32 = match equity {
+                // red to yellow to green.
+                Some(_) if t < 0.5 => egui::Color32::from_rgb(230, (460. * t) as u8, 40),
+                Some(_) => egui::Color32::from_rgb((460. * (1. - t)) as u8, 230, 40),
+                None => egui::Color32::GRAY,
+            };
+            ui.painter().rect_filled(rect, 2., color);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                hand,
+                egui::FontId::proportional(12.),
+                egui::Color32::BLACK,
+            );
+            let described: String = match equity {
+                Some(e) => {
+                    let equity: String = format!("{:.1}", e * 100.);
+                    locales.format("chart-cell", &[hand, &equity, &against])
+                }
+                None => locales.format("chart-cell-none", &[hand, &against]),
+            };
+            response.widget_info(|| {
+                egui::WidgetInfo::labeled(egui::WidgetType::Other, true, &described)
+            });
+            response.on_hover_text(described);
+            if i % 13 == 12 {
+                ui.end_row();
+            }
+        }
+    });
+}
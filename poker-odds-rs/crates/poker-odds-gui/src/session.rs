@@ -0,0 +1,71 @@
+use std::fmt;
+
+// the calculator's inputs and whether they had been solved, saved as
+// "key = value" lines like the locales, so a session can be picked up
+// after a restart or sent to someone. the results themselves are solved
+// again on loading.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Session {
+    // by seat, the hero's first, and whether the seat is in the hand.
+    pub hands: Vec<(String, bool)>,
+    pub board: String,
+    pub villain_range: String,
+    pub pot: f32,
+    pub bet: f32,
+    // the monte carlo samples, None for exact solves.
+    pub samples: Option<usize>,
+    pub solved: bool,
+}
+
+// the default file name, in the working directory.
+pub const SESSION_FILE: &str = "session.txt";
+
+impl Session {
+    // fails with the first line that couldn't be read.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut session: Session = Session::default();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(line.to_string());
+            };
+            let value: &str = value.trim();
+            let number = || value.parse::<f32>().map_err(|_| line.to_string());
+            match key.trim() {
+                "hand" => session.hands.push((value.to_string(), true)),
+                // a seat left out of the hand.
+                "folded" => session.hands.push((value.to_string(), false)),
+                "board" => session.board = value.to_string(),
+                "range" => session.villain_range = value.to_string(),
+                "pot" => session.pot = number()?,
+                "bet" => session.bet = number()?,
+                "samples" => session.samples = Some(number()? as usize),
+                "solved" => session.solved = value == "true",
+                _ => return Err(line.to_string()),
+            }
+        }
+        Ok(session)
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# poker odds session")?;
+        for (hand, enabled) in &self.hands {
+            match enabled {
+                true => writeln!(f, "hand = {}", hand)?,
+                false => writeln!(f, "folded = {}", hand)?,
+            }
+        }
+        writeln!(f, "board = {}", self.board)?;
+        writeln!(f, "range = {}", self.villain_range)?;
+        writeln!(f, "pot = {}", self.pot)?;
+        writeln!(f, "bet = {}", self.bet)?;
+        if let Some(samples) = self.samples {
+            writeln!(f, "samples = {}", samples)?;
+        }
+        writeln!(f, "solved = {}", self.solved)
+    }
+}
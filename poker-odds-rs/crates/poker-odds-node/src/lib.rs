@@ -0,0 +1,42 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct SolveInput {
+    pub hands: Vec<String>,
+    pub board: Option<String>,
+    // only "exact" for now.
+    pub mode: Option<String>,
+}
+
+#[napi(object)]
+pub struct SolveOutput {
+    pub equity: f64,
+    pub elapsed_ms: f64,
+    pub hands: Vec<String>,
+    pub board: String,
+    pub mode: String,
+}
+
+#[napi]
+pub fn solve_equity(input: SolveInput) -> Result<SolveOutput> {
+    let mode: String = input.mode.unwrap_or_else(|| "exact".to_string());
+    if mode != "exact" {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("'{}' is not a supported solve mode", mode),
+        ));
+    }
+
+    let board: String = input.board.unwrap_or_default();
+    let solution = poker_odds_backend::try_solve(&input.hands, &board)
+        .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+
+    Ok(SolveOutput {
+        equity: solution.equity as f64,
+        elapsed_ms: solution.stats.elapsed.as_secs_f64() * 1000.,
+        hands: input.hands,
+        board,
+        mode,
+    })
+}
@@ -0,0 +1,10 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "grpc")]
+    {
+        // a protoc binary, so building doesn't need one installed.
+        // prost-build looks for it here first.
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        tonic_build::compile_protos("proto/equity.proto")?;
+    }
+    Ok(())
+}
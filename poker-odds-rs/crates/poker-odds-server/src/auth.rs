@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// the keys a server takes, one per client. a request shows one in an
+// `Authorization: Bearer <key>` or `X-API-Key: <key>` header.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    keys: Vec<String>,
+}
+
+impl ApiKeys {
+    pub fn new(keys: Vec<String>) -> Self {
+        ApiKeys { keys }
+    }
+
+    // one key per line. blank lines and lines starting with '#' are
+    // skipped, so keys can be labeled with who they were given to.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let keys: Vec<String> = fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        if keys.is_empty() {
+            // a server nobody can use is a typo, not a choice.
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the key file has no keys",
+            ));
+        }
+        Ok(ApiKeys { keys })
+    }
+
+    // whether the headers carry one of the keys.
+    pub(crate) fn allows(&self, headers: &HashMap<String, String>) -> bool {
+        let given: Option<&str> = headers
+            .get("authorization")
+            .and_then(|a| a.strip_prefix("Bearer "))
+            .or_else(|| headers.get("x-api-key").map(String::as_str));
+        given.is_some_and(|given| self.allows_key(given))
+    }
+
+    // whether `given` is one of the keys.
+    pub fn allows_key(&self, given: &str) -> bool {
+        // every key is compared in full, so how long the answer takes
+        // doesn't tell how much of a guess was right.
+        self.keys.iter().fold(false, |found, key| {
+            same(key.as_bytes(), given.trim().as_bytes()) | found
+        })
+    }
+}
+
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(name: &str, value: &str) -> HashMap<String, String> {
+        HashMap::from([(name.to_string(), value.to_string())])
+    }
+
+    #[test]
+    fn takes_a_key_from_either_header() {
+        let keys: ApiKeys = ApiKeys::new(vec!["alpha".to_string(), "beta".to_string()]);
+        assert!(keys.allows(&headers("authorization", "Bearer beta")));
+        assert!(keys.allows(&headers("x-api-key", " alpha ")));
+        assert!(!keys.allows(&headers("authorization", "beta")));
+        assert!(!keys.allows(&headers("authorization", "Bearer gamma")));
+        assert!(!keys.allows(&headers("x-api-key", "alph")));
+        assert!(!keys.allows(&HashMap::new()));
+    }
+
+    #[test]
+    fn loads_keys_without_comments() {
+        let path = std::env::temp_dir().join(format!("keys-{}.txt", std::process::id()));
+        fs::write(&path, "# alice\nalpha\n\n  beta  \n#gamma\n").unwrap();
+        let keys: io::Result<ApiKeys> = ApiKeys::load(&path);
+        fs::write(&path, "# nobody yet\n").unwrap();
+        let empty: io::Result<ApiKeys> = ApiKeys::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        let keys: ApiKeys = keys.unwrap();
+        assert!(keys.allows_key("alpha") && keys.allows_key("beta"));
+        assert!(!keys.allows_key("gamma") && !keys.allows_key("# alice"));
+        assert_eq!(empty.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::SolveRequest;
+use poker_odds_backend::{canonical_scenario, Solution, SolveStats};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// the spot with its suits relabeled and its opponents sorted, and the
+// mode it was asked in.
+type Key = (Vec<String>, String, String);
+
+// answers already given to any client, so the same question asked again,
+// in whatever suits or seat order, is answered without queueing a solve.
+// monte carlo answers are kept too: asking again gets the same estimate.
+pub struct ResultCache {
+    entries: Mutex<Entries>,
+    // answers kept at most. once full, the one used longest ago makes room
+    // for a new one.
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct Entries {
+    // each answer with when it was last given or kept.
+    answers: HashMap<Key, (Solution, u64)>,
+    // the keys by when they were last used, oldest first.
+    used: BTreeMap<u64, Key>,
+    clock: u64,
+}
+
+impl Entries {
+    // marks `key`, last used at `then`, as used now.
+    fn touch(&mut self, key: &Key, then: u64) -> u64 {
+        self.clock += 1;
+        let key: Key = self.used.remove(&then).unwrap_or_else(|| key.clone());
+        self.used.insert(self.clock, key);
+        self.clock
+    }
+}
+
+impl ResultCache {
+    pub fn new(capacity: usize) -> Self {
+        ResultCache {
+            entries: Mutex::new(Entries::default()),
+            capacity,
+        }
+    }
+
+    // None for cards that can't be read, the solve reports those.
+    fn key(req: &SolveRequest) -> Option<Key> {
+        let (hands, board) = canonical_scenario(&req.hands, &req.board).ok()?;
+        Some((hands, board, format!("{:?}", req.mode)))
+    }
+
+    // the answer given before, with stats saying it took no work.
+    pub fn get(&self, req: &SolveRequest) -> Option<Solution> {
+        let key: Key = Self::key(req)?;
+        let mut entries = self.entries.lock().unwrap();
+        let (solution, then) = *entries.answers.get(&key)?;
+        let now: u64 = entries.touch(&key, then);
+        entries.answers.get_mut(&key).unwrap().1 = now;
+        Some(Solution {
+            stats: SolveStats {
+                leaves: 0,
+                cache_hits: 0,
+                cache_misses: 0,
+                threads: 0,
+                elapsed: Duration::ZERO,
+                busy: Duration::ZERO,
+                cached: true,
+                ..solution.stats
+            },
+            ..solution
+        })
+    }
+
+    pub fn insert(&self, req: &SolveRequest, solution: &Solution) {
+        let Some(key) = Self::key(req) else {
+            return;
+        };
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let then: u64 = match entries.answers.get(&key) {
+            Some(&(_, then)) => then,
+            None => {
+                if entries.answers.len() >= self.capacity {
+                    if let Some((_, oldest)) = entries.used.pop_first() {
+                        entries.answers.remove(&oldest);
+                    }
+                }
+                0
+            }
+        };
+        let now: u64 = entries.touch(&key, then);
+        entries.answers.insert(key, (*solution, now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_odds_backend::{SolveMode, Solver};
+
+    fn request(hands: &[&str], board: &str) -> SolveRequest {
+        SolveRequest {
+            hands: hands.iter().map(|h| h.to_string()).collect(),
+            board: board.to_string(),
+            mode: SolveMode::Exact,
+        }
+    }
+
+    fn solution(req: &SolveRequest) -> Solution {
+        Solver::new().try_solve(&req.hands, &req.board).unwrap()
+    }
+
+    #[test]
+    fn answers_the_same_spot_in_other_suits_and_seats() {
+        let cache: ResultCache = ResultCache::new(10);
+        let asked: SolveRequest = request(&["AhKh", "QsQd", "7c6c"], "Th9h2c");
+        cache.insert(&asked, &solution(&asked));
+        // hearts and spades swapped, the opponents the other way round.
+        let again: SolveRequest = request(&["AsKs", "7c6c", "QhQd"], "Ts9s2c");
+        let cached: Solution = cache.get(&again).unwrap();
+        assert!(cached.stats.cached);
+        assert_eq!(cached.equity, solution(&again).equity);
+        // the hero's suits matter, a different hero isn't the same spot.
+        assert!(cache
+            .get(&request(&["AhKd", "QsQd", "7c6c"], "Th9h2c"))
+            .is_none());
+        let mut sampled: SolveRequest = asked;
+        sampled.mode = SolveMode::MonteCarlo { samples: 1000 };
+        assert!(cache.get(&sampled).is_none());
+    }
+
+    #[test]
+    fn makes_room_by_dropping_the_answer_used_longest_ago() {
+        let cache: ResultCache = ResultCache::new(2);
+        let spots: Vec<SolveRequest> = ["Th9h2c", "Th9h3c", "Th9h4c"]
+            .iter()
+            .map(|bd| request(&["AhKh", "QsQd"], bd))
+            .collect();
+        cache.insert(&spots[0], &solution(&spots[0]));
+        cache.insert(&spots[1], &solution(&spots[1]));
+        assert!(cache.get(&spots[0]).is_some());
+        cache.insert(&spots[2], &solution(&spots[2]));
+        assert!(cache.get(&spots[0]).is_some());
+        assert!(cache.get(&spots[1]).is_none());
+        assert!(cache.get(&spots[2]).is_some());
+    }
+}
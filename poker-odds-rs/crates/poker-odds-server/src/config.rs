@@ -0,0 +1,166 @@
+use crate::{ApiKeys, Frontends, Pool, Server, DEFAULT_QUEUE, DEFAULT_WORKERS};
+use poker_odds_backend::{share_tables, Solver};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+// where http and the websocket go when no frontend is asked for.
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+// the flags of a `serve` subcommand, read the same by every binary that
+// starts a Server. each frontend given an address is served on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeConfig {
+    pub http: Option<String>,
+    pub ws: Option<String>,
+    #[cfg(feature = "grpc")]
+    pub grpc: Option<String>,
+    pub workers: usize,
+    pub queue: usize,
+    // a file with one api key per line.
+    pub keys: Option<String>,
+    // where the evaluator's tables are shared with the other processes
+    // that are given the same file.
+    pub tables: Option<String>,
+}
+
+impl ServeConfig {
+    #[cfg(feature = "grpc")]
+    pub const USAGE: &'static str =
+        "serve [--http addr] [--ws addr] [--grpc addr] [--workers n] [--queue n] [--keys file] [--tables file]";
+    #[cfg(not(feature = "grpc"))]
+    pub const USAGE: &'static str =
+        "serve [--http addr] [--ws addr] [--workers n] [--queue n] [--keys file] [--tables file]";
+
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut config: ServeConfig = ServeConfig {
+            http: None,
+            ws: None,
+            #[cfg(feature = "grpc")]
+            grpc: None,
+            workers: DEFAULT_WORKERS,
+            queue: DEFAULT_QUEUE,
+            keys: None,
+            tables: None,
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value: String = args
+                .next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+            let number = || {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("{} needs a number, not '{}'", flag, value))
+            };
+            match flag.as_str() {
+                "--http" => config.http = Some(value),
+                "--ws" => config.ws = Some(value),
+                #[cfg(feature = "grpc")]
+                "--grpc" => config.grpc = Some(value),
+                "--workers" => config.workers = number()?,
+                "--queue" => config.queue = number()?,
+                "--keys" => config.keys = Some(value),
+                "--tables" => config.tables = Some(value),
+                _ => return Err(format!("unknown flag '{}'", flag)),
+            }
+        }
+        if config.addrs().is_empty() {
+            config.http = Some(DEFAULT_ADDR.to_string());
+            config.ws = Some(DEFAULT_ADDR.to_string());
+        }
+        Ok(config)
+    }
+
+    // one solver, cache and pool for every frontend, scoring hands with the
+    // shared tables when there are some.
+    pub fn server(&self, solver: Arc<Solver>) -> io::Result<Server> {
+        if let Some(path) = &self.tables {
+            share_tables(path).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("can't share the evaluator tables in {}: {}", path, e),
+                )
+            })?;
+        }
+        let keys: Option<ApiKeys> = self
+            .keys
+            .as_ref()
+            .map(|path| {
+                ApiKeys::load(path).map_err(|e| {
+                    io::Error::new(e.kind(), format!("can't read the keys in {}: {}", path, e))
+                })
+            })
+            .transpose()?;
+        Ok(Server::new(
+            solver,
+            Pool::new(self.workers, self.queue),
+            keys,
+        ))
+    }
+
+    // starts the listeners asked for, http and the websocket on a single
+    // one when they share an address.
+    pub fn spawn_listeners(&self, server: &Server) -> io::Result<Vec<JoinHandle<()>>> {
+        let mut listeners: Vec<JoinHandle<()>> = Vec::new();
+        match (&self.http, &self.ws) {
+            (Some(http), Some(ws)) if http == ws => {
+                listeners.push(server.spawn(http, Frontends::ALL)?);
+            }
+            (http, ws) => {
+                if let Some(http) = http {
+                    let only: Frontends = Frontends {
+                        http: true,
+                        ws: false,
+                    };
+                    listeners.push(server.spawn(http, only)?);
+                }
+                if let Some(ws) = ws {
+                    let only: Frontends = Frontends {
+                        http: false,
+                        ws: true,
+                    };
+                    listeners.push(server.spawn(ws, only)?);
+                }
+            }
+        }
+        #[cfg(feature = "grpc")]
+        if let Some(grpc) = &self.grpc {
+            listeners.push(crate::grpc::spawn(server, grpc)?);
+        }
+        Ok(listeners)
+    }
+
+    // the addresses other machines can reach, when there are no keys to
+    // keep them out.
+    pub fn open_addrs(&self) -> Vec<&str> {
+        if self.keys.is_some() {
+            return Vec::new();
+        }
+        self.addrs()
+            .into_iter()
+            .filter(|addr| !is_local(addr))
+            .collect()
+    }
+
+    // every frontend's address.
+    fn addrs(&self) -> Vec<&str> {
+        #[cfg(feature = "grpc")]
+        let grpc: &Option<String> = &self.grpc;
+        #[cfg(not(feature = "grpc"))]
+        let grpc: &Option<String> = &None;
+        [&self.http, &self.ws, grpc]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+fn is_local(addr: &str) -> bool {
+    addr.parse::<SocketAddr>()
+        .is_ok_and(|a| a.ip().is_loopback())
+        || addr.starts_with("localhost:")
+}
@@ -0,0 +1,155 @@
+use crate::json::Json;
+use poker_odds_backend::Error;
+
+// why a request was turned down, sent back as {"code", "message"} with
+// an http status to match.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    // the request itself couldn't be read: not json, a field missing or
+    // of the wrong type.
+    BadRequest(String),
+    // no api key, or one the server doesn't know.
+    Unauthorized,
+    // no such path.
+    NotFound(String),
+    // every solve worker is taken and the queue behind them is full.
+    Busy,
+    // the solve died before answering.
+    Internal,
+    // the solver turned the cards down.
+    Solver(Error),
+}
+
+impl ApiError {
+    // cards that can't be read are the client's typo, 400. cards that read
+    // fine but can't all be dealt are a scenario that can't happen, 422.
+    pub fn status(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "400 Bad Request",
+            ApiError::Unauthorized => "401 Unauthorized",
+            ApiError::NotFound(_) => "404 Not Found",
+            ApiError::Busy => "503 Service Unavailable",
+            ApiError::Internal => "500 Internal Server Error",
+            ApiError::Solver(e) => match e {
+                Error::InvalidCard(_)
+                | Error::InvalidHand(_)
+                | Error::InvalidBoard(_)
+                | Error::InvalidRange(_)
+                | Error::InvalidHistory(_)
+                | Error::InvalidQuery(_) => "400 Bad Request",
+                Error::DuplicateCard(_) | Error::PlayerCount(_) | Error::EmptyRange => {
+                    "422 Unprocessable Entity"
+                }
+            },
+        }
+    }
+
+    // for clients to branch on, the message is for people.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Busy => "busy",
+            ApiError::Internal => "internal",
+            ApiError::Solver(e) => match e {
+                Error::InvalidCard(_) => "invalid_card",
+                Error::InvalidHand(_) => "invalid_hand",
+                Error::InvalidBoard(_) => "invalid_board",
+                Error::DuplicateCard(_) => "duplicate_card",
+                Error::PlayerCount(_) => "player_count",
+                Error::InvalidRange(_) => "invalid_range",
+                Error::EmptyRange => "empty_range",
+                Error::InvalidHistory(_) => "invalid_history",
+                Error::InvalidQuery(_) => "invalid_query",
+            },
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(m) => m.clone(),
+            ApiError::Unauthorized => "a valid api key is needed".to_string(),
+            ApiError::NotFound(path) => format!("nothing at '{}'", path),
+            ApiError::Busy => "too many solves queued, try again later".to_string(),
+            ApiError::Internal => "the solve failed".to_string(),
+            ApiError::Solver(e) => e.to_string(),
+        }
+    }
+
+    pub fn fields(&self) -> Vec<(&'static str, Json)> {
+        vec![
+            ("code", self.code().into()),
+            ("message", self.message().as_str().into()),
+        ]
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        ApiError::Solver(e)
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(m: String) -> Self {
+        ApiError::BadRequest(m)
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(m: &str) -> Self {
+        ApiError::BadRequest(m.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos_are_bad_requests_and_impossible_deals_unprocessable() {
+        let e: ApiError = Error::InvalidCard("Xx".to_string()).into();
+        assert_eq!((e.status(), e.code()), ("400 Bad Request", "invalid_card"));
+        let e: ApiError = Error::InvalidBoard("2c7d".to_string()).into();
+        assert_eq!((e.status(), e.code()), ("400 Bad Request", "invalid_board"));
+        let e: ApiError = Error::DuplicateCard("Ah".to_string()).into();
+        assert_eq!(
+            (e.status(), e.code()),
+            ("422 Unprocessable Entity", "duplicate_card")
+        );
+        assert_eq!(e.message(), "Ah is dealt more than once");
+        let e: ApiError = Error::PlayerCount(11).into();
+        assert_eq!(e.status(), "422 Unprocessable Entity");
+        assert_eq!(ApiError::from(Error::EmptyRange).code(), "empty_range");
+    }
+
+    #[test]
+    fn the_servers_own_errors() {
+        for (e, status, code) in [
+            (ApiError::Unauthorized, "401 Unauthorized", "unauthorized"),
+            (
+                ApiError::NotFound("/x".to_string()),
+                "404 Not Found",
+                "not_found",
+            ),
+            (ApiError::Busy, "503 Service Unavailable", "busy"),
+            (ApiError::Internal, "500 Internal Server Error", "internal"),
+        ] {
+            assert_eq!((e.status(), e.code()), (status, code));
+        }
+        let e: ApiError = "'hands' is missing".into();
+        assert_eq!(e, ApiError::BadRequest("'hands' is missing".to_string()));
+        assert_eq!(e.status(), "400 Bad Request");
+    }
+
+    #[test]
+    fn the_body_carries_the_code_and_message() {
+        let body: Json = Json::object(ApiError::NotFound("/x".to_string()).fields());
+        assert_eq!(body.get("code").and_then(Json::as_str), Some("not_found"));
+        assert_eq!(
+            body.get("message").and_then(Json::as_str),
+            Some("nothing at '/x'")
+        );
+    }
+}
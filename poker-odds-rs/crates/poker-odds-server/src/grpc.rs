@@ -0,0 +1,226 @@
+// the equity service over grpc, see proto/equity.proto, answered by the
+// same Server as http and the websocket.
+use crate::{ApiError, Server};
+use poker_odds_backend::{EquityEstimate, Solution, SolveMode};
+use std::io;
+use std::thread::{self, JoinHandle};
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::metadata::MetadataMap;
+use tonic::transport::Server as GrpcServer;
+use tonic::{Request, Response, Status};
+
+pub mod pb {
+    tonic::include_proto!("pokerodds");
+}
+
+use pb::equity_server::{Equity, EquityServer};
+use pb::{SolveRequest, SolveResponse, SolveUpdate};
+
+// estimates waiting for a slow client before the solve waits for it.
+const UPDATE_BUFFER: usize = 64;
+
+fn mode(req: &SolveRequest) -> SolveMode {
+    match req.samples {
+        0 => SolveMode::Exact,
+        n => SolveMode::MonteCarlo {
+            samples: n as usize,
+        },
+    }
+}
+
+// the grpc status for a solve the server turned down.
+fn status(e: ApiError) -> Status {
+    let message: String = e.message();
+    match e {
+        ApiError::Unauthorized => Status::unauthenticated(message),
+        ApiError::Busy => Status::resource_exhausted(message),
+        ApiError::Internal => Status::internal(message),
+        _ => Status::invalid_argument(message),
+    }
+}
+
+// the key in an `authorization: Bearer <key>` or `x-api-key` entry.
+fn key(metadata: &MetadataMap) -> Option<&str> {
+    metadata
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|a| a.strip_prefix("Bearer "))
+        .or_else(|| metadata.get("x-api-key").and_then(|v| v.to_str().ok()))
+}
+
+struct EquityService {
+    server: Server,
+}
+
+impl EquityService {
+    fn check(&self, request: &Request<SolveRequest>) -> Result<(), ApiError> {
+        match self.server.allows(key(request.metadata())) {
+            true => Ok(()),
+            false => Err(ApiError::Unauthorized),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Equity for EquityService {
+    async fn solve(
+        &self,
+        request: Request<SolveRequest>,
+    ) -> Result<Response<SolveResponse>, Status> {
+        self.check(&request).map_err(status)?;
+        let req: SolveRequest = request.into_inner();
+        let server: Server = self.server.clone();
+
+        // waiting on the pool blocks, keep it off the runtime.
+        let solution = tokio::task::spawn_blocking(move || {
+            let mode: SolveMode = mode(&req);
+            server.solve(req.hands, req.board, mode, |_| {})
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(status)?;
+
+        Ok(Response::new(SolveResponse {
+            equity: solution.equity,
+            elapsed_ms: solution.stats.elapsed.as_secs_f32() * 1000.,
+        }))
+    }
+
+    type SolveStreamingStream = ReceiverStream<Result<SolveUpdate, Status>>;
+
+    async fn solve_streaming(
+        &self,
+        request: Request<SolveRequest>,
+    ) -> Result<Response<Self::SolveStreamingStream>, Status> {
+        self.check(&request).map_err(status)?;
+        let req: SolveRequest = request.into_inner();
+        let server: Server = self.server.clone();
+        let (tx, rx) = mpsc::channel::<Result<SolveUpdate, Status>>(UPDATE_BUFFER);
+
+        tokio::task::spawn_blocking(move || {
+            // sends fail once the client hangs up, the solve finishes anyway.
+            let mode: SolveMode = mode(&req);
+            let result: Result<Solution, ApiError> =
+                server.solve(req.hands, req.board, mode, |e: EquityEstimate| {
+                    let _ = tx.blocking_send(Ok(SolveUpdate {
+                        equity: e.equity,
+                        progress: e.progress,
+                        std_err: e.std_err,
+                        done: false,
+                        elapsed_ms: 0.,
+                    }));
+                });
+            let last: Result<SolveUpdate, Status> = result.map(|s| last_update(&s)).map_err(status);
+            let _ = tx.blocking_send(last);
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+fn last_update(solution: &Solution) -> SolveUpdate {
+    SolveUpdate {
+        equity: solution.equity,
+        progress: 1.,
+        std_err: solution.stats.std_err,
+        done: true,
+        elapsed_ms: solution.stats.elapsed.as_secs_f32() * 1000.,
+    }
+}
+
+// answers grpc on `addr` on a thread of its own with a runtime of its own.
+// binds before returning, so an address in use is reported here.
+pub(crate) fn spawn(server: &Server, addr: &str) -> io::Result<JoinHandle<()>> {
+    let listener: std::net::TcpListener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let runtime: Runtime = Runtime::new()?;
+    let listener: TcpListener = {
+        let _inside = runtime.enter();
+        TcpListener::from_std(listener)?
+    };
+    let service = EquityServer::new(EquityService {
+        server: server.clone(),
+    });
+    Ok(thread::spawn(move || {
+        let incoming = TcpListenerStream::new(listener);
+        // like the other listeners, it runs until it fails.
+        let _ = runtime.block_on(
+            GrpcServer::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming),
+        );
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pool;
+    use pb::equity_client::EquityClient;
+    use poker_odds_backend::Solver;
+    use std::sync::Arc;
+
+    #[test]
+    fn solves_and_streams_over_grpc() {
+        let server: Server = Server::new(Arc::new(Solver::new()), Pool::new(1, 4), None);
+        // a free port, taken again right away.
+        let addr: String = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .unwrap()
+            .to_string();
+        spawn(&server, &addr).unwrap();
+        let request = || SolveRequest {
+            hands: vec!["AhKh".to_string(), "QsQd".to_string()],
+            board: "7c8c9c".to_string(),
+            samples: 0,
+        };
+        Runtime::new().unwrap().block_on(async {
+            let mut client = EquityClient::connect(format!("http://{}", addr))
+                .await
+                .unwrap();
+            let answer = client.solve(request()).await.unwrap().into_inner();
+            assert!((answer.equity - 0.2621).abs() < 1e-4);
+
+            let mut stream = client
+                .solve_streaming(request())
+                .await
+                .unwrap()
+                .into_inner();
+            let mut last: Option<SolveUpdate> = None;
+            while let Some(update) = stream.message().await.unwrap() {
+                last = Some(update);
+            }
+            let last: SolveUpdate = last.unwrap();
+            assert!(last.done);
+            assert_eq!(last.equity, answer.equity);
+        });
+    }
+
+    #[test]
+    fn reads_keys_and_maps_errors() {
+        let key = |entries: &[(&'static str, &str)]| {
+            let mut metadata: MetadataMap = MetadataMap::new();
+            for &(k, v) in entries {
+                metadata.insert(k, v.parse().unwrap());
+            }
+            key(&metadata).map(str::to_string)
+        };
+        assert_eq!(
+            key(&[("authorization", "Bearer abc")]).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(key(&[("x-api-key", "abc")]).as_deref(), Some("abc"));
+        assert_eq!(key(&[("authorization", "Basic abc")]), None);
+        assert_eq!(
+            status(ApiError::Unauthorized).code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            status(ApiError::Busy).code(),
+            tonic::Code::ResourceExhausted
+        );
+    }
+}
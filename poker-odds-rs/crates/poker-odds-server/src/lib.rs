@@ -0,0 +1,497 @@
+mod auth;
+mod cache;
+mod config;
+mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+mod json;
+mod openapi;
+mod pool;
+mod ws;
+
+pub use auth::ApiKeys;
+use cache::ResultCache;
+pub use config::ServeConfig;
+pub use error::ApiError;
+use json::Json;
+use poker_odds_backend::{EquityEstimate, Error, Solution, SolveMode, Solver};
+pub use pool::Pool;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+fn read_request(stream: &mut TcpStream) -> io::Result<Request> {
+    // read byte by byte so nothing after the header block is swallowed.
+    let mut head: Vec<u8> = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 16 * 1024 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "header too large",
+            ));
+        }
+        stream.read_exact(&mut byte)?;
+        head.push(byte[0]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method: String = request_line.next().unwrap_or_default().to_string();
+    let path: String = request_line.next().unwrap_or_default().to_string();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+    })
+}
+
+// the body of a request, as long as its Content-Length says.
+fn read_body(stream: &mut TcpStream, req: &Request) -> Result<String, ApiError> {
+    let len: usize = req
+        .headers
+        .get("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+        .ok_or("a body with a Content-Length is needed")?;
+    // requests are a few hundred bytes, anything this big is garbage.
+    if len > 1 << 20 {
+        return Err("the body is too large".into());
+    }
+    let mut body: Vec<u8> = vec![0; len];
+    stream
+        .read_exact(&mut body)
+        .map_err(|_| "the body ended early")?;
+    String::from_utf8(body).map_err(|_| "the body isn't utf-8".into())
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+fn write_error(stream: &mut TcpStream, e: &ApiError) -> io::Result<()> {
+    write_response(stream, e.status(), &Json::object(e.fields()).to_string())
+}
+
+struct SolveRequest {
+    hands: Vec<String>,
+    board: String,
+    mode: SolveMode,
+}
+
+fn parse_solve_request(text: &str) -> Result<SolveRequest, ApiError> {
+    let v: Json = Json::parse(text)?;
+
+    let hands: Vec<String> = v
+        .get("hands")
+        .and_then(Json::as_array)
+        .ok_or("'hands' must be an array of strings")?
+        .iter()
+        .map(|h| h.as_str().map(str::to_string))
+        .collect::<Option<_>>()
+        .ok_or("'hands' must be an array of strings")?;
+
+    let board: String = match v.get("board") {
+        None | Some(Json::Null) => String::new(),
+        Some(b) => b.as_str().ok_or("'board' must be a string")?.to_string(),
+    };
+
+    let mode: SolveMode = match v.get("mode").and_then(Json::as_str).unwrap_or("exact") {
+        "exact" => SolveMode::Exact,
+        "montecarlo" => {
+            let samples: f64 = v
+                .get("samples")
+                .and_then(Json::as_f64)
+                .unwrap_or(1_000_000.);
+            if samples < 1. {
+                return Err("'samples' must be positive".into());
+            }
+            SolveMode::MonteCarlo {
+                samples: samples as usize,
+            }
+        }
+        m => return Err(format!("'{}' is not a supported solve mode", m).into()),
+    };
+
+    Ok(SolveRequest { hands, board, mode })
+}
+
+fn send(stream: &mut TcpStream, message: Json) -> io::Result<()> {
+    ws::write_frame(stream, ws::OP_TEXT, message.to_string().as_bytes())
+}
+
+fn send_error(stream: &mut TcpStream, e: &ApiError) -> io::Result<()> {
+    let mut fields: Vec<(&str, Json)> = vec![("type", "error".into())];
+    fields.extend(e.fields());
+    send(stream, Json::object(fields))
+}
+
+// what a finished solve is answered with, over http or the websocket.
+fn result_fields(solution: &Solution) -> Vec<(&'static str, Json)> {
+    vec![
+        ("equity", solution.equity.into()),
+        (
+            "elapsed_ms",
+            Json::Number(solution.stats.elapsed.as_secs_f64() * 1000.),
+        ),
+        ("leaves", Json::Number(solution.stats.leaves as f64)),
+        ("cache_hits", Json::Number(solution.stats.cache_hits as f64)),
+        ("threads", Json::Number(solution.stats.threads as f64)),
+        // answered from an earlier request for the same spot.
+        ("cached", Json::Bool(solution.stats.cached)),
+    ]
+}
+
+// how a queued solve reports back to the connection that asked for it.
+enum Update {
+    Progress(EquityEstimate),
+    // the request goes back with the answer, to be cached under.
+    Done(Box<(SolveRequest, Result<Solution, Error>)>),
+}
+
+// what every connection shares.
+struct State {
+    solver: Arc<Solver>,
+    pool: Pool,
+    cache: ResultCache,
+    // None lets anyone solve.
+    keys: Option<ApiKeys>,
+    // connections open, up to MAX_CONNECTIONS.
+    connections: AtomicUsize,
+}
+
+// answers `req` from the cache, or else queues it on the pool and waits
+// for it, passing its estimates to `progress` as they come in.
+fn solve_queued(
+    state: &State,
+    req: SolveRequest,
+    mut progress: impl FnMut(EquityEstimate),
+) -> Result<Solution, ApiError> {
+    if let Some(solution) = state.cache.get(&req) {
+        return Ok(solution);
+    }
+    let (tx, rx) = mpsc::channel::<Update>();
+    let solver: Arc<Solver> = state.solver.clone();
+    let queued: bool = state.pool.submit(move || {
+        // sends fail once the client is gone, the solve finishes anyway.
+        let result: Result<Solution, Error> =
+            solver.try_solve_with_progress(&req.hands, &req.board, req.mode, |e| {
+                let _ = tx.send(Update::Progress(e));
+            });
+        let _ = tx.send(Update::Done(Box::new((req, result))));
+    });
+    if !queued {
+        return Err(ApiError::Busy);
+    }
+    for update in rx {
+        match update {
+            Update::Progress(e) => progress(e),
+            Update::Done(done) => {
+                let (req, result) = *done;
+                let solution: Solution = result?;
+                state.cache.insert(&req, &solution);
+                return Ok(solution);
+            }
+        }
+    }
+    // the job panicked before it was done.
+    Err(ApiError::Internal)
+}
+
+// each text message is one solve request, answered by a stream of
+// "progress" messages and a final "result" (or an "error").
+fn handle_websocket(mut stream: TcpStream, state: &State) -> io::Result<()> {
+    while let Some(text) = ws::read_message(&mut stream)? {
+        let req: SolveRequest = match parse_solve_request(&text) {
+            Ok(req) => req,
+            Err(e) => {
+                send_error(&mut stream, &e)?;
+                continue;
+            }
+        };
+
+        let mut sent: io::Result<()> = Ok(());
+        let result: Result<Solution, ApiError> = solve_queued(state, req, |e: EquityEstimate| {
+            if sent.is_ok() {
+                let mut fields = vec![
+                    ("type", "progress".into()),
+                    ("equity", e.equity.into()),
+                    ("progress", e.progress.into()),
+                ];
+                if let Some(std_err) = e.std_err {
+                    fields.push(("std_err", std_err.into()));
+                }
+                sent = send(&mut stream, Json::object(fields));
+            }
+        });
+        // the client went away mid solve.
+        sent?;
+
+        match result {
+            Ok(solution) => {
+                let mut fields: Vec<(&str, Json)> = vec![("type", "result".into())];
+                fields.extend(result_fields(&solution));
+                send(&mut stream, Json::object(fields))?
+            }
+            Err(e) => send_error(&mut stream, &e)?,
+        }
+    }
+    Ok(())
+}
+
+// a single solve over plain http, answered once it's done.
+fn handle_solve(stream: &mut TcpStream, req: &Request, state: &State) -> io::Result<()> {
+    let solved: Result<Solution, ApiError> = read_body(stream, req)
+        .and_then(|body| parse_solve_request(&body))
+        .and_then(|r| solve_queued(state, r, |_| {}));
+    match solved {
+        Ok(solution) => write_response(
+            stream,
+            "200 OK",
+            &Json::object(result_fields(&solution)).to_string(),
+        ),
+        Err(e) => write_error(stream, &e),
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, state: &State, frontends: Frontends) -> io::Result<()> {
+    let req: Request = read_request(&mut stream)?;
+
+    // the api document is public, solving takes a key.
+    let open: bool = req.path == "/openapi.json";
+    if let Some(keys) = &state.keys {
+        if !open && !keys.allows(&req.headers) {
+            return write_error(&mut stream, &ApiError::Unauthorized);
+        }
+    }
+
+    match (req.method.as_str(), req.path.as_str()) {
+        ("GET", "/ws") if frontends.ws => {}
+        ("GET", "/openapi.json") if frontends.http => {
+            return write_response(&mut stream, "200 OK", openapi::OPENAPI)
+        }
+        ("POST", "/solve") if frontends.http => return handle_solve(&mut stream, &req, state),
+        _ => return write_error(&mut stream, &ApiError::NotFound(req.path.clone())),
+    }
+
+    let upgrade: bool = req
+        .headers
+        .get("upgrade")
+        .is_some_and(|u| u.eq_ignore_ascii_case("websocket"));
+    let key: Option<&String> = req.headers.get("sec-websocket-key");
+    let key: &String = match key {
+        Some(key) if upgrade => key,
+        _ => return write_error(&mut stream, &"expected a websocket upgrade".into()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        ws::accept_key(key)
+    )?;
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    handle_websocket(stream, state)
+}
+
+// solves at a time and solves waiting behind them when serve picks.
+pub const DEFAULT_WORKERS: usize = 2;
+pub const DEFAULT_QUEUE: usize = 16;
+// answers kept for clients asking the same thing again.
+const CACHED_ANSWERS: usize = 100_000;
+// connections served at once, each on a thread of its own. the ones past
+// it are told the server is busy.
+const MAX_CONNECTIONS: usize = 256;
+// how long a client gets to send its request, or to take an answer.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+// how long a websocket may sit between requests.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+// what a listener answers: `/solve` and `/openapi.json` over plain http,
+// and `/ws`. the rest is a 404.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frontends {
+    pub http: bool,
+    pub ws: bool,
+}
+
+impl Frontends {
+    pub const ALL: Frontends = Frontends {
+        http: true,
+        ws: true,
+    };
+}
+
+// the solver, answer cache, worker pool and api keys, shared by every
+// listener started from it, grpc's too, so a spot solved through one is
+// cached for all of them.
+#[derive(Clone)]
+pub struct Server {
+    state: Arc<State>,
+}
+
+impl Server {
+    pub fn new(solver: Arc<Solver>, pool: Pool, keys: Option<ApiKeys>) -> Self {
+        Server {
+            state: Arc::new(State {
+                solver,
+                pool,
+                cache: ResultCache::new(CACHED_ANSWERS),
+                keys,
+                connections: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    // whether a request showing `key` may solve, always when the server
+    // takes no keys.
+    pub fn allows(&self, key: Option<&str>) -> bool {
+        match (&self.state.keys, key) {
+            (None, _) => true,
+            (Some(keys), Some(key)) => keys.allows_key(key),
+            (Some(_), None) => false,
+        }
+    }
+
+    // a solve through the cache and the pool, like one asked over http.
+    pub fn solve(
+        &self,
+        hands: Vec<String>,
+        board: String,
+        mode: SolveMode,
+        progress: impl FnMut(EquityEstimate),
+    ) -> Result<Solution, ApiError> {
+        solve_queued(&self.state, SolveRequest { hands, board, mode }, progress)
+    }
+
+    // answers `frontends` on `addr` until the listener fails, one thread
+    // per connection and MAX_CONNECTIONS at most.
+    pub fn listen(&self, addr: &str, frontends: Frontends) -> io::Result<()> {
+        accept(TcpListener::bind(addr)?, self.state.clone(), frontends);
+        Ok(())
+    }
+
+    // like listen, on a thread of its own. binds before returning, so an
+    // address in use is reported here.
+    pub fn spawn(&self, addr: &str, frontends: Frontends) -> io::Result<JoinHandle<()>> {
+        let listener: TcpListener = TcpListener::bind(addr)?;
+        let state: Arc<State> = self.state.clone();
+        Ok(thread::spawn(move || accept(listener, state, frontends)))
+    }
+}
+
+fn accept(listener: TcpListener, state: Arc<State>, frontends: Frontends) {
+    for stream in listener.incoming() {
+        let mut stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        // a client that stops sending or reading only holds its own thread,
+        // and not for long.
+        if stream.set_read_timeout(Some(IO_TIMEOUT)).is_err()
+            || stream.set_write_timeout(Some(IO_TIMEOUT)).is_err()
+        {
+            continue;
+        }
+        let Some(slot) = Connection::open(&state) else {
+            let _ = write_error(&mut stream, &ApiError::Busy);
+            continue;
+        };
+        thread::spawn(move || {
+            // a dropped connection only concerns that client.
+            let _ = handle_connection(stream, &slot.state, frontends);
+        });
+    }
+}
+
+// one of the MAX_CONNECTIONS, given back when the connection is done.
+struct Connection {
+    state: Arc<State>,
+}
+
+impl Connection {
+    fn open(state: &Arc<State>) -> Option<Self> {
+        state
+            .connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()?;
+        Some(Connection {
+            state: state.clone(),
+        })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.state.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+// serves `/ws`, `/solve` and `/openapi.json` on `addr`, one thread per
+// connection. all connections share `solver`, and their solves queue for
+// a pool of DEFAULT_WORKERS.
+pub fn serve(addr: &str, solver: Arc<Solver>) -> io::Result<()> {
+    serve_with_pool(addr, solver, Pool::new(DEFAULT_WORKERS, DEFAULT_QUEUE))
+}
+
+// like serve, with solves run on `pool`. a solve that finds its queue
+// full is answered with a "busy" error, 503 over http.
+pub fn serve_with_pool(addr: &str, solver: Arc<Solver>, pool: Pool) -> io::Result<()> {
+    serve_with_keys(addr, solver, pool, None)
+}
+
+// like serve_with_pool, answering only requests that carry one of `keys`
+// when there are any. the rest get an "unauthorized" error, 401 over http.
+pub fn serve_with_keys(
+    addr: &str,
+    solver: Arc<Solver>,
+    pool: Pool,
+    keys: Option<ApiKeys>,
+) -> io::Result<()> {
+    Server::new(solver, pool, keys).listen(addr, Frontends::ALL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_connections_away_past_the_cap() {
+        let server: Server = Server::new(Arc::new(Solver::new()), Pool::new(1, 1), None);
+        let state: &Arc<State> = &server.state;
+        state
+            .connections
+            .store(MAX_CONNECTIONS - 1, Ordering::Relaxed);
+        let last: Connection = Connection::open(state).unwrap();
+        assert!(Connection::open(state).is_none());
+        drop(last);
+        assert!(Connection::open(state).is_some());
+        assert_eq!(
+            state.connections.load(Ordering::Relaxed),
+            MAX_CONNECTIONS - 1
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use poker_odds_backend::Solver;
+use poker_odds_server::{ServeConfig, Server};
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+// poker-odds-server serve [--http addr] [--ws addr] [--grpc addr] [--workers n] [--queue n] [--keys file] [--tables file]
+// (--grpc with the grpc feature)
+// poker-odds-server [--keys file] [addr] [solves at a time] [solves queued behind them]
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let config: Result<ServeConfig, String> = match args.first().map(String::as_str) {
+        Some("serve") => ServeConfig::parse(&args[1..]),
+        _ => legacy_config(args),
+    };
+    let config: ServeConfig = config.unwrap_or_else(|e| {
+        eprintln!("{}.\nUsage: poker-odds-server {}", e, ServeConfig::USAGE);
+        process::exit(1);
+    });
+    let server: Server = config.server(Arc::new(Solver::new())).unwrap_or_else(|e| {
+        eprintln!("Can't start: {}.", e);
+        process::exit(1);
+    });
+    for addr in config.open_addrs() {
+        eprintln!(
+            "Warning: {} is reachable from other machines and anyone can solve on it, pass --keys to require an api key.",
+            addr
+        );
+    }
+    let listeners: Vec<JoinHandle<()>> = config.spawn_listeners(&server).unwrap_or_else(|e| {
+        eprintln!("Can't listen: {}", e);
+        process::exit(1);
+    });
+    if let Some(addr) = &config.http {
+        println!("HTTP endpoint at http://{}/solve.", addr);
+    }
+    if let Some(addr) = &config.ws {
+        println!("WebSocket endpoint at ws://{}/ws.", addr);
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(addr) = &config.grpc {
+        println!("gRPC server listening on {}.", addr);
+    }
+    for listener in listeners {
+        let _ = listener.join();
+    }
+}
+
+// the positional form from before `serve`, http and the websocket on
+// one address.
+fn legacy_config(mut args: Vec<String>) -> Result<ServeConfig, String> {
+    let mut flags: Vec<String> = Vec::new();
+    if let Some(i) = args.iter().position(|a| a == "--keys") {
+        if i + 1 == args.len() {
+            return Err("--keys needs a file with one api key per line".to_string());
+        }
+        flags.extend(args.drain(i..i + 2));
+    }
+    let mut positional = args.into_iter();
+    if let Some(addr) = positional.next() {
+        flags.extend(["--http".to_string(), addr.clone(), "--ws".to_string(), addr]);
+    }
+    for flag in ["--workers", "--queue"] {
+        if let Some(n) = positional.next() {
+            flags.extend([flag.to_string(), n]);
+        }
+    }
+    ServeConfig::parse(&flags)
+}
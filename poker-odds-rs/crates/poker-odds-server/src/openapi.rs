@@ -0,0 +1,157 @@
+// the http api as an openapi 3 document, served at /openapi.json. kept by
+// hand next to the handlers in lib.rs, update both together.
+pub const OPENAPI: &str = r##"{
+  "openapi": "3.0.3",
+  "info": {
+    "title": "poker-odds",
+    "version": "0.1.0",
+    "description": "Hold'em equity solver. POST /solve answers once; GET /ws upgrades to a websocket that takes the same requests as text messages and streams progress before the result."
+  },
+  "paths": {
+    "/solve": {
+      "post": {
+        "summary": "The first hand's equity against the others on a board.",
+        "requestBody": {
+          "required": true,
+          "content": {
+            "application/json": {
+              "schema": { "$ref": "#/components/schemas/SolveRequest" }
+            }
+          }
+        },
+        "responses": {
+          "200": {
+            "description": "The solve finished.",
+            "content": {
+              "application/json": {
+                "schema": { "$ref": "#/components/schemas/SolveResult" }
+              }
+            }
+          },
+          "400": {
+            "description": "The request isn't json of the right shape, or a card, hand or board can't be read.",
+            "content": {
+              "application/json": {
+                "schema": { "$ref": "#/components/schemas/Error" }
+              }
+            }
+          },
+          "401": {
+            "description": "The server takes api keys and the request carried none it knows.",
+            "content": {
+              "application/json": {
+                "schema": { "$ref": "#/components/schemas/Error" }
+              }
+            }
+          },
+          "422": {
+            "description": "The cards read fine but can't be dealt: a card twice, or not 2 to 10 players.",
+            "content": {
+              "application/json": {
+                "schema": { "$ref": "#/components/schemas/Error" }
+              }
+            }
+          },
+          "503": {
+            "description": "Every solve worker is taken and the queue behind them is full. Try again later.",
+            "content": {
+              "application/json": {
+                "schema": { "$ref": "#/components/schemas/Error" }
+              }
+            }
+          }
+        }
+      }
+    },
+    "/openapi.json": {
+      "get": {
+        "summary": "This document.",
+        "security": [],
+        "responses": {
+          "200": { "description": "The openapi document." }
+        }
+      }
+    },
+    "/ws": {
+      "get": {
+        "summary": "Websocket upgrade. Each text message is a SolveRequest, answered with progress messages and then a result or an error.",
+        "responses": {
+          "101": { "description": "Switched to the websocket protocol." },
+          "400": { "description": "Not a websocket upgrade." },
+          "401": { "description": "The server takes api keys and the request carried none it knows." }
+        }
+      }
+    }
+  },
+  "security": [{ "bearer": [] }, { "apiKey": [] }],
+  "components": {
+    "securitySchemes": {
+      "bearer": {
+        "type": "http",
+        "scheme": "bearer",
+        "description": "Only needed when the server was started with --keys."
+      },
+      "apiKey": { "type": "apiKey", "in": "header", "name": "X-API-Key" }
+    },
+    "schemas": {
+      "SolveRequest": {
+        "type": "object",
+        "required": ["hands"],
+        "properties": {
+          "hands": {
+            "type": "array",
+            "items": { "type": "string", "example": "AhKh" },
+            "minItems": 2,
+            "maxItems": 10,
+            "description": "Hole cards, the hero's first."
+          },
+          "board": { "type": "string", "example": "7c8c9c", "default": "" },
+          "mode": { "type": "string", "enum": ["exact", "montecarlo"], "default": "exact" },
+          "samples": {
+            "type": "integer",
+            "minimum": 1,
+            "default": 1000000,
+            "description": "Runouts to sample in montecarlo mode."
+          }
+        }
+      },
+      "SolveResult": {
+        "type": "object",
+        "properties": {
+          "equity": { "type": "number" },
+          "elapsed_ms": { "type": "number" },
+          "leaves": { "type": "integer" },
+          "cache_hits": { "type": "integer" },
+          "threads": { "type": "integer" },
+          "cached": { "type": "boolean" }
+        }
+      },
+      "Error": {
+        "type": "object",
+        "properties": {
+          "code": {
+            "type": "string",
+            "enum": [
+              "bad_request",
+              "unauthorized",
+              "not_found",
+              "busy",
+              "internal",
+              "invalid_card",
+              "invalid_hand",
+              "invalid_board",
+              "duplicate_card",
+              "player_count",
+              "invalid_range",
+              "empty_range",
+              "invalid_history",
+              "invalid_query"
+            ]
+          },
+          "message": { "type": "string" }
+        }
+      }
+    }
+  }
+}
+"##;
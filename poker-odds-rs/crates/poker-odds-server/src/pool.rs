@@ -0,0 +1,41 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// solves run on a fixed set of workers fed from a bounded queue, so a
+// burst of requests waits its turn, or is turned away once the queue is
+// full, instead of every request starting solver threads of its own.
+#[derive(Clone)]
+pub struct Pool {
+    jobs: SyncSender<Job>,
+}
+
+impl Pool {
+    // `workers` solves at a time, each on all of the solver's threads, and
+    // up to `queue` more waiting.
+    pub fn new(workers: usize, queue: usize) -> Self {
+        let (jobs, rx) = mpsc::sync_channel::<Job>(queue);
+        let rx: Arc<Mutex<Receiver<Job>>> = Arc::new(Mutex::new(rx));
+        for _ in 0..workers.max(1) {
+            let rx = rx.clone();
+            thread::spawn(move || loop {
+                // the lock is let go before the job runs.
+                let job: Job = match rx.lock().unwrap().recv() {
+                    Ok(job) => job,
+                    Err(_) => return,
+                };
+                // a job that panics mustn't take its worker with it.
+                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+            });
+        }
+        Pool { jobs }
+    }
+
+    // queues `job`, false when the queue is full.
+    pub fn submit(&self, job: impl FnOnce() + Send + 'static) -> bool {
+        self.jobs.try_send(Box::new(job)).is_ok()
+    }
+}
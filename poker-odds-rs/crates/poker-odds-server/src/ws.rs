@@ -0,0 +1,134 @@
+use sha1::{Digest, Sha1};
+use std::io::{self, Read, Write};
+
+// rfc 6455, section 1.3.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+pub const OP_CONTINUATION: u8 = 0x0;
+pub const OP_TEXT: u8 = 0x1;
+pub const OP_CLOSE: u8 = 0x8;
+pub const OP_PING: u8 = 0x9;
+pub const OP_PONG: u8 = 0xA;
+
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n: u32 = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+pub fn accept_key(key: &str) -> String {
+    let mut sha = Sha1::new();
+    sha.update(key.trim().as_bytes());
+    sha.update(GUID.as_bytes());
+    base64(&sha.finalize())
+}
+
+pub struct Frame {
+    pub fin: bool,
+    pub opcode: u8,
+    pub payload: Vec<u8>,
+}
+
+pub fn read_frame(r: &mut impl Read) -> io::Result<Frame> {
+    let mut head = [0u8; 2];
+    r.read_exact(&mut head)?;
+    let fin: bool = head[0] & 0x80 != 0;
+    let opcode: u8 = head[0] & 0x0F;
+    let masked: bool = head[1] & 0x80 != 0;
+
+    let mut len: u64 = (head[1] & 0x7F) as u64;
+    if len == 126 {
+        let mut ext = [0u8; 2];
+        r.read_exact(&mut ext)?;
+        len = u16::from_be_bytes(ext) as u64;
+    } else if len == 127 {
+        let mut ext = [0u8; 8];
+        r.read_exact(&mut ext)?;
+        len = u64::from_be_bytes(ext);
+    }
+    // requests are a few hundred bytes, anything this big is garbage.
+    if len > 1 << 20 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+
+    let mut mask = [0u8; 4];
+    if masked {
+        r.read_exact(&mut mask)?;
+    }
+    let mut payload: Vec<u8> = vec![0; len as usize];
+    r.read_exact(&mut payload)?;
+    if masked {
+        for (i, b) in payload.iter_mut().enumerate() {
+            *b ^= mask[i % 4];
+        }
+    }
+    Ok(Frame {
+        fin,
+        opcode,
+        payload,
+    })
+}
+
+// server frames are never masked.
+pub fn write_frame(w: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut head: Vec<u8> = vec![0x80 | opcode];
+    if payload.len() < 126 {
+        head.push(payload.len() as u8);
+    } else if payload.len() <= u16::MAX as usize {
+        head.push(126);
+        head.extend((payload.len() as u16).to_be_bytes());
+    } else {
+        head.push(127);
+        head.extend((payload.len() as u64).to_be_bytes());
+    }
+    w.write_all(&head)?;
+    w.write_all(payload)?;
+    w.flush()
+}
+
+// reads frames until a whole message arrives, answering pings along the way.
+// None means the client closed the connection.
+pub fn read_message(stream: &mut (impl Read + Write)) -> io::Result<Option<String>> {
+    let mut message: Vec<u8> = Vec::new();
+    loop {
+        let frame: Frame = read_frame(stream)?;
+        match frame.opcode {
+            OP_TEXT | OP_CONTINUATION => {
+                message.extend(frame.payload);
+                if frame.fin {
+                    let text = String::from_utf8(message)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    return Ok(Some(text));
+                }
+            }
+            OP_PING => write_frame(stream, OP_PONG, &frame.payload)?,
+            OP_PONG => {}
+            OP_CLOSE => {
+                write_frame(stream, OP_CLOSE, &frame.payload)?;
+                return Ok(None);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "only text messages are supported",
+                ))
+            }
+        }
+    }
+}